@@ -0,0 +1,224 @@
+use crate::prelude::*;
+use crate::{command_line::build_timestamp, server::utility::floor_char_boundary};
+/// A compilation error or warning located in a source file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Diagnostic {
+  /// Stable identifier of the error or warning, such as `UNDEFINED_FUNC` or `UNUSED_NAME`.
+  pub code: DiagnosticCode,
+  /// 1-based column in characters, or 0 when the diagnostic has no location.
+  pub column: u32,
+  pub file: String,
+  /// 1-based line, or 0 when the diagnostic has no location.
+  pub line: u32,
+  pub message: String,
+  pub severity: Severity,
+}
+/// Stable identifier of a [`Diagnostic`]; new codes may be added, but the spelling returned by
+/// [`DiagnosticCode::as_str`] never changes between releases.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum DiagnosticCode {
+  ArityError,
+  DuplicateName,
+  EarlyElse,
+  ExpectedIdent,
+  ExpectedToken,
+  ExtensionError,
+  IncludeFuncNotFound,
+  IntOutOfRange,
+  /// A compiler bug rather than a problem with the source.
+  Internal,
+  InvalidChar,
+  InvalidFloat,
+  InvalidKeyword,
+  IoError,
+  OutsideError,
+  Overflow,
+  Platform,
+  RecursiveInclude,
+  TooLargeFile,
+  TooLargeShift,
+  TypeError,
+  UndefinedField,
+  UndefinedFunc,
+  UndefinedVar,
+  UnexpectedToken,
+  UnknownType,
+  UnreachableIf,
+  UnreachableWhile,
+  UnsupportedFile,
+  UnsupportedOnTarget,
+  UnsupportedType,
+  UnterminatedLiteral,
+  UnusedName,
+  UselessIfTrue,
+  UselessLiteral,
+  ZeroDivision,
+}
+impl DiagnosticCode {
+  /// The code in `SCREAMING_SNAKE_CASE`, such as `UNDEFINED_FUNC`.
+  #[must_use]
+  #[inline]
+  pub const fn as_str(self) -> &'static str {
+    match self {
+      Self::ArityError => "ARITY_ERROR",
+      Self::DuplicateName => "DUPLICATE_NAME",
+      Self::EarlyElse => "EARLY_ELSE",
+      Self::ExpectedIdent => "EXPECTED_IDENT",
+      Self::ExpectedToken => "EXPECTED_TOKEN",
+      Self::ExtensionError => "EXTENSION_ERROR",
+      Self::IncludeFuncNotFound => "INCLUDE_FUNC_NOT_FOUND",
+      Self::IntOutOfRange => "INT_OUT_OF_RANGE",
+      Self::Internal => "INTERNAL",
+      Self::InvalidChar => "INVALID_CHAR",
+      Self::InvalidFloat => "INVALID_FLOAT",
+      Self::InvalidKeyword => "INVALID_KEYWORD",
+      Self::IoError => "IO_ERROR",
+      Self::OutsideError => "OUTSIDE_ERROR",
+      Self::Overflow => "OVERFLOW",
+      Self::Platform => "PLATFORM",
+      Self::RecursiveInclude => "RECURSIVE_INCLUDE",
+      Self::TooLargeFile => "TOO_LARGE_FILE",
+      Self::TooLargeShift => "TOO_LARGE_SHIFT",
+      Self::TypeError => "TYPE_ERROR",
+      Self::UndefinedField => "UNDEFINED_FIELD",
+      Self::UndefinedFunc => "UNDEFINED_FUNC",
+      Self::UndefinedVar => "UNDEFINED_VAR",
+      Self::UnexpectedToken => "UNEXPECTED_TOKEN",
+      Self::UnknownType => "UNKNOWN_TYPE",
+      Self::UnreachableIf => "UNREACHABLE_IF",
+      Self::UnreachableWhile => "UNREACHABLE_WHILE",
+      Self::UnsupportedFile => "UNSUPPORTED_FILE",
+      Self::UnsupportedOnTarget => "UNSUPPORTED_ON_TARGET",
+      Self::UnsupportedType => "UNSUPPORTED_TYPE",
+      Self::UnterminatedLiteral => "UNTERMINATED_LITERAL",
+      Self::UnusedName => "UNUSED_NAME",
+      Self::UselessIfTrue => "USELESS_IF_TRUE",
+      Self::UselessLiteral => "USELESS_LITERAL",
+      Self::ZeroDivision => "ZERO_DIVISION",
+    }
+  }
+}
+impl fmt::Display for DiagnosticCode {
+  #[inline]
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(self.as_str())
+  }
+}
+/// Severity of a [`Diagnostic`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Severity {
+  Error,
+  Warning,
+}
+impl Jsonpiler {
+  fn build_image(&mut self, file: &str, source: String) -> ErrOR<Vec<u8>> {
+    let is_jspl = match Path::new(file).extension().map(|ext| ext.to_string_lossy()) {
+      Some(ext) if ext == "jspl" => true,
+      Some(ext) if ext == "json" => false,
+      _ => return Err(Compilation(UnsupportedFile, vec![])),
+    };
+    let parser = self.push_parser(source, file.to_owned())?;
+    let parsed = if is_jspl { parser.parse_jspl() } else { parser.parse_json() }
+      .map_err(Into::<JsonpilerErr>::into)?;
+    self.compile(parsed)?;
+    let entry = self.first_parser()?.val.dep.id;
+    let (mut insts, mut seh) = self.build_functions()?;
+    if self.release {
+      allocate_registers(&mut insts, &mut seh)?;
+      Assembler::new(vec![], entry, self.handlers, Target::Windows, Subsystem::Console, None, 0)
+        .peephole(&mut insts)?;
+    }
+    let assembler = Assembler::new(
+      take(&mut self.dlls),
+      entry,
+      self.handlers,
+      Target::Windows,
+      self.default_subsystem(),
+      None,
+      build_timestamp(true)?,
+    );
+    let image =
+      assembler.assemble(&insts, take(&mut self.data), file, seh, None, &Resources::default())?;
+    Ok(image.bytes)
+  }
+  /// Compiles `source`, named `file`, into the bytes of a Windows x64 executable.
+  ///
+  /// Imports are resolved and read through `provider`, so an in-memory provider keeps the file
+  /// system untouched. The build is deterministic, and must run on a fresh `Jsonpiler`.
+  #[inline]
+  pub fn compile_source<P>(
+    &mut self,
+    file: &str,
+    source: String,
+    release: bool,
+    provider: P,
+  ) -> Result<Vec<u8>, Vec<Diagnostic>>
+  where
+    P: SourceProvider + 'static,
+  {
+    self.release = release;
+    self.set_source_provider(provider);
+    self.build_image(file, source).map_err(|err| {
+      let mut diagnostics = vec![self.diagnostic(&err)];
+      diagnostics.extend(self.warnings());
+      diagnostics
+    })
+  }
+  pub(crate) fn diagnostic(&self, err: &JsonpilerErr) -> Diagnostic {
+    let message = err.to_string();
+    match err.pos_vec().first() {
+      Some(pos) => self.located(*pos, err.err_code(), message, Severity::Error),
+      None => Diagnostic {
+        code: err.err_code(),
+        column: 0,
+        file: self.parsers.first().map(|parser| parser.val.file.clone()).unwrap_or_default(),
+        line: 0,
+        message,
+        severity: Severity::Error,
+      },
+    }
+  }
+  fn located(
+    &self,
+    pos: Position,
+    code: DiagnosticCode,
+    message: String,
+    severity: Severity,
+  ) -> Diagnostic {
+    let parser = &self.parsers[pos.file as usize].val;
+    let offset = floor_char_boundary(&parser.text, pos.offset as usize);
+    let line_start =
+      parser.text.get(..offset).unwrap_or_default().rfind('\n').map_or(0, |idx| idx + 1);
+    let column = parser.text.get(line_start..offset).unwrap_or_default().chars().count() + 1;
+    Diagnostic {
+      code,
+      column: u32::try_from(column).unwrap_or(u32::MAX),
+      file: parser.file.clone(),
+      line: pos.line + 1,
+      message,
+      severity,
+    }
+  }
+  /// Reads the input, `import`ed files and, in the language server, unsaved buffers through
+  /// `provider` instead of the file system.
+  #[inline]
+  pub fn set_source_provider<P: SourceProvider + 'static>(&mut self, provider: P) {
+    self.provider = Box::new(provider);
+  }
+  /// Warnings reported while compiling, in the order they were found.
+  #[must_use]
+  #[inline]
+  pub fn warnings(&self) -> Vec<Diagnostic> {
+    self
+      .parsers
+      .iter()
+      .flat_map(|parser| &parser.val.warns)
+      .map(|warn| {
+        self.located(warn.pos, warn.val.err_code(), warn.val.to_string(), Severity::Warning)
+      })
+      .collect()
+  }
+}
@@ -1,21 +1,51 @@
+mod coff;
+mod decode;
+mod disasm;
 pub(crate) mod disp;
+mod elf;
 mod encode;
 pub(crate) mod inst;
+mod listing;
 pub(crate) mod ops;
+mod pdb;
 mod pe;
+mod peephole;
+mod regalloc;
 pub(crate) mod register;
 pub(crate) mod rm;
+mod rsrc;
 pub(crate) mod section;
 mod sizeof;
+mod srcmap;
 mod utility;
-use self::utility::*;
+pub(crate) use self::{
+  disasm::disassemble,
+  pdb::{PdbFunc, write_pdb},
+  regalloc::allocate_registers,
+  rsrc::Resources,
+};
+use self::{pdb::pdb_guid, rsrc::build_rsrc, utility::*};
 use crate::prelude::*;
 pub(crate) struct Assembler {
+  debug_dir: Option<usize>,
   dlls: Vec<Dll>,
   handlers: Handlers,
   labels: HashMap<u32, (Section, u32)>,
+  pdb: Option<String>,
   root_id: LabelId,
   rva: [u32; NUMBER_OF_SECTIONS as usize],
+  src_map: Option<LabelId>,
+  subsystem: Subsystem,
+  target: Target,
+  timestamp: u32,
+}
+#[derive(Debug, Default)]
+pub(crate) struct ImageInfo {
+  pub bytes: Vec<u8>,
+  pub functions: Vec<(LabelId, u32, u32)>,
+  pub guid: [u8; 16],
+  pub ranges: SourceMap,
+  pub sections: Vec<SectionHeader>,
 }
 impl Assembler {
   pub(crate) fn assemble(
@@ -24,42 +54,39 @@ impl Assembler {
     data_insts: Vec<DataLbl>,
     file: &str,
     mut seh: Seh,
-  ) -> ErrOR<()> {
+    exports: Option<&[(String, LabelId)]>,
+    resources: &Resources,
+  ) -> ErrOR<ImageInfo> {
     self.labels.clear();
-    let mut text_size: u32 = 0;
-    let mut data = vec![];
-    let mut rdata = vec![];
-    let mut bss_v_size: u32 = 0;
-    for data_inst in data_insts {
-      self.encode_data_lbl(data_inst, &mut data, &mut rdata, &mut bss_v_size)?;
-    }
-    #[cfg(debug_assertions)]
-    let mut validate_vec = vec![];
-    for inst in insts.iter().flatten() {
-      if let Lbl(idx) = inst
-        && self.labels.insert(*idx, (Text, text_size)).is_some()
-      {
-        return Err(Internal(DuplicateLabel));
-      }
-      let inst_size = self.sizeof_inst(inst, text_size)?;
-      text_size += inst_size;
-      #[cfg(debug_assertions)]
-      validate_vec.push(inst_size);
-    }
-    seh.retain(|seh_elem| self.labels.contains_key(&seh_elem.0));
+    let (data, mut rdata, bss_v_size) = self.layout_data(data_insts)?;
+    let (text_size, sizes) = self.layout_text(insts)?;
     self.rva[Text as usize] = SECTION_ALIGNMENT;
-    let (mut pdata, stack_sizes) = self.build_pdata(&mut seh)?;
+    let ranges = self.source_ranges(insts, &sizes);
+    let functions = self.function_ranges(insts, &sizes);
+    self.reserve_src_map(&mut rdata, ranges.len())?;
+    self.reserve_debug_dir(&mut rdata)?;
     let base_h = SectionHeader::from(Text, HEADERS_SIZE, 0, r_size(HEADERS_SIZE)?, 0);
     let text_h = base_h.next(Text, text_size)?;
     let data_h = text_h.next(Data, len_u32(&data)?)?;
     let rdata_h = data_h.next(RData, len_u32(&rdata)?)?;
+    self.rva[Data as usize] = data_h.v_addr;
+    self.rva[RData as usize] = rdata_h.v_addr;
+    self.fill_src_map(&mut rdata, &ranges)?;
+    if self.target == Target::Linux {
+      let bss_h = SectionHeader::from(Bss, bss_v_size, rdata_h.next_v_addr()?, 0, 0);
+      self.rva[Bss as usize] = bss_h.v_addr;
+      let text = self.encode_text(insts, &sizes)?;
+      let bytes =
+        self.link_elf(&[(text, text_h), (data, data_h), (rdata, rdata_h), (vec![], bss_h)])?;
+      return Ok(ImageInfo { bytes, functions, ranges, ..ImageInfo::default() });
+    }
+    seh.retain(|seh_elem| self.labels.contains_key(&seh_elem.0));
+    let (mut pdata, frames) = self.build_pdata(&mut seh)?;
     let pdata_h = rdata_h.next(PData, len_u32(&pdata)?)?;
     let xdata_v_addr = pdata_h.next_v_addr()?;
-    let xdata = self.build_xdata(xdata_v_addr, &mut pdata, &stack_sizes)?;
+    let (xdata, _) = self.build_xdata(xdata_v_addr, &mut pdata, &frames)?;
     let xdata_h = pdata_h.next(XData, len_u32(&xdata)?)?;
     let bss_h = SectionHeader::from(Bss, bss_v_size, xdata_h.next_v_addr()?, 0, 0);
-    self.rva[Data as usize] = data_h.v_addr;
-    self.rva[RData as usize] = rdata_h.v_addr;
     self.rva[PData as usize] = pdata_h.v_addr;
     self.rva[XData as usize] = xdata_h.v_addr;
     self.rva[Bss as usize] = bss_h.v_addr;
@@ -72,6 +99,43 @@ impl Assembler {
       r_size(len_u32(&idata)?)?,
       xdata_h.next_r_ptr(),
     );
+    let text = self.encode_text(insts, &sizes)?;
+    let guid = pdb_guid(&[&text, &data, &rdata, self.pdb.as_deref().unwrap_or("").as_bytes()]);
+    self.fill_debug_dir(&mut rdata, rdata_h, guid)?;
+    let reloc = self.build_reloc();
+    let reloc_h = idata_h.next(Reloc, len_u32(&reloc)?)?;
+    let mut sections = vec![
+      (text, text_h),
+      (data, data_h),
+      (rdata, rdata_h),
+      (pdata, pdata_h),
+      (xdata, xdata_h),
+      (vec![], bss_h),
+      (idata, idata_h),
+      (reloc, reloc_h),
+    ];
+    let mut last_h = reloc_h;
+    if !resources.is_empty() {
+      let rsrc = build_rsrc(resources, last_h.next_v_addr()?, exports.is_some())?;
+      last_h = last_h.next(Rsrc, len_u32(&rsrc)?)?;
+      sections.push((rsrc, last_h));
+    }
+    if let Some(exported) = exports {
+      let dll_name = Path::new(file).with_extension("dll");
+      let file_name = dll_name.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+      let edata = self.build_edata(exported, &file_name, last_h.next_v_addr()?)?;
+      let edata_h = last_h.next(EData, len_u32(&edata)?)?;
+      sections.push((edata, edata_h));
+    }
+    let headers = sections.iter().map(|(_, header)| *header).collect();
+    let bytes = self.link(&sections)?;
+    Ok(ImageInfo { bytes, functions, guid, ranges, sections: headers })
+  }
+  fn encode_text(
+    &mut self,
+    insts: &[Vec<Inst>],
+    #[cfg_attr(not(debug_assertions), expect(unused_variables))] sizes: &[u32],
+  ) -> ErrOR<Vec<u8>> {
     let mut text = vec![];
     #[cfg(not(debug_assertions))]
     for inst in insts.iter().flatten() {
@@ -81,7 +145,7 @@ impl Assembler {
     #[cfg(debug_assertions)]
     {
       let mut is_invalid_inst = false;
-      for (inst, size) in insts.iter().flatten().zip(validate_vec) {
+      for (inst, &size) in insts.iter().flatten().zip(sizes) {
         let bytes = self.encode_inst(len_u32(&text)?, inst)?;
         if len_u32(&bytes)? != size {
           is_invalid_inst = true;
@@ -97,17 +161,44 @@ impl Assembler {
         eprintln!("{ISSUE}INST_SIZE`");
       }
     };
-    self.link(
-      &[
-        (text, text_h),
-        (data, data_h),
-        (rdata, rdata_h),
-        (pdata, pdata_h),
-        (xdata, xdata_h),
-        (vec![], bss_h),
-        (idata, idata_h),
-      ],
-      file,
-    )
+    Ok(text)
+  }
+  fn layout_data(&mut self, data_insts: Vec<DataLbl>) -> ErrOR<(Vec<u8>, Vec<u8>, u32)> {
+    let mut data = vec![];
+    let mut rdata = vec![];
+    let mut bss_v_size: u32 = 0;
+    for data_inst in data_insts {
+      self.encode_data_lbl(data_inst, &mut data, &mut rdata, &mut bss_v_size)?;
+    }
+    Ok((data, rdata, bss_v_size))
+  }
+  fn layout_text(&mut self, insts: &[Vec<Inst>]) -> ErrOR<(u32, Vec<u32>)> {
+    self.labels.retain(|_, (sect, _)| *sect != Text);
+    let mut sizes: Vec<u32> = vec![];
+    loop {
+      let mut text_labels = HashMap::new();
+      let mut text_size: u32 = 0;
+      let mut prev_offset: u32 = 0;
+      let mut next_sizes = Vec::with_capacity(sizes.len());
+      for (idx, inst) in insts.iter().flatten().enumerate() {
+        if let Lbl(id) = inst
+          && text_labels.insert(*id, (Text, text_size)).is_some()
+        {
+          return Err(Internal(DuplicateLabel));
+        }
+        let inst_size = match sizes.get(idx) {
+          Some(&prev_size) => self.sizeof_relaxed(inst, prev_offset, prev_size)?,
+          None => self.sizeof_inst(inst, prev_offset)?,
+        };
+        prev_offset += sizes.get(idx).copied().unwrap_or(inst_size);
+        text_size += inst_size;
+        next_sizes.push(inst_size);
+      }
+      self.labels.extend(text_labels);
+      if next_sizes == sizes {
+        return Ok((text_size, sizes));
+      }
+      sizes = next_sizes;
+    }
   }
 }
@@ -0,0 +1,201 @@
+use super::pe::sizeof_unwind_info;
+use crate::prelude::*;
+const IMAGE_REL_AMD64_ADDR32NB: u16 = 3;
+const IMAGE_REL_AMD64_REL32: u16 = 4;
+const OBJ_SECTIONS: [Section; 6] = [Text, Data, RData, PData, XData, Bss];
+enum RipRef {
+  Import(Api),
+  Label(LabelId, u32),
+}
+impl Assembler {
+  pub(crate) fn assemble_obj(
+    mut self,
+    insts: &[Vec<Inst>],
+    data_insts: Vec<DataLbl>,
+    path: &Path,
+    mut seh: Seh,
+    exports: &[(String, LabelId)],
+  ) -> ErrOR<()> {
+    self.labels.clear();
+    let (data, mut rdata, bss_size) = self.layout_data(data_insts)?;
+    self.reserve_src_map(&mut rdata, 0)?;
+    let (text_size, sizes) = self.layout_text(insts)?;
+    seh.retain(|seh_elem| self.labels.contains_key(&seh_elem.0));
+    let mut xdata_size = 0;
+    for (_, _, _, saved) in &seh {
+      xdata_size += sizeof_unwind_info(saved.len())?;
+    }
+    let mut v_addr = SECTION_ALIGNMENT;
+    for (sect, size) in [(Text, text_size), (Data, len_u32(&data)?), (RData, len_u32(&rdata)?)]
+      .into_iter()
+      .chain([(PData, len_u32(&seh)? * 12), (XData, xdata_size), (Bss, bss_size)])
+    {
+      self.rva[sect as usize] = v_addr;
+      v_addr += align_up_u32(size, SECTION_ALIGNMENT)?;
+    }
+    self.rva[IData as usize] = v_addr;
+    let mut imports = BTreeMap::new();
+    let mut symbol_idx = len_u32(&OBJ_SECTIONS)? * 2 + len_u32(exports)?;
+    for (dll_idx, (_, funcs)) in self.dlls.iter().enumerate() {
+      for func_idx in 0..funcs.len() {
+        imports.insert((u32::try_from(dll_idx)?, u32::try_from(func_idx)?), symbol_idx);
+        symbol_idx += 1;
+      }
+    }
+    let mut text = self.encode_text(insts, &sizes)?;
+    let mut relocs: [Vec<(u32, u32, u16)>; 6] = Default::default();
+    let mut offset = 0;
+    for (inst, size) in insts.iter().flatten().zip(sizes) {
+      match rip_ref(inst) {
+        Some(RipRef::Import(api)) => {
+          text[offset as usize + 2..offset as usize + 6].copy_from_slice(&[0; 4]);
+          relocs[0].push((offset + 2, imports[&api], IMAGE_REL_AMD64_REL32));
+        }
+        Some(RipRef::Label(id, imm_len)) => {
+          let (sect, target) = *self.labels.get(&id).ok_or(Internal(UnknownLabel))?;
+          if sect != Text {
+            let field = (offset + size - 4 - imm_len) as usize;
+            text[field..field + 4].copy_from_slice(&target.wrapping_sub(imm_len).to_le_bytes());
+            relocs[0].push((offset + size - 4 - imm_len, sect as u32 * 2, IMAGE_REL_AMD64_REL32));
+          }
+        }
+        None => (),
+      }
+      offset += size;
+    }
+    let (mut pdata, frames) = self.build_pdata(&mut seh)?;
+    let (mut xdata, handler_fields) =
+      self.build_xdata(self.rva[XData as usize], &mut pdata, &frames)?;
+    for (entry, handler_field) in (0..).zip(handler_fields) {
+      for (field, sect) in [(0, Text), (4, Text), (8, XData)] {
+        self.rebase(&mut pdata, entry * 12 + field, sect, &mut relocs[PData as usize])?;
+      }
+      self.rebase(&mut xdata, handler_field, Text, &mut relocs[XData as usize])?;
+    }
+    let raw = [text, data, rdata, pdata, xdata, vec![]];
+    let mut strings = vec![];
+    let mut symbols = vec![];
+    for (idx, sect) in OBJ_SECTIONS.iter().enumerate() {
+      let number = i16::try_from(idx + 1)?;
+      let size = if *sect == Bss { bss_size } else { len_u32(&raw[idx])? };
+      symbols.push(symbol(sect.name(), 0, number, (0, 3, 1)));
+      let mut aux = [0; 18];
+      aux[..4].copy_from_slice(&size.to_le_bytes());
+      aux[4..6].copy_from_slice(&u16::try_from(relocs[idx].len())?.to_le_bytes());
+      aux[12..14].copy_from_slice(&number.cast_unsigned().to_le_bytes());
+      symbols.push(aux);
+    }
+    for (name, id) in exports {
+      let name_field = symbol_name(name, &mut strings)?;
+      symbols.push(symbol(
+        name_field,
+        self.get_rva(*id)? - self.rva[Text as usize],
+        1,
+        (0x20, 2, 0),
+      ));
+    }
+    for (dll_idx, func_idx) in imports.keys() {
+      let name = format!("__imp_{}", self.dlls[*dll_idx as usize].1[*func_idx as usize]);
+      symbols.push(symbol(symbol_name(&name, &mut strings)?, 0, 0, (0, 2, 0)));
+    }
+    let headers_size = 20 + 40 * len_u32(&OBJ_SECTIONS)?;
+    let mut out = vec![];
+    let mut section_headers = vec![];
+    let mut body = vec![];
+    for (idx, sect) in OBJ_SECTIONS.iter().enumerate() {
+      let raw_ptr = if raw[idx].is_empty() { 0 } else { headers_size + len_u32(&body)? };
+      body.extend_from_slice(&raw[idx]);
+      let reloc_ptr = if relocs[idx].is_empty() { 0 } else { headers_size + len_u32(&body)? };
+      for (field, target, kind) in &relocs[idx] {
+        extend!(body, field.to_le_bytes(), target.to_le_bytes(), kind.to_le_bytes());
+      }
+      let size = if *sect == Bss { bss_size } else { len_u32(&raw[idx])? };
+      let align = if matches!(sect, PData | XData) { 0x30_0000 } else { 0x50_0000 };
+      extend!(
+        section_headers,
+        sect.name(),
+        [0; 8],
+        size.to_le_bytes(),
+        raw_ptr.to_le_bytes(),
+        reloc_ptr.to_le_bytes(),
+        [0; 4],
+        u16::try_from(relocs[idx].len())?.to_le_bytes(),
+        [0; 2],
+        (sect.characteristics() | align).to_le_bytes(),
+      );
+    }
+    extend!(
+      out,
+      0x8664u16.to_le_bytes(),
+      u16::try_from(OBJ_SECTIONS.len())?.to_le_bytes(),
+      [0; 4],
+      (headers_size + len_u32(&body)?).to_le_bytes(),
+      len_u32(&symbols)?.to_le_bytes(),
+      [0; 4],
+      section_headers,
+      body,
+      symbols.concat(),
+      (len_u32(&strings)? + 4).to_le_bytes(),
+      strings,
+    );
+    fs::write(path, out)?;
+    Ok(())
+  }
+  fn rebase(
+    &self,
+    bytes: &mut [u8],
+    field: u32,
+    sect: Section,
+    relocs: &mut Vec<(u32, u32, u16)>,
+  ) -> ErrOR<()> {
+    let range = field as usize..field as usize + 4;
+    let mut le_bytes = [0; 4];
+    le_bytes.copy_from_slice(&bytes[range.clone()]);
+    let rva = u32::from_le_bytes(le_bytes);
+    bytes[range].copy_from_slice(&(rva - self.rva[sect as usize]).to_le_bytes());
+    relocs.push((field, sect as u32 * 2, IMAGE_REL_AMD64_ADDR32NB));
+    Ok(())
+  }
+}
+fn rip_ref(inst: &Inst) -> Option<RipRef> {
+  if let CallApi(api) | CallApiCheck(api) = inst {
+    Some(RipRef::Import(*api))
+  } else if let LeaRM(_, Global(id))
+  | MovSxDRMd(_, Global(id))
+  | IncMd(Global(id))
+  | DecMd(Global(id))
+  | MovSdM(_, Global(id))
+  | MovMSd(Global(id), _)
+  | MovBB((Reg(_), Mem(Global(id))) | (Mem(Global(id)), Reg(_)))
+  | MovDD((Reg(_), Mem(Global(id))) | (Mem(Global(id)), Reg(_)))
+  | MovQQ((Reg(_), Mem(Global(id))) | (Mem(Global(id)), Reg(_))) = inst
+  {
+    Some(RipRef::Label(*id, 0))
+  } else if let MovBB((Mem(Global(id)), Imm(_))) = inst {
+    Some(RipRef::Label(*id, 1))
+  } else if let MovDD((Mem(Global(id)), Imm(_))) = inst {
+    Some(RipRef::Label(*id, 4))
+  } else {
+    None
+  }
+}
+fn symbol(name: [u8; 8], value: u32, section: i16, (kind, class, aux): (u16, u8, u8)) -> [u8; 18] {
+  let mut sym = [0; 18];
+  sym[..8].copy_from_slice(&name);
+  sym[8..12].copy_from_slice(&value.to_le_bytes());
+  sym[12..14].copy_from_slice(&section.to_le_bytes());
+  sym[14..16].copy_from_slice(&kind.to_le_bytes());
+  sym[16] = class;
+  sym[17] = aux;
+  sym
+}
+fn symbol_name(name: &str, strings: &mut Vec<u8>) -> ErrOR<[u8; 8]> {
+  let mut field = [0; 8];
+  if name.len() <= 8 {
+    field[..name.len()].copy_from_slice(name.as_bytes());
+  } else {
+    field[4..].copy_from_slice(&(len_u32(strings)? + 4).to_le_bytes());
+    extend!(strings, name.as_bytes(), [0]);
+  }
+  Ok(field)
+}
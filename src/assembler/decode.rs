@@ -0,0 +1,557 @@
+use super::listing::{ptr, reg_name, signed_hex};
+use crate::prelude::*;
+pub(crate) struct Decoded {
+  pub len: u32,
+  pub target: Option<u32>,
+  pub text: String,
+}
+pub(crate) struct Decoder<'a> {
+  code: &'a [u8],
+  names: &'a HashMap<u32, String>,
+  rva: u32,
+  sections: &'a [(String, u32, u32)],
+}
+enum Arg {
+  Imm(u64),
+  Mem(u8, Mem),
+  Reg(String),
+  Rel(i32),
+}
+enum Mem {
+  Base(u8, Option<(u8, u8)>, i32),
+  Rip(i32),
+}
+#[derive(Default)]
+struct Cursor {
+  op_size: bool,
+  pos: usize,
+  rep: Option<&'static str>,
+  rex: Option<u8>,
+}
+type Decoding = Option<(String, Vec<Arg>)>;
+impl Cursor {
+  fn b(&self) -> u8 {
+    (self.rex.unwrap_or(0) & 1) << 3
+  }
+  fn gpr(&self, num: u8, size: u8) -> Arg {
+    if size == 1 && self.rex.is_none() && (4..8).contains(&num) {
+      Arg::Reg(["ah", "ch", "dh", "bh"][usize::from(num - 4)].to_owned())
+    } else {
+      Arg::Reg(reg_name_num(num, size))
+    }
+  }
+  fn is_f2(&self) -> bool {
+    self.rep == Some("repne")
+  }
+  fn r(&self) -> u8 {
+    (self.rex.unwrap_or(0) & 4) << 1
+  }
+  fn size(&self) -> u8 {
+    if self.w() {
+      8
+    } else if self.op_size {
+      2
+    } else {
+      4
+    }
+  }
+  fn w(&self) -> bool {
+    self.rex.unwrap_or(0) & 8 != 0
+  }
+  fn x(&self) -> u8 {
+    (self.rex.unwrap_or(0) & 2) << 2
+  }
+  fn xmm(&self, num: u8) -> Arg {
+    Arg::Reg(format!("xmm{}", num | self.r()))
+  }
+}
+impl<'a> Decoder<'a> {
+  fn alu(&self, cur: &mut Cursor, op: u8) -> Decoding {
+    let size = if op & 1 == 0 { 1 } else { cur.size() };
+    let (num, rm) = self.modrm(cur, size)?;
+    let mnemonic = match op & 0xF8 {
+      _ if op < 0x40 && op & 7 > 3 => return None,
+      0x00 => "add",
+      0x08 => "or",
+      0x10 => "adc",
+      0x18 => "sbb",
+      0x20 => "and",
+      0x28 => "sub",
+      0x30 => "xor",
+      0x38 => "cmp",
+      _ if op < 0x88 => "test",
+      _ => "mov",
+    };
+    if op & 2 == 0 {
+      Some((mnemonic.to_owned(), vec![rm, cur.gpr(num, size)]))
+    } else {
+      Some((mnemonic.to_owned(), vec![cur.gpr(num, size), rm]))
+    }
+  }
+  fn byte(&self, cur: &mut Cursor) -> Option<u8> {
+    let byte = *self.code.get(cur.pos)?;
+    cur.pos += 1;
+    Some(byte)
+  }
+  fn bytes<const N: usize>(&self, cur: &mut Cursor) -> Option<[u8; N]> {
+    let bytes = self.code.get(cur.pos..cur.pos + N)?.try_into().ok()?;
+    cur.pos += N;
+    Some(bytes)
+  }
+  pub(crate) fn decode(&self, offset: usize) -> Decoded {
+    let mut cur = Cursor { pos: offset, ..Cursor::default() };
+    let Some((mnemonic, args)) = self.prefixed(&mut cur) else {
+      let byte = self.code.get(offset).copied().unwrap_or(0);
+      return Decoded { len: 1, target: None, text: format!("db {byte:#04x}") };
+    };
+    let end = self.rva + u32::try_from(cur.pos).unwrap_or(u32::MAX);
+    let len = u32::try_from(cur.pos - offset).unwrap_or(0);
+    let target = args.iter().find_map(|arg| match arg {
+      Arg::Rel(rel) => Some(end.wrapping_add_signed(*rel)),
+      Arg::Imm(_) | Arg::Mem(..) | Arg::Reg(_) => None,
+    });
+    let operands = args.into_iter().map(|arg| self.format_arg(arg, end)).collect::<Vec<_>>();
+    let text =
+      if operands.is_empty() { mnemonic } else { format!("{mnemonic} {}", operands.join(", ")) };
+    Decoded { len, target, text }
+  }
+  fn escape(&self, cur: &mut Cursor) -> Decoding {
+    let op = self.byte(cur)?;
+    let size = cur.size();
+    Some(match op {
+      0x05 => ("syscall".to_owned(), vec![]),
+      0x10 if cur.is_f2() => {
+        let (num, rm) = self.modrm_xmm(cur)?;
+        ("movsd".to_owned(), vec![cur.xmm(num), rm])
+      }
+      0x11 if cur.is_f2() => {
+        let (num, rm) = self.modrm_xmm(cur)?;
+        ("movsd".to_owned(), vec![rm, cur.xmm(num)])
+      }
+      0x2A if cur.is_f2() => {
+        let (num, rm) = self.modrm(cur, size)?;
+        ("cvtsi2sd".to_owned(), vec![cur.xmm(num), rm])
+      }
+      0x2C if cur.is_f2() => {
+        let (num, rm) = self.modrm_xmm(cur)?;
+        ("cvttsd2si".to_owned(), vec![cur.gpr(num, size), rm])
+      }
+      0x2E if cur.op_size => {
+        let (num, rm) = self.modrm_xmm(cur)?;
+        ("ucomisd".to_owned(), vec![cur.xmm(num), rm])
+      }
+      0x31 => ("rdtsc".to_owned(), vec![]),
+      0x40..=0x4F => {
+        let (num, rm) = self.modrm(cur, size)?;
+        (format!("cmov{}", cc_name(op)), vec![cur.gpr(num, size), rm])
+      }
+      0x51 | 0x58 | 0x59 | 0x5C | 0x5E if cur.is_f2() => {
+        let (num, rm) = self.modrm_xmm(cur)?;
+        let mnemonic = match op {
+          0x51 => "sqrtsd",
+          0x58 => "addsd",
+          0x59 => "mulsd",
+          0x5C => "subsd",
+          _ => "divsd",
+        };
+        (mnemonic.to_owned(), vec![cur.xmm(num), rm])
+      }
+      0x6E if cur.op_size => {
+        let (num, rm) = self.modrm(cur, size)?;
+        ("movq".to_owned(), vec![cur.xmm(num), rm])
+      }
+      0x7E if cur.op_size => {
+        let (num, rm) = self.modrm(cur, size)?;
+        ("movq".to_owned(), vec![rm, cur.xmm(num)])
+      }
+      0x80..=0x8F => {
+        let rel = i32::from_le_bytes(self.bytes(cur)?);
+        (format!("j{}", cc_name(op)), vec![Arg::Rel(rel)])
+      }
+      0x90..=0x9F => {
+        let (_, rm) = self.modrm(cur, 1)?;
+        (format!("set{}", cc_name(op)), vec![rm])
+      }
+      0xAF => {
+        let (num, rm) = self.modrm(cur, size)?;
+        ("imul".to_owned(), vec![cur.gpr(num, size), rm])
+      }
+      0xBA => {
+        let (num, rm) = self.modrm(cur, size)?;
+        let imm = self.byte(cur)?;
+        let mnemonic = ["", "", "", "", "bt", "bts", "btr", "btc"][usize::from(num & 7)];
+        if mnemonic.is_empty() {
+          return None;
+        }
+        (mnemonic.to_owned(), vec![rm, Arg::Imm(u64::from(imm))])
+      }
+      _ => return None,
+    })
+  }
+  fn format_arg(&self, arg: Arg, end: u32) -> String {
+    match arg {
+      Arg::Imm(imm) => format!("{imm:#x}"),
+      Arg::Mem(size, Mem::Base(base, index_opt, disp)) => {
+        let index = index_opt.map_or_else(String::new, |(index_num, scale)| {
+          let factor = if scale == 1 { String::new() } else { format!("*{scale}") };
+          format!(" + {}{factor}", reg_name_num(index_num, 8))
+        });
+        format!("{}[{}{index}{}]", ptr_or_none(size), reg_name_num(base, 8), signed_hex(disp))
+      }
+      Arg::Mem(size, Mem::Rip(disp)) => {
+        format!("{}[{}]", ptr_or_none(size), self.name(end.wrapping_add_signed(disp)))
+      }
+      Arg::Reg(reg) => reg,
+      Arg::Rel(rel) => self.name(end.wrapping_add_signed(rel)),
+    }
+  }
+  fn group(&self, cur: &mut Cursor, op: u8) -> Decoding {
+    let size = if op & 1 == 0 { 1 } else { cur.size() };
+    let (num, _) = self.peek_modrm(cur)?;
+    let ext = usize::from(num & 7);
+    let mnemonic = match op {
+      0x81 => ["add", "or", "adc", "sbb", "and", "sub", "xor", "cmp"][ext],
+      0xC1 | 0xD1 | 0xD3 => ["rol", "ror", "rcl", "rcr", "shl", "shr", "sal", "sar"][ext],
+      0xC6 | 0xC7 if ext == 0 => "mov",
+      0xF6 | 0xF7 => ["", "", "not", "neg", "mul", "imul", "div", "idiv"][ext],
+      0xFF => ["inc", "dec", "call", "", "", "", "", ""][ext],
+      _ => "",
+    };
+    if mnemonic.is_empty() {
+      return None;
+    }
+    let (_, rm) = self.modrm(cur, if op == 0xFF && ext == 2 { 8 } else { size })?;
+    let args = match op {
+      0x81 | 0xC7 => vec![rm, Arg::Imm(u64::from(u32::from_le_bytes(self.bytes(cur)?)))],
+      0xC1 | 0xC6 => vec![rm, Arg::Imm(u64::from(self.byte(cur)?))],
+      0xD1 => vec![rm, Arg::Imm(1)],
+      0xD3 => vec![rm, Arg::Reg("cl".to_owned())],
+      _ => vec![rm],
+    };
+    Some((mnemonic.to_owned(), args))
+  }
+  fn memory(&self, cur: &mut Cursor) -> Option<Mem> {
+    let modrm = self.byte(cur)?;
+    let mode = modrm >> 6;
+    let (base, index) = if modrm & 7 == 4 {
+      let sib = self.byte(cur)?;
+      let index_num = ((sib >> 3) & 7) | cur.x();
+      ((sib & 7) | cur.b(), (index_num != 4).then_some((index_num, 1 << (sib >> 6))))
+    } else if mode == 0 && modrm & 7 == 5 {
+      return Some(Mem::Rip(i32::from_le_bytes(self.bytes(cur)?)));
+    } else {
+      ((modrm & 7) | cur.b(), None)
+    };
+    let disp = match mode {
+      1 => i32::from(i8::from_le_bytes(self.bytes(cur)?)),
+      2 => i32::from_le_bytes(self.bytes(cur)?),
+      _ => 0,
+    };
+    Some(Mem::Base(base, index, disp))
+  }
+  fn modrm(&self, cur: &mut Cursor, size: u8) -> Option<(u8, Arg)> {
+    let (num, is_reg) = self.peek_modrm(cur)?;
+    if is_reg {
+      let modrm = self.byte(cur)?;
+      Some((num, cur.gpr((modrm & 7) | cur.b(), size)))
+    } else {
+      Some((num, Arg::Mem(size, self.memory(cur)?)))
+    }
+  }
+  fn modrm_xmm(&self, cur: &mut Cursor) -> Option<(u8, Arg)> {
+    let (num, is_reg) = self.peek_modrm(cur)?;
+    if is_reg {
+      let modrm = self.byte(cur)?;
+      Some((num, Arg::Reg(format!("xmm{}", (modrm & 7) | cur.b()))))
+    } else {
+      Some((num, Arg::Mem(8, self.memory(cur)?)))
+    }
+  }
+  pub(crate) fn name(&self, rva: u32) -> String {
+    if let Some(name) = self.names.get(&rva) {
+      return name.clone();
+    }
+    for (name, start, size) in self.sections {
+      if rva == *start {
+        return name.clone();
+      }
+      if (*start..start + size).contains(&rva) {
+        return format!("{name}+{:#x}", rva - start);
+      }
+    }
+    format!("{rva:#x}")
+  }
+  pub(crate) fn new(
+    code: &'a [u8],
+    rva: u32,
+    names: &'a HashMap<u32, String>,
+    sections: &'a [(String, u32, u32)],
+  ) -> Self {
+    Decoder { code, names, rva, sections }
+  }
+  fn one_byte(&self, cur: &mut Cursor, op: u8) -> Decoding {
+    let size = cur.size();
+    Some(match op {
+      0x00..=0x3F | 0x84 | 0x85 | 0x88..=0x8B => return self.alu(cur, op),
+      0x50..=0x5F => {
+        let mnemonic = if op < 0x58 { "push" } else { "pop" };
+        (mnemonic.to_owned(), vec![cur.gpr((op & 7) | cur.b(), 8)])
+      }
+      0x63 => {
+        let (num, rm) = self.modrm(cur, 4)?;
+        ("movsxd".to_owned(), vec![cur.gpr(num, size), rm])
+      }
+      0x70..=0x7F => {
+        let rel = i8::from_le_bytes(self.bytes(cur)?);
+        (format!("j{}", cc_name(op)), vec![Arg::Rel(i32::from(rel))])
+      }
+      0x8D => {
+        let (num, rm) = self.modrm(cur, 0)?;
+        ("lea".to_owned(), vec![cur.gpr(num, size), rm])
+      }
+      0x99 => ((if cur.w() { "cqo" } else { "cdq" }).to_owned(), vec![]),
+      0xA4 | 0xAE => {
+        let mnemonic = if op == 0xA4 { "movsb" } else { "scasb" };
+        (cur.rep.map_or_else(|| mnemonic.to_owned(), |rep| format!("{rep} {mnemonic}")), vec![])
+      }
+      0xB0..=0xB7 => {
+        let imm = self.byte(cur)?;
+        ("mov".to_owned(), vec![cur.gpr((op & 7) | cur.b(), 1), Arg::Imm(u64::from(imm))])
+      }
+      0xB8..=0xBF => {
+        let imm = if cur.w() {
+          u64::from_le_bytes(self.bytes(cur)?)
+        } else {
+          u64::from(u32::from_le_bytes(self.bytes(cur)?))
+        };
+        ("mov".to_owned(), vec![cur.gpr((op & 7) | cur.b(), size), Arg::Imm(imm)])
+      }
+      0xC3 => ("ret".to_owned(), vec![]),
+      0xE8 | 0xE9 => {
+        let rel = i32::from_le_bytes(self.bytes(cur)?);
+        ((if op == 0xE8 { "call" } else { "jmp" }).to_owned(), vec![Arg::Rel(rel)])
+      }
+      0xEB => {
+        let rel = i8::from_le_bytes(self.bytes(cur)?);
+        ("jmp".to_owned(), vec![Arg::Rel(i32::from(rel))])
+      }
+      0xFC => ("cld".to_owned(), vec![]),
+      0x81 | 0xC1 | 0xC6 | 0xC7 | 0xD1 | 0xD3 | 0xF6 | 0xF7 | 0xFF => return self.group(cur, op),
+      _ => return None,
+    })
+  }
+  fn peek_modrm(&self, cur: &Cursor) -> Option<(u8, bool)> {
+    let modrm = *self.code.get(cur.pos)?;
+    Some((((modrm >> 3) & 7) | cur.r(), modrm >> 6 == 3))
+  }
+  fn prefixed(&self, cur: &mut Cursor) -> Decoding {
+    let mut op = self.byte(cur)?;
+    loop {
+      match op {
+        0x66 => cur.op_size = true,
+        0xF2 => cur.rep = Some("repne"),
+        0xF3 => cur.rep = Some("rep"),
+        _ => break,
+      }
+      op = self.byte(cur)?;
+    }
+    if op & 0xF0 == 0x40 {
+      cur.rex = Some(op);
+      op = self.byte(cur)?;
+    }
+    if op == 0x0F { self.escape(cur) } else { self.one_byte(cur, op) }
+  }
+}
+fn cc_name(op: u8) -> &'static str {
+  ["o", "no", "b", "ae", "e", "ne", "be", "a", "s", "ns", "p", "np", "l", "ge", "le", "g"]
+    [usize::from(op & 0xF)]
+}
+fn ptr_or_none(size: u8) -> &'static str {
+  if size == 0 { "" } else { ptr(size) }
+}
+fn reg_name_num(num: u8, size: u8) -> String {
+  const REGS: [Register; 16] =
+    [Rax, Rcx, Rdx, Rbx, Rsp, Rbp, Rsi, Rdi, R8, R9, R10, R11, R12, R13, R14, R15];
+  reg_name(REGS[usize::from(num & 15)], size)
+}
+#[cfg(test)]
+mod tests {
+  use super::super::listing::Listing;
+  use super::*;
+  use std::{collections::HashSet, mem::discriminant};
+  #[test]
+  #[expect(clippy::too_many_lines, clippy::panic_in_result_fn)]
+  fn encode_decode_round_trip() -> ErrOR<()> {
+    let dlls =
+      vec![("kernel32.dll".to_owned(), vec!["ExitProcess".to_owned(), "WriteFile".to_owned()])];
+    let handlers = Handlers { ctrl_c: 1, err: None, seh: 2, win: 3 };
+    let mut asm =
+      Assembler::new(dlls.clone(), 0, handlers, Target::Windows, Subsystem::Console, None, 0);
+    let (back, forward, var, buf) = (10, 11, 12, 13);
+    asm.labels.insert(var, (Data, 0x10));
+    asm.labels.insert(buf, (Bss, 0x8));
+    for (section, rva) in [(Text, 0x1000), (Data, 0x2000), (Bss, 0x6000), (IData, 0x7000)] {
+      asm.rva[section as usize] = rva;
+    }
+    let sib = Sib { scale: S4, index: R10, base: Rcx };
+    let sib_r13 = Sib { scale: S1, index: Rdx, base: R13 };
+    let cases = [
+      (Lbl(back), "", ""),
+      (AddRId(R12, 0x1234), "49 81 C4 34 12 00 00", "add r12, 0x1234"),
+      (AddRR(Rax, R9), "4C 01 C8", "add rax, r9"),
+      (ArithSd(Add, Rax, Rcx), "F2 0F 58 C1", "addsd xmm0, xmm1"),
+      (ArithSd(Div, Rdx, Rbx), "F2 0F 5E D3", "divsd xmm2, xmm3"),
+      (ArithSd(Mul, Rsi, Rdi), "F2 0F 59 F7", "mulsd xmm6, xmm7"),
+      (ArithSd(Sub, Rcx, Rax), "F2 0F 5C C8", "subsd xmm1, xmm0"),
+      (CMovCc(E, Rcx, R11), "49 0F 44 CB", "cmove rcx, r11"),
+      (Call(back), "E8 DD FF FF FF", "call .L10"),
+      (Call(forward), "E8 50 01 00 00", "call .L11"),
+      (CallApi((0, 1)), "FF 15 1A 60 00 00", "call qword ptr [kernel32.dll!WriteFile]"),
+      (
+        CallApiCheck((0, 0)),
+        "FF 15 0C 60 00 00 48 85 C0 0F 84 3A 01 00 00",
+        "call qword ptr [kernel32.dll!ExitProcess]; test rax, rax; je win_handler",
+      ),
+      (Clear(R8), "45 31 C0", "xor r8d, r8d"),
+      (Custom(CQO), "48 99", "cqo"),
+      (Custom(RET), "C3", "ret"),
+      (Custom(CALL_RAX), "FF D0", "call rax"),
+      (Custom(CLD_REPNE_SCASB), "FC F2 AE", "cld; repne scasb"),
+      (Custom(CLD_REP_MOVSB), "FC F3 A4", "cld; rep movsb"),
+      (Custom(BTR_RAX_63), "48 0F BA F0 3F", "btr rax, 0x3f"),
+      (Custom(BTC_RAX_63), "48 0F BA F8 3F", "btc rax, 0x3f"),
+      (Custom(SYSCALL), "0F 05", "syscall"),
+      (Custom(RDTSC), "0F 31", "rdtsc"),
+      (CvtSi2Sd(Rdx, R10), "F2 49 0F 2A D2", "cvtsi2sd xmm2, r10"),
+      (CvtTSd2Si(R11, Rbx), "F2 4C 0F 2C DB", "cvttsd2si r11, xmm3"),
+      (DecMd(Local(Tmp, -0x8)), "FF 4D F8", "dec dword ptr [rbp - 0x8]"),
+      (DecR(Rsi), "48 FF CE", "dec rsi"),
+      (IDivR(R9), "49 F7 F9", "idiv r9"),
+      (IMulRR(Rax, R14), "49 0F AF C6", "imul rax, r14"),
+      (IncMd(Global(var)), "FF 05 9A 0F 00 00", "inc dword ptr [.L12]"),
+      (IncR(R15), "49 FF C7", "inc r15"),
+      (JCc(L, back), "7C 85", "jl .L10"),
+      (JCc(Ne, forward), "0F 85 F7 00 00 00", "jne .L11"),
+      (Jmp(back), "E9 7A FF FF FF", "jmp .L10"),
+      (Jmp(forward), "E9 ED 00 00 00", "jmp .L11"),
+      (LeaRM(Rsi, Global(buf)), "48 8D 35 76 4F 00 00", "lea rsi, [.L13]"),
+      (LeaRM(R10, Local(Long, -0x200)), "4C 8D 95 00 FE FF FF", "lea r10, [rbp - 0x200]"),
+      (LogicRR(And, Rax, Rbx), "48 23 C3", "and rax, rbx"),
+      (LogicRR(Or, R8, Rcx), "4C 0B C1", "or r8, rcx"),
+      (LogicRR(Xor, Rdx, R12), "49 33 D4", "xor rdx, r12"),
+      (LogicRR(Cmp, Rbx, Rax), "48 3B D8", "cmp rbx, rax"),
+      (LogicRR(Test, R13, R13), "4D 85 ED", "test r13, r13"),
+      (LogicRbRb(Cmp, Rax, R8), "41 3A C0", "cmp al, r8b"),
+      (LogicRbRb(Test, Rcx, Rdx), "84 CA", "test dl, cl"),
+      (MovBB((Reg(Rax), Ref(Rcx))), "8A 01", "mov al, byte ptr [rcx]"),
+      (MovBB((Ref(R12), Reg(Rdx))), "41 88 14 24", "mov byte ptr [r12], dl"),
+      (MovBB((Reg(Rbx), Reg(R9))), "44 88 CB", "mov bl, r9b"),
+      (MovBB((Reg(Rcx), Mem(Global(var)))), "8A 0D 54 0F 00 00", "mov cl, byte ptr [.L12]"),
+      (
+        MovBB((Reg(Rax), SibDisp(sib, Disp::Zero))),
+        "42 8A 04 91",
+        "mov al, byte ptr [rcx + r10*4]",
+      ),
+      (MovBB((Mem(Local(Tmp, -0x1)), Reg(Rcx))), "88 4D FF", "mov byte ptr [rbp - 0x1], cl"),
+      (MovBB((Mem(Global(var)), Imm(0xFF))), "C6 05 46 0F 00 00 FF", "mov byte ptr [.L12], 0xff"),
+      (MovBB((Reg(R10), Imm(7))), "41 B2 07", "mov r10b, 0x7"),
+      (MovDD((Reg(Rax), Ref(Rbp))), "8B 45 00", "mov eax, dword ptr [rbp]"),
+      (MovDD((Ref(Rsp), Reg(R11))), "44 89 1C 24", "mov dword ptr [rsp], r11d"),
+      (MovDD((Reg(R8), Reg(Rax))), "41 89 C0", "mov r8d, eax"),
+      (
+        MovDD((Reg(Rdx), Mem(Local(Long, -0x1000)))),
+        "8B 95 00 F0 FF FF",
+        "mov edx, dword ptr [rbp - 0x1000]",
+      ),
+      (MovDD((Mem(Global(buf)), Reg(Rcx))), "89 0D 25 4F 00 00", "mov dword ptr [.L13], ecx"),
+      (
+        MovDD((Mem(Local(Tmp, -0x4)), Imm(0xC000_0005))),
+        "C7 45 FC 05 00 00 C0",
+        "mov dword ptr [rbp - 0x4], 0xc0000005",
+      ),
+      (MovDD((Reg(R9), Imm(0x20))), "41 B9 20 00 00 00", "mov r9d, 0x20"),
+      (
+        MovDD((SibDisp(sib, Disp::Byte(-8)), Reg(Rax))),
+        "42 89 44 91 F8",
+        "mov dword ptr [rcx + r10*4 - 0x8], eax",
+      ),
+      (MovQQ((Reg(Rax), Args(1))), "48 8B 04 24", "mov rax, qword ptr [rsp]"),
+      (MovQQ((Args(5), Reg(R9))), "4C 89 4C 24 20", "mov qword ptr [rsp + 0x20], r9"),
+      (MovQQ((Reg(Rbx), Ref(R13))), "49 8B 5D 00", "mov rbx, qword ptr [r13]"),
+      (MovQQ((Ref(Rdi), Reg(Rsi))), "48 89 37", "mov qword ptr [rdi], rsi"),
+      (MovQQ((Reg(Rbp), Reg(Rsp))), "48 89 E5", "mov rbp, rsp"),
+      (MovQQ((Reg(R14), Mem(Global(var)))), "4C 8B 35 01 0F 00 00", "mov r14, qword ptr [.L12]"),
+      (
+        MovQQ((Mem(Local(Long, -0x10)), Reg(R15))),
+        "4C 89 7D F0",
+        "mov qword ptr [rbp - 0x10], r15",
+      ),
+      (
+        MovQQ((Reg(Rcx), Imm(0x1234_5678_9ABC_DEF0))),
+        "48 B9 F0 DE BC 9A 78 56 34 12",
+        "mov rcx, 0x123456789abcdef0",
+      ),
+      (
+        MovQQ((SibDisp(sib_r13, Disp::Dword(0x1_0000)), Reg(Rdx))),
+        "49 89 94 15 00 00 01 00",
+        "mov qword ptr [r13 + rdx + 0x10000], rdx",
+      ),
+      (MovMSd(Local(Tmp, -0x18), Rdx), "F2 0F 11 55 E8", "movsd qword ptr [rbp - 0x18], xmm2"),
+      (MovRSd(R13, Rcx), "66 49 0F 7E CD", "movq r13, xmm1"),
+      (MovRefSd(R8, Rcx), "F2 41 0F 11 08", "movsd qword ptr [r8], xmm1"),
+      (MovSdM(Rax, Global(var)), "F2 0F 10 05 D4 0E 00 00", "movsd xmm0, qword ptr [.L12]"),
+      (MovSdR(Rdx, R14), "66 49 0F 6E D6", "movq xmm2, r14"),
+      (MovSdRef(Rbx, Rbp), "F2 0F 10 5D 00", "movsd xmm3, qword ptr [rbp]"),
+      (MovSxDRMd(R12, Local(Tmp, -0x20)), "4C 63 65 E0", "movsxd r12, dword ptr [rbp - 0x20]"),
+      (Pop(R15), "41 5F", "pop r15"),
+      (Push(Rbp), "55", "push rbp"),
+      (SetCc(Rcx, G), "0F 9F C1", "setg cl"),
+      (ShiftR(Shl, Rax, Shift::Cl), "48 D3 E0", "shl rax, cl"),
+      (ShiftR(Shr, R8, Shift::One), "49 D1 E8", "shr r8, 0x1"),
+      (ShiftR(Shl, Rdx, Shift::Ib(3)), "48 C1 E2 03", "shl rdx, 0x3"),
+      (SqrtSd(Rax, Rcx), "F2 0F 51 C1", "sqrtsd xmm0, xmm1"),
+      (SubRId(Rsp, 0x28), "48 81 EC 28 00 00 00", "sub rsp, 0x28"),
+      (SubRR(Rcx, R10), "4C 29 D1", "sub rcx, r10"),
+      (UComISd(Rdx, Rdi), "66 0F 2E D7", "ucomisd xmm2, xmm7"),
+      (UnaryR(Neg, Rax), "48 F7 D8", "neg rax"),
+      (UnaryR(Not, R11), "49 F7 D3", "not r11"),
+      (UnaryRb(Neg, Rbx), "F6 DB", "neg bl"),
+      (UnaryRb(Not, R9), "41 F6 D1", "not r9b"),
+      (Lbl(handlers.win), "", ""),
+      (Loc(var), "", ""),
+      (Custom(RET), "C3", "ret"),
+      (Lbl(forward), "", ""),
+    ];
+    let variants = cases.iter().map(|(inst, ..)| discriminant(inst)).collect::<HashSet<_>>();
+    assert_eq!(variants.len(), 44, "every `Inst` variant must be covered");
+    let insts = cases.iter().map(|(inst, ..)| *inst).collect::<Vec<_>>();
+    asm.layout_text(&[insts])?;
+    let listing = Listing::new(dlls, HashMap::from([(handlers.win, "win_handler".to_owned())]));
+    let mut rva_names = asm
+      .labels
+      .keys()
+      .map(|id| Ok((asm.get_rva(*id)?, listing.name(*id))))
+      .collect::<ErrOR<HashMap<_, _>>>()?;
+    for api in [(0, 0), (0, 1)] {
+      rva_names.insert(asm.i_f_rva(api)?, listing.api(api));
+    }
+    let mut offset = 0;
+    for (inst, hex, text) in &cases {
+      let bytes = asm.encode_inst(offset, inst)?;
+      let encoded = bytes.iter().map(|byte| format!("{byte:02X}")).collect::<Vec<_>>().join(" ");
+      assert_eq!(encoded, *hex, "encoding of {inst:?}");
+      assert_eq!(len_u32(&bytes)?, asm.sizeof_inst(inst, offset)?, "size of {inst:?}");
+      let decoder = Decoder::new(&bytes, 0x1000 + offset, &rva_names, &[]);
+      let mut decoded = vec![];
+      let mut pos = 0;
+      while pos < bytes.len() {
+        let inst_decoded = decoder.decode(pos);
+        pos += inst_decoded.len as usize;
+        decoded.push(inst_decoded.text);
+      }
+      assert_eq!(decoded.join("; "), *text, "decoding of {inst:?}");
+      offset += len_u32(&bytes)?;
+    }
+    Ok(())
+  }
+}
@@ -0,0 +1,148 @@
+use super::decode::Decoder;
+use crate::prelude::*;
+const IMAGE_SCN_CNT_CODE: u32 = 0x20;
+struct PeImage<'a> {
+  bytes: &'a [u8],
+  sections: Vec<(String, u32, u32, u32, u32)>,
+}
+impl PeImage<'_> {
+  fn at<const N: usize>(&self, offset: usize) -> ErrOR<[u8; N]> {
+    self
+      .bytes
+      .get(offset..offset + N)
+      .and_then(|bytes| bytes.try_into().ok())
+      .ok_or_else(|| invalid_pe("truncated file"))
+  }
+  fn c_str(&self, rva: u32) -> ErrOR<String> {
+    let start = self.offset(rva)?;
+    let len = self.bytes[start..].iter().position(|&byte| byte == 0).unwrap_or(0);
+    Ok(String::from_utf8_lossy(&self.bytes[start..start + len]).into_owned())
+  }
+  fn imports(&self, names: &mut HashMap<u32, String>, idt_rva: u32) -> ErrOR<()> {
+    if idt_rva == 0 {
+      return Ok(());
+    }
+    let mut descriptor = idt_rva;
+    loop {
+      let name_rva = self.u32(descriptor + 12)?;
+      let iat_rva = self.u32(descriptor + 16)?;
+      if name_rva == 0 {
+        return Ok(());
+      }
+      let dll = self.c_str(name_rva)?;
+      let lookup_rva = match self.u32(descriptor)? {
+        0 => iat_rva,
+        rva => rva,
+      };
+      let mut slot = 0;
+      loop {
+        let thunk = u64::from_le_bytes(self.at(self.offset(lookup_rva + slot)?)?);
+        if thunk == 0 {
+          break;
+        }
+        let func = if thunk >> 63 == 1 {
+          format!("#{}", thunk & 0xFFFF)
+        } else {
+          self.c_str(u32::try_from(thunk)? + 2)?
+        };
+        names.insert(iat_rva + slot, format!("{dll}!{func}"));
+        slot += 8;
+      }
+      descriptor += 20;
+    }
+  }
+  fn offset(&self, rva: u32) -> ErrOR<usize> {
+    for (_, v_addr, v_size, r_size, r_ptr) in &self.sections {
+      if (*v_addr..v_addr + v_size.max(r_size)).contains(&rva) {
+        return Ok((r_ptr + rva - v_addr) as usize);
+      }
+    }
+    Err(invalid_pe(&format!("RVA {rva:#x} is outside of every section")))
+  }
+  fn u32(&self, rva: u32) -> ErrOR<u32> {
+    Ok(u32::from_le_bytes(self.at(self.offset(rva)?)?))
+  }
+}
+pub(crate) fn disassemble(bytes: &[u8]) -> ErrOR<String> {
+  let mut image = PeImage { bytes, sections: vec![] };
+  if image.at::<2>(0)? != *b"MZ" {
+    return Err(invalid_pe("missing MZ signature"));
+  }
+  let pe = u32::from_le_bytes(image.at(0x3C)?) as usize;
+  if image.at::<4>(pe)? != *b"PE\0\0" {
+    return Err(invalid_pe("missing PE signature"));
+  }
+  let number_of_sections = u16::from_le_bytes(image.at(pe + 6)?);
+  let optional = pe + 24;
+  let sections_start = optional + usize::from(u16::from_le_bytes(image.at(pe + 20)?));
+  let entry = u32::from_le_bytes(image.at(optional + 16)?);
+  let directory = |idx: usize| image.at::<4>(optional + 112 + idx * 8).map(u32::from_le_bytes);
+  let (idt_rva, pdata_rva, pdata_size) = (directory(1)?, directory(3)?, image.at(optional + 140)?);
+  let mut code = vec![];
+  for idx in 0..usize::from(number_of_sections) {
+    let header = sections_start + idx * 40;
+    let name_bytes = image.at::<8>(header)?;
+    let name = String::from_utf8_lossy(&name_bytes).trim_end_matches('\0').to_owned();
+    let [v_size, v_addr, r_size, r_ptr] =
+      [8, 12, 16, 20].map(|field| image.at(header + field).map(u32::from_le_bytes));
+    let section = (name, v_addr?, v_size?, r_size?, r_ptr?);
+    if u32::from_le_bytes(image.at(header + 36)?) & IMAGE_SCN_CNT_CODE != 0 {
+      code.push(section.clone());
+    }
+    image.sections.push(section);
+  }
+  let mut names = HashMap::new();
+  image.imports(&mut names, idt_rva)?;
+  for entry_offset in (0..u32::from_le_bytes(pdata_size)).step_by(12) {
+    let begin = image.u32(pdata_rva + entry_offset)?;
+    names.insert(begin, format!("sub_{begin:x}"));
+  }
+  names.insert(entry, "entry".to_owned());
+  let section_ranges = image
+    .sections
+    .iter()
+    .map(|(name, v_addr, v_size, ..)| (name.clone(), *v_addr, *v_size))
+    .collect::<Vec<_>>();
+  let mut out = format!("; entry point {entry:#x}\n");
+  for (name, v_addr, v_size, ..) in &image.sections {
+    out.push_str(&format!("; section {name} {v_addr:#x} size {v_size:#x}\n"));
+  }
+  for (name, v_addr, v_size, r_size, r_ptr) in code {
+    let start = r_ptr as usize;
+    let text = bytes
+      .get(start..start + v_size.min(r_size) as usize)
+      .ok_or_else(|| invalid_pe("section data is outside of the file"))?;
+    let mut decoded = vec![];
+    let mut offset = 0;
+    while offset < text.len() {
+      let decoder = Decoder::new(text, v_addr, &names, &section_ranges);
+      let inst = decoder.decode(offset);
+      decoded.push((offset, inst.len, inst.target));
+      offset += inst.len as usize;
+    }
+    for target in decoded.iter().filter_map(|inst| inst.2) {
+      names.entry(target).or_insert_with(|| format!("loc_{target:x}"));
+    }
+    let decoder = Decoder::new(text, v_addr, &names, &section_ranges);
+    out.push_str(&format!("\nsection {name}\n"));
+    for (inst_offset, len, _) in decoded {
+      let rva = v_addr + u32::try_from(inst_offset)?;
+      if let Some(label) = names.get(&rva) {
+        if label.starts_with("sub_") || rva == entry {
+          out.push('\n');
+        }
+        out.push_str(&format!("{label}:\n"));
+      }
+      let hex = text[inst_offset..inst_offset + len as usize]
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<Vec<_>>()
+        .join(" ");
+      out.push_str(&format!("  {rva:08x}  {hex:<30} {}\n", decoder.decode(inst_offset).text));
+    }
+  }
+  Ok(out)
+}
+fn invalid_pe(reason: &str) -> JsonpilerErr {
+  Platform(format!("Not a valid PE image: {reason}"))
+}
@@ -0,0 +1,99 @@
+use crate::prelude::*;
+const ELF_HEADER_SIZE: u16 = 0x40;
+const PROGRAM_HEADER_SIZE: u16 = 0x38;
+const SECTION_HEADER_SIZE: u16 = 0x40;
+const SHSTRTAB: &[u8] = b"\0.text\0.data\0.rodata\0.bss\0.shstrtab\0";
+impl Assembler {
+  pub(crate) fn link_elf(self, sect: &[(Vec<u8>, SectionHeader); 4]) -> ErrOR<Vec<u8>> {
+    const ET_EXEC: u16 = 2;
+    const EM_X86_64: u16 = 0x3E;
+    const PT_LOAD: u32 = 1;
+    let mut segments = vec![];
+    for ((data, header), flags) in sect.iter().zip([5u32, 6, 4, 6]) {
+      if header.v_size != 0 {
+        let file_size = len_u32(data)?;
+        let offset = if file_size == 0 { 0 } else { header.v_addr };
+        segments.push((flags, offset, header.v_addr, file_size, header.v_size));
+      }
+    }
+    let file_end =
+      sect[..3].iter().map(|(data, header)| header.v_addr + len_u32(data).unwrap_or(0));
+    let shstrtab_offset = file_end.max().unwrap_or(0);
+    let sh_offset = align_up_u32(shstrtab_offset + len_u32(SHSTRTAB)?, 8)?;
+    let mut out = Vec::with_capacity(sh_offset as usize + 6 * SECTION_HEADER_SIZE as usize);
+    extend!(
+      out,
+      *b"\x7FELF",
+      [2, 1, 1, 0],
+      [0; 8],
+      ET_EXEC.to_le_bytes(),
+      EM_X86_64.to_le_bytes(),
+      1u32.to_le_bytes(),
+      (ELF_IMAGE_BASE + u64::from(self.get_rva(self.root_id)?)).to_le_bytes(),
+      u64::from(ELF_HEADER_SIZE).to_le_bytes(),
+      u64::from(sh_offset).to_le_bytes(),
+      0u32.to_le_bytes(),
+      ELF_HEADER_SIZE.to_le_bytes(),
+      PROGRAM_HEADER_SIZE.to_le_bytes(),
+      u16::try_from(segments.len())?.to_le_bytes(),
+      SECTION_HEADER_SIZE.to_le_bytes(),
+      6u16.to_le_bytes(),
+      5u16.to_le_bytes(),
+    );
+    for (flags, offset, v_addr, file_size, mem_size) in segments {
+      extend!(
+        out,
+        PT_LOAD.to_le_bytes(),
+        flags.to_le_bytes(),
+        u64::from(offset).to_le_bytes(),
+        (ELF_IMAGE_BASE + u64::from(v_addr)).to_le_bytes(),
+        (ELF_IMAGE_BASE + u64::from(v_addr)).to_le_bytes(),
+        u64::from(file_size).to_le_bytes(),
+        u64::from(mem_size).to_le_bytes(),
+        u64::from(SECTION_ALIGNMENT).to_le_bytes(),
+      );
+    }
+    for (data, header) in &sect[..3] {
+      if !data.is_empty() {
+        out.resize(header.v_addr as usize, 0);
+        out.extend_from_slice(data);
+      }
+    }
+    out.resize(shstrtab_offset as usize, 0);
+    out.extend_from_slice(SHSTRTAB);
+    out.resize(sh_offset as usize, 0);
+    out.extend_from_slice(&[0; SECTION_HEADER_SIZE as usize]);
+    for (name, kind, flags, (data, header)) in [
+      (1u32, 1u32, 6u64, &sect[0]),
+      (7, 1, 3, &sect[1]),
+      (13, 1, 2, &sect[2]),
+      (21, 8, 3, &sect[3]),
+    ] {
+      let offset = if data.is_empty() { 0 } else { header.v_addr };
+      section_header(&mut out, (name, kind, flags), header.v_addr, offset, header.v_size);
+    }
+    section_header(&mut out, (26, 3, 0), 0, shstrtab_offset, len_u32(SHSTRTAB)?);
+    Ok(out)
+  }
+}
+fn section_header(
+  out: &mut Vec<u8>,
+  (name, kind, flags): (u32, u32, u64),
+  v_addr: u32,
+  offset: u32,
+  size: u32,
+) {
+  let addr = if flags == 0 { 0 } else { ELF_IMAGE_BASE + u64::from(v_addr) };
+  extend!(
+    out,
+    name.to_le_bytes(),
+    kind.to_le_bytes(),
+    flags.to_le_bytes(),
+    addr.to_le_bytes(),
+    u64::from(offset).to_le_bytes(),
+    u64::from(size).to_le_bytes(),
+    [0; 8],
+    (if flags & 4 == 0 { 1u64 } else { 16 }).to_le_bytes(),
+    [0; 8],
+  );
+}
@@ -39,6 +39,7 @@ impl Assembler {
         self.labels.insert(idx, (Data, len_u32(data)?));
         data.extend_from_slice(&qword.to_le_bytes());
       }
+      SrcMapLbl(idx) => self.src_map = Some(idx),
       StrLbl(idx, string) => {
         self.labels.insert(idx, (RData, len_u32(rdata)?));
         rdata.extend_from_slice(string.as_bytes());
@@ -98,17 +99,19 @@ impl Assembler {
       MovMSd(addr, xmm) => self.rm(*addr, size, inst)?.encode_ex(0xF2, 0, &two(0x11), *xmm, &[]),
       MovSdRef(xmm, reg) => RM::Base(*reg, Disp::Zero).encode_ex(0xF2, 0, &two(0x10), *xmm, &[]),
       MovRefSd(reg, xmm) => RM::Base(*reg, Disp::Zero).encode_ex(0xF2, 0, &two(0x11), *xmm, &[]),
+      MovSdR(xmm, reg) => RM::Reg(*reg).encode_ex(0x66, 1, &two(0x6E), *xmm, &[]),
+      MovRSd(reg, xmm) => RM::Reg(*reg).encode_ex(0x66, 1, &two(0x7E), *xmm, &[]),
       CvtSi2Sd(xmm, reg) => RM::Reg(*reg).encode_ex(0xF2, 1, &two(0x2A), *xmm, &[]),
       CvtTSd2Si(reg, xmm) => RM::Reg(*xmm).encode_ex(0xF2, 1, &two(0x2C), *reg, &[]),
       CMovCc(cc, dst, src) => RM::Reg(*src).encode(1, &two(0x40 + *cc as u8), *dst),
-      SqrtSd(dst, src) => RM::Reg(*dst).encode_ex(0xF2, 0, &two(0x51), *src, &[]),
+      SqrtSd(dst, src) => RM::Reg(*src).encode_ex(0xF2, 0, &two(0x51), *dst, &[]),
       ArithSd(kind, xmm, xmm2) => RM::Reg(*xmm2).encode_ex(0xF2, 0, &two(*kind as u8), *xmm, &[]),
       UComISd(xmm, xmm2) => RM::Reg(*xmm2).encode_ex(0x66, 0, &two(0x2E), *xmm, &[]),
       JCc(cc, id) => self.encode_jmp(*id, size, inst, 0x70 + *cc as u8, &two(0x80 + *cc as u8))?,
       IMulRR(dst, src) => RM::Reg(*src).encode(1, &two(0xAF), *dst),
       UnaryR(kind, reg) => RM::Reg(*reg).encode(1, &[0xF7], kind.reg_field()),
       UnaryRb(kind, reg) => RM::Reg(reg.rb()?).encode(0, &[0xF6], kind.reg_field()),
-      Lbl(_) => vec![],
+      Lbl(_) | Loc(_) => vec![],
     })
   }
   fn encode_jmp(
@@ -120,8 +123,7 @@ impl Assembler {
     long: &[u8],
   ) -> ErrOR<Vec<u8>> {
     if let Some((Text, offset)) = self.labels.get(&id)
-      && let Ok(disp_b) = i8::try_from(i32::try_from(*offset)? - 2 - i32::try_from(size)?)
-      && disp_b.is_negative()
+      && let Ok(disp_b) = i8::try_from(i64::from(*offset) - 2 - i64::from(size))
     {
       Ok(vec![short, disp_b.cast_unsigned()])
     } else {
@@ -168,6 +170,7 @@ impl Assembler {
       (Reg(dst), Mem(src)) => self.rm(src, size, &MovQQ(operands))?.encode(1, &[0x8B], dst),
       (Mem(dst), Reg(src)) => self.rm(dst, size, &MovQQ(operands))?.encode(1, &[0x89], src),
       (Reg(dst), Imm(imm)) => dst.encode_plus_reg(&[], 1, 0xB8, &imm.to_le_bytes()),
+      (Reg(dst), SibDisp(sib, disp)) => RM::Sib(sib, disp).encode(1, &[0x8B], dst),
       (SibDisp(sib, disp), Reg(src)) => RM::Sib(sib, disp).encode(1, &[0x89], src),
       _ => return Err(Internal(InvalidInst(format!("MovQQ{operands:?}")))),
     })
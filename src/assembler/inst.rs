@@ -23,14 +23,17 @@ pub(crate) enum Inst {
   Jmp(LabelId),
   Lbl(LabelId),
   LeaRM(Register, Address),
+  Loc(LabelId),
   LogicRR(Logic, Register, Register),
   LogicRbRb(Logic, Register, Register),
   MovBB((Operand<u8>, Operand<u8>)),
   MovDD((Operand<u32>, Operand<u32>)),
   MovMSd(Address, Register),
   MovQQ((Operand<u64>, Operand<u64>)),
+  MovRSd(Register, Register),
   MovRefSd(Register, Register),
   MovSdM(Register, Address),
+  MovSdR(Register, Register),
   MovSdRef(Register, Register),
   MovSxDRMd(Register, Address),
   Pop(Register),
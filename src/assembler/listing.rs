@@ -0,0 +1,320 @@
+use super::decode::Decoder;
+use crate::prelude::*;
+pub(crate) struct Listing {
+  dlls: Vec<Dll>,
+  names: HashMap<LabelId, String>,
+}
+impl Listing {
+  fn addr(&self, addr: Address) -> String {
+    match addr {
+      Global(id) => format!("[{}]", self.name(id)),
+      Local(_, offset) => format!("[rbp{}]", signed_hex(offset)),
+    }
+  }
+  pub(crate) fn api(&self, (dll, func): Api) -> String {
+    let (dll_name, funcs) = &self.dlls[dll as usize];
+    format!("{dll_name}!{}", funcs[func as usize])
+  }
+  pub(crate) fn data_lbl(&self, data_lbl: &DataLbl) -> String {
+    match data_lbl {
+      BssLbl(id, size, align) => format!("{}: resb {size:#x} ; align {align}", self.name(*id)),
+      Byte(id, byte) => format!("{}: db {byte:#x}", self.name(*id)),
+      Quad(id, qword) => format!("{}: dq {qword:#x}", self.name(*id)),
+      SrcMapLbl(id) => format!("{}: ; source map", self.name(*id)),
+      StrLbl(id, string) => format!("{}: db {string:?}, 0", self.name(*id)),
+      WStrLbl(id, string) => format!("{}: dw {string:?}, 0", self.name(*id)),
+    }
+  }
+  pub(crate) fn inst(&self, inst: &Inst) -> Vec<String> {
+    vec![match inst {
+      AddRId(reg, imm) => format!("add {}, {imm:#x}", reg_name(*reg, 8)),
+      AddRR(dst, src) => format!("add {}, {}", reg_name(*dst, 8), reg_name(*src, 8)),
+      ArithSd(kind, dst, src) => format!("{}sd {}, {}", kind.name(), xmm(*dst), xmm(*src)),
+      CMovCc(cc, dst, src) => {
+        format!("cmov{} {}, {}", cc.name(), reg_name(*dst, 8), reg_name(*src, 8))
+      }
+      Call(id) => format!("call {}", self.name(*id)),
+      CallApi(api) => format!("call qword ptr [{}]", self.api(*api)),
+      CallApiCheck(api) => {
+        return vec![
+          format!("call qword ptr [{}]", self.api(*api)),
+          "test rax, rax".to_owned(),
+          "je win_handler".to_owned(),
+        ];
+      }
+      Clear(reg) => format!("xor {0}, {0}", reg_name(*reg, 4)),
+      Custom(bytes) => return custom(bytes),
+      CvtSi2Sd(dst, src) => format!("cvtsi2sd {}, {}", xmm(*dst), reg_name(*src, 8)),
+      CvtTSd2Si(dst, src) => format!("cvttsd2si {}, {}", reg_name(*dst, 8), xmm(*src)),
+      DecMd(addr) => format!("dec dword ptr {}", self.addr(*addr)),
+      DecR(reg) => format!("dec {}", reg_name(*reg, 8)),
+      IDivR(reg) => format!("idiv {}", reg_name(*reg, 8)),
+      IMulRR(dst, src) => format!("imul {}, {}", reg_name(*dst, 8), reg_name(*src, 8)),
+      IncMd(addr) => format!("inc dword ptr {}", self.addr(*addr)),
+      IncR(reg) => format!("inc {}", reg_name(*reg, 8)),
+      JCc(cc, id) => format!("j{} {}", cc.name(), self.name(*id)),
+      Jmp(id) => format!("jmp {}", self.name(*id)),
+      Lbl(id) => return vec![format!("{}:", self.name(*id))],
+      Loc(_) => return vec![],
+      LeaRM(reg, addr) => format!("lea {}, {}", reg_name(*reg, 8), self.addr(*addr)),
+      LogicRR(logic, dst, src) => {
+        format!("{} {}, {}", logic.name(), reg_name(*dst, 8), reg_name(*src, 8))
+      }
+      LogicRbRb(logic, dst, src) => {
+        format!("{} {}, {}", logic.name(), reg_name(*dst, 1), reg_name(*src, 1))
+      }
+      MovBB((dst, src)) => format!("mov {}, {}", self.operand(*dst, 1), self.operand(*src, 1)),
+      MovDD((dst, src)) => format!("mov {}, {}", self.operand(*dst, 4), self.operand(*src, 4)),
+      MovMSd(addr, src) => format!("movsd qword ptr {}, {}", self.addr(*addr), xmm(*src)),
+      MovQQ((dst, src)) => format!("mov {}, {}", self.operand(*dst, 8), self.operand(*src, 8)),
+      MovRSd(dst, src) => format!("movq {}, {}", reg_name(*dst, 8), xmm(*src)),
+      MovRefSd(dst, src) => format!("movsd qword ptr [{}], {}", reg_name(*dst, 8), xmm(*src)),
+      MovSdM(dst, addr) => format!("movsd {}, qword ptr {}", xmm(*dst), self.addr(*addr)),
+      MovSdR(dst, src) => format!("movq {}, {}", xmm(*dst), reg_name(*src, 8)),
+      MovSdRef(dst, src) => format!("movsd {}, qword ptr [{}]", xmm(*dst), reg_name(*src, 8)),
+      MovSxDRMd(dst, addr) => {
+        format!("movsxd {}, dword ptr {}", reg_name(*dst, 8), self.addr(*addr))
+      }
+      Pop(reg) => format!("pop {}", reg_name(*reg, 8)),
+      Push(reg) => format!("push {}", reg_name(*reg, 8)),
+      SetCc(reg, cc) => format!("set{} {}", cc.name(), reg_name(*reg, 1)),
+      ShiftR(direction, reg, shift) => {
+        let amount = match shift {
+          Shift::Cl => "cl".to_owned(),
+          Shift::One => "0x1".to_owned(),
+          Shift::Ib(imm) => format!("{imm:#x}"),
+        };
+        format!("{} {}, {amount}", direction.name(), reg_name(*reg, 8))
+      }
+      SqrtSd(dst, src) => format!("sqrtsd {}, {}", xmm(*dst), xmm(*src)),
+      SubRId(reg, imm) => format!("sub {}, {imm:#x}", reg_name(*reg, 8)),
+      SubRR(dst, src) => format!("sub {}, {}", reg_name(*dst, 8), reg_name(*src, 8)),
+      UComISd(lhs, rhs) => format!("ucomisd {}, {}", xmm(*lhs), xmm(*rhs)),
+      UnaryR(kind, reg) => format!("{} {}", kind.name(), reg_name(*reg, 8)),
+      UnaryRb(kind, reg) => format!("{} {}", kind.name(), reg_name(*reg, 1)),
+    }]
+  }
+  pub(crate) fn name(&self, id: LabelId) -> String {
+    self.names.get(&id).cloned().unwrap_or_else(|| format!(".L{id}"))
+  }
+  pub(crate) fn new(dlls: Vec<Dll>, names: HashMap<LabelId, String>) -> Self {
+    Listing { dlls, names }
+  }
+  fn operand<T: fmt::LowerHex>(&self, operand: Operand<T>, size: u8) -> String {
+    match operand {
+      Args(nth) => format!("{}[rsp{}]", ptr(size), signed_hex((nth - 1) * 8)),
+      Imm(imm) => format!("{imm:#x}"),
+      Mem(addr) => format!("{}{}", ptr(size), self.addr(addr)),
+      Ref(reg) => format!("{}[{}]", ptr(size), reg_name(reg, 8)),
+      Reg(reg) => reg_name(reg, size),
+      SibDisp(sib, disp) => {
+        let scale = match sib.scale {
+          S1 => "",
+          S2 => "*2",
+          S4 => "*4",
+          S8 => "*8",
+        };
+        let offset = match disp {
+          Disp::Byte(byte) => signed_hex(i32::from(byte)),
+          Disp::Dword(dword) => signed_hex(dword),
+          Disp::Zero => String::new(),
+        };
+        format!(
+          "{}[{} + {}{scale}{offset}]",
+          ptr(size),
+          reg_name(sib.base, 8),
+          reg_name(sib.index, 8)
+        )
+      }
+    }
+  }
+}
+impl ArithSdKind {
+  pub(crate) fn name(self) -> &'static str {
+    match self {
+      Add => "add",
+      Div => "div",
+      Mul => "mul",
+      Sub => "sub",
+    }
+  }
+}
+impl ConditionCode {
+  pub(crate) fn name(self) -> &'static str {
+    match self {
+      O => "o",
+      No => "no",
+      B => "b",
+      Ae => "ae",
+      E => "e",
+      Ne => "ne",
+      Be => "be",
+      A => "a",
+      S => "s",
+      Ns => "ns",
+      P => "p",
+      Np => "np",
+      L => "l",
+      Ge => "ge",
+      Le => "le",
+      G => "g",
+    }
+  }
+}
+impl Logic {
+  pub(crate) fn name(self) -> &'static str {
+    match self {
+      And => "and",
+      Or => "or",
+      Xor => "xor",
+      Cmp => "cmp",
+      Test => "test",
+    }
+  }
+}
+impl ShiftDirection {
+  pub(crate) fn name(self) -> &'static str {
+    match self {
+      Sar => "sar",
+      Shl => "shl",
+      Shr => "shr",
+    }
+  }
+}
+impl UnaryKind {
+  pub(crate) fn name(self) -> &'static str {
+    match self {
+      Neg => "neg",
+      Not => "not",
+    }
+  }
+}
+impl Jsonpiler {
+  pub(crate) fn label_names(&self) -> HashMap<LabelId, String> {
+    let mut names =
+      self.symbols.iter().map(|(name, id)| (*id, (*name).to_owned())).collect::<HashMap<_, _>>();
+    names.insert(self.handlers.ctrl_c, "ctrl_c_handler".to_owned());
+    names.insert(self.handlers.seh, "seh_handler".to_owned());
+    names.insert(self.handlers.win, "win_handler".to_owned());
+    if let Some(err) = self.handlers.err {
+      names.insert(err, "err_handler".to_owned());
+    }
+    for (name, u_d) in &self.user_defined {
+      names.insert(u_d.val.dep.id, name.clone());
+    }
+    for (idx, parser) in self.parsers.iter().enumerate() {
+      let stem = Path::new(&parser.val.file).file_stem().unwrap_or_default().to_string_lossy();
+      names.insert(
+        parser.val.dep.id,
+        if idx == 0 { "main".to_owned() } else { format!("{stem}.jspl") },
+      );
+    }
+    names
+  }
+  pub(crate) fn listing(&self, insts: &[Vec<Inst>], data: &[DataLbl]) -> ErrOR<String> {
+    let root_file = &self.first_parser()?.val.file;
+    let names = self.label_names();
+    let mut positions = HashMap::new();
+    for u_d in self.user_defined.values() {
+      positions.insert(u_d.val.dep.id, u_d.pos);
+    }
+    for parser in &self.parsers {
+      positions.insert(parser.val.dep.id, parser.pos);
+    }
+    let listing = Listing::new(self.dlls.clone(), names);
+    let mut out = String::from("section .text\n");
+    for block in insts {
+      if let Some(Lbl(id)) = block.first() {
+        out.push_str(&format!("\n; {}", listing.name(*id)));
+        if let Some(pos) = positions.get(id) {
+          let (file, l_c, ..) = self.parsers[pos.file as usize].err_info(*pos, root_file);
+          out.push_str(&format!(" ({file}{l_c})"));
+        }
+        out.push('\n');
+      }
+      for inst in block {
+        if let Loc(id) = inst
+          && let Some(pos) = self.locations.get(id)
+        {
+          let (file, l_c, ..) = self.parsers[pos.file as usize].err_info(*pos, root_file);
+          out.push_str(&format!("  ; {file}{l_c}\n"));
+          continue;
+        }
+        let indent = if let Lbl(_) = inst { "" } else { "  " };
+        for line in listing.inst(inst) {
+          out.push_str(&format!("{indent}{line}\n"));
+        }
+      }
+    }
+    for (title, section) in [("data", Data), ("rdata", RData), ("bss", Bss)] {
+      out.push_str(&format!("\nsection .{title}\n"));
+      for data_lbl in data.iter().filter(|data_lbl| data_lbl.section() == section) {
+        out.push_str(&listing.data_lbl(data_lbl));
+        out.push('\n');
+      }
+    }
+    out.push_str("\n; imports\n");
+    for (dll, funcs) in &self.dlls {
+      out.push_str(&format!("; {dll}: {}\n", funcs.join(", ")));
+    }
+    Ok(out)
+  }
+}
+impl DataLbl {
+  pub(crate) fn section(&self) -> Section {
+    match self {
+      BssLbl(..) => Bss,
+      Byte(..) | Quad(..) => Data,
+      SrcMapLbl(_) | StrLbl(..) | WStrLbl(..) => RData,
+    }
+  }
+}
+fn custom(bytes: &[u8]) -> Vec<String> {
+  let names = HashMap::new();
+  let decoder = Decoder::new(bytes, 0, &names, &[]);
+  let mut lines = vec![];
+  let mut offset = 0;
+  while offset < bytes.len() {
+    let decoded = decoder.decode(offset);
+    offset += decoded.len as usize;
+    lines.push(decoded.text);
+  }
+  lines
+}
+pub(super) fn ptr(size: u8) -> &'static str {
+  match size {
+    1 => "byte ptr ",
+    2 => "word ptr ",
+    4 => "dword ptr ",
+    _ => "qword ptr ",
+  }
+}
+pub(crate) fn reg_name(reg: Register, size: u8) -> String {
+  const NAMES: [&str; 8] = ["ax", "cx", "dx", "bx", "sp", "bp", "si", "di"];
+  let idx = reg as usize;
+  if idx < 8 {
+    match size {
+      1 => ["al", "cl", "dl", "bl", "spl", "bpl", "sil", "dil"][idx].to_owned(),
+      2 => NAMES[idx].to_owned(),
+      4 => format!("e{}", NAMES[idx]),
+      _ => format!("r{}", NAMES[idx]),
+    }
+  } else {
+    match size {
+      1 => format!("r{idx}b"),
+      2 => format!("r{idx}w"),
+      4 => format!("r{idx}d"),
+      _ => format!("r{idx}"),
+    }
+  }
+}
+pub(super) fn signed_hex(offset: i32) -> String {
+  match offset {
+    0 => String::new(),
+    1.. => format!(" + {offset:#x}"),
+    _ => format!(" - {:#x}", offset.unsigned_abs()),
+  }
+}
+fn xmm(reg: Register) -> String {
+  format!("xmm{}", reg as u8)
+}
@@ -66,7 +66,6 @@ pub(crate) enum Operand<T> {
 }
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) enum ShiftDirection {
-  #[expect(dead_code)]
   Sar,
   Shl,
   Shr,
@@ -0,0 +1,498 @@
+use super::utility::align_up;
+use crate::prelude::*;
+const BLOCK_SIZE: usize = 0x1000;
+const DEBUG_S_FILECHKSMS: u32 = 0xF4;
+const DEBUG_S_LINES: u32 = 0xF2;
+const GLOBALS_STREAM: u16 = 10;
+const GSI_VERSION: u32 = 0xEFFE_0000 + 19_990_810;
+const IMAGE_DEBUG_TYPE_CODEVIEW: u32 = 2;
+const IPHR_HASH: usize = 4096;
+const MODULE_STREAM: u16 = 6;
+const MSF_MAGIC: &[u8; 32] = b"Microsoft C/C++ MSF 7.00\r\n\x1aDS\0\0\0";
+const NAMES_STREAM: u32 = 5;
+const PUBLICS_STREAM: u16 = 9;
+const SECTION_HEADERS_STREAM: u16 = 7;
+const SYMBOL_RECORDS_STREAM: u16 = 8;
+const S_END: u16 = 0x0006;
+const S_GPROC32: u16 = 0x1110;
+const S_OBJNAME: u16 = 0x1101;
+const S_PROCREF: u16 = 0x1125;
+const S_PUB32: u16 = 0x110E;
+struct ModuleInfo<'a> {
+  c13_size: u32,
+  module_name: &'a str,
+  procs: Vec<(u32, &'a str)>,
+  sym_size: u32,
+  text_size: u32,
+}
+#[derive(Debug)]
+pub(crate) struct PdbFunc {
+  pub lines: Vec<(u32, FileIdx, u32)>,
+  pub name: String,
+  pub rva: u32,
+  pub size: u32,
+}
+impl Assembler {
+  pub(crate) fn fill_debug_dir(
+    &self,
+    rdata: &mut [u8],
+    rdata_h: SectionHeader,
+    guid: [u8; 16],
+  ) -> ErrOR<()> {
+    let (Some(offset), Some(path)) = (self.debug_dir, &self.pdb) else {
+      return Ok(());
+    };
+    let rsds_offset = u32::try_from(offset)? + 28;
+    let mut rsds = vec![];
+    extend!(rsds, *b"RSDS", guid, 1u32.to_le_bytes(), path.as_bytes(), [0]);
+    let mut dir = vec![0; 12];
+    extend!(
+      dir,
+      IMAGE_DEBUG_TYPE_CODEVIEW.to_le_bytes(),
+      len_u32(&rsds)?.to_le_bytes(),
+      (rdata_h.v_addr + rsds_offset).to_le_bytes(),
+      (rdata_h.r_ptr + rsds_offset).to_le_bytes(),
+      rsds
+    );
+    rdata
+      .get_mut(offset..offset + dir.len())
+      .ok_or(Internal(InternalOverFlow))?
+      .copy_from_slice(&dir);
+    Ok(())
+  }
+  pub(crate) fn reserve_debug_dir(&mut self, rdata: &mut Vec<u8>) -> ErrOR<()> {
+    if let Some(path) = &self.pdb {
+      rdata.resize(align_up(rdata.len(), 4)?, 0);
+      self.debug_dir = Some(rdata.len());
+      rdata.resize(rdata.len() + 28 + 24 + path.len() + 1, 0);
+    }
+    Ok(())
+  }
+}
+pub(crate) fn pdb_guid(parts: &[&[u8]]) -> [u8; 16] {
+  let mut halves = [0xCBF2_9CE4_8422_2325u64, 0x6C62_272E_07BB_0142];
+  for (idx, half) in halves.iter_mut().enumerate() {
+    for byte in parts.iter().flat_map(|part| part.iter()) {
+      *half = (*half ^ u64::from(*byte)).wrapping_mul(0x100_0000_01B3 + idx as u64 * 2);
+    }
+  }
+  let mut guid = [0; 16];
+  guid[..8].copy_from_slice(&halves[0].to_le_bytes());
+  guid[8..].copy_from_slice(&halves[1].to_le_bytes());
+  guid
+}
+pub(crate) fn write_pdb(
+  path: &str,
+  guid: [u8; 16],
+  sections: &[SectionHeader],
+  files: &[String],
+  funcs: &[PdbFunc],
+) -> ErrOR<()> {
+  let text_h = sections.first().ok_or(Internal(InternalOverFlow))?;
+  let module_name = files.first().map_or("", String::as_str);
+  let (names, name_offsets) = string_table(files)?;
+  let (module, info) = module_stream(funcs, &name_offsets, text_h, module_name)?;
+  let mut records = vec![];
+  let mut publics = vec![];
+  let mut globals = vec![];
+  for func in funcs {
+    publics.push((len_u32(&records)?, func.name.as_str(), func.rva));
+    let mut body = vec![];
+    extend!(
+      body,
+      3u32.to_le_bytes(),
+      (func.rva - text_h.v_addr).to_le_bytes(),
+      1u16.to_le_bytes(),
+      func.name.as_bytes(),
+      [0]
+    );
+    extend!(records, symbol(S_PUB32, &body)?);
+  }
+  for (offset, name) in &info.procs {
+    globals.push((len_u32(&records)?, *name));
+    let mut body = vec![0; 4];
+    extend!(body, offset.to_le_bytes(), 1u16.to_le_bytes(), name.as_bytes(), [0]);
+    extend!(records, symbol(S_PROCREF, &body)?);
+  }
+  publics.sort_by(|lhs, rhs| (lhs.2, lhs.1).cmp(&(rhs.2, rhs.1)));
+  let publics_hash =
+    gsi_hash(&publics.iter().map(|(off, name, _)| (*off, *name)).collect::<Vec<_>>())?;
+  let mut publics_stream = vec![];
+  extend!(
+    publics_stream,
+    len_u32(&publics_hash)?.to_le_bytes(),
+    (len_u32(&publics)? * 4).to_le_bytes(),
+    [0; 20],
+    publics_hash
+  );
+  for (offset, ..) in &publics {
+    extend!(publics_stream, offset.to_le_bytes());
+  }
+  let mut section_headers = vec![];
+  for header in sections {
+    extend!(section_headers, header.encode());
+  }
+  let streams = [
+    vec![],
+    info_stream(guid)?,
+    type_stream(),
+    dbi_stream(&info, files, sections)?,
+    type_stream(),
+    names,
+    module,
+    section_headers,
+    records,
+    publics_stream,
+    gsi_hash(&globals)?,
+  ];
+  fs::write(path, msf(&streams)?)?;
+  Ok(())
+}
+fn dbi_stream(info: &ModuleInfo, files: &[String], sections: &[SectionHeader]) -> ErrOR<Vec<u8>> {
+  let mut modules = vec![0; 4];
+  let contribution = section_contribution(info.text_size)?;
+  extend!(
+    modules,
+    contribution,
+    [0; 2],
+    MODULE_STREAM.to_le_bytes(),
+    info.sym_size.to_le_bytes(),
+    [0; 4],
+    info.c13_size.to_le_bytes(),
+    u16::try_from(files.len())?.to_le_bytes(),
+    [0; 14],
+    info.module_name.as_bytes(),
+    [0],
+    info.module_name.as_bytes(),
+    [0]
+  );
+  modules.resize(align_up(modules.len(), 4)?, 0);
+  let mut contributions = vec![];
+  extend!(contributions, (0xEFFE_0000u32 + 19_970_605).to_le_bytes(), contribution);
+  let count = u16::try_from(sections.len() + 1)?;
+  let mut section_map = vec![];
+  extend!(section_map, count.to_le_bytes(), count.to_le_bytes());
+  for (idx, header) in sections.iter().enumerate() {
+    let chars = header.characteristics;
+    let flags = u16::from(chars & 0x4000_0000 != 0)
+      | (u16::from(chars & 0x8000_0000 != 0) << 1)
+      | (u16::from(chars & 0x2000_0000 != 0) << 2)
+      | 0x108;
+    extend!(
+      section_map,
+      flags.to_le_bytes(),
+      [0; 4],
+      u16::try_from(idx + 1)?.to_le_bytes(),
+      [0xFF; 4],
+      [0; 4],
+      header.v_size.to_le_bytes()
+    );
+  }
+  extend!(section_map, 0x208u16.to_le_bytes(), [0; 4], count.to_le_bytes(), [0xFF; 4], [0; 4]);
+  extend!(section_map, u32::MAX.to_le_bytes());
+  let file_count = u16::try_from(files.len())?.to_le_bytes();
+  let mut file_info = vec![];
+  extend!(file_info, 1u16.to_le_bytes(), file_count, [0; 2], file_count);
+  let mut file_names = vec![];
+  for file in files {
+    extend!(file_info, len_u32(&file_names)?.to_le_bytes());
+    extend!(file_names, file.as_bytes(), [0]);
+  }
+  extend!(file_info, file_names);
+  file_info.resize(align_up(file_info.len(), 4)?, 0);
+  let (ec_names, _) = string_table(&[])?;
+  let mut debug_header = vec![];
+  for idx in 0..11 {
+    let stream = if idx == 5 { SECTION_HEADERS_STREAM } else { u16::MAX };
+    extend!(debug_header, stream.to_le_bytes());
+  }
+  let mut dbi = vec![];
+  extend!(
+    dbi,
+    u32::MAX.to_le_bytes(),
+    19_990_903u32.to_le_bytes(),
+    1u32.to_le_bytes(),
+    GLOBALS_STREAM.to_le_bytes(),
+    0x8E00u16.to_le_bytes(),
+    PUBLICS_STREAM.to_le_bytes(),
+    [0; 2],
+    SYMBOL_RECORDS_STREAM.to_le_bytes(),
+    [0; 2],
+    len_u32(&modules)?.to_le_bytes(),
+    len_u32(&contributions)?.to_le_bytes(),
+    len_u32(&section_map)?.to_le_bytes(),
+    len_u32(&file_info)?.to_le_bytes(),
+    [0; 4],
+    [0; 4],
+    len_u32(&debug_header)?.to_le_bytes(),
+    len_u32(&ec_names)?.to_le_bytes(),
+    [0; 2],
+    0x8664u16.to_le_bytes(),
+    [0; 4],
+    modules,
+    contributions,
+    section_map,
+    file_info,
+    ec_names,
+    debug_header
+  );
+  Ok(dbi)
+}
+fn gsi_hash(symbols: &[(u32, &str)]) -> ErrOR<Vec<u8>> {
+  let mut buckets = vec![vec![]; IPHR_HASH];
+  for (offset, name) in symbols {
+    buckets[hash_v1(name) as usize % IPHR_HASH].push(*offset);
+  }
+  let mut records = vec![];
+  let mut bitmap = [0u32; IPHR_HASH / 32 + 1];
+  let mut starts = vec![];
+  for (idx, bucket) in buckets.iter().enumerate() {
+    if bucket.is_empty() {
+      continue;
+    }
+    bitmap[idx / 32] |= 1 << (idx % 32);
+    starts.push(len_u32(&records)? / 8 * 12);
+    for offset in bucket {
+      extend!(records, (offset + 1).to_le_bytes(), 1u32.to_le_bytes());
+    }
+  }
+  let mut hash = vec![];
+  extend!(
+    hash,
+    u32::MAX.to_le_bytes(),
+    GSI_VERSION.to_le_bytes(),
+    len_u32(&records)?.to_le_bytes(),
+    ((len_u32(&bitmap)? + len_u32(&starts)?) * 4).to_le_bytes(),
+    records
+  );
+  for word in bitmap.iter().chain(&starts) {
+    extend!(hash, word.to_le_bytes());
+  }
+  Ok(hash)
+}
+fn hash_v1(text: &str) -> u32 {
+  let mut chunks = text.as_bytes().chunks_exact(4);
+  let mut hash = 0u32;
+  for chunk in chunks.by_ref() {
+    hash ^= chunk.iter().rev().fold(0, |acc, byte| (acc << 8) | u32::from(*byte));
+  }
+  let rest = chunks.remainder();
+  if let [lo, hi, ..] = rest {
+    hash ^= u32::from(u16::from_le_bytes([*lo, *hi]));
+  }
+  if let [.., last] = rest
+    && rest.len() % 2 == 1
+  {
+    hash ^= u32::from(*last);
+  }
+  hash |= 0x2020_2020;
+  hash ^= hash >> 11;
+  hash ^ (hash >> 16)
+}
+fn info_stream(guid: [u8; 16]) -> ErrOR<Vec<u8>> {
+  const NAMES: &str = "/names";
+  let capacity = 8;
+  let bucket = hash_v1(NAMES) % 0x1_0000 % capacity;
+  let mut info = vec![];
+  extend!(
+    info,
+    20_000_404u32.to_le_bytes(),
+    guid[..4],
+    1u32.to_le_bytes(),
+    guid,
+    (len_u32(NAMES.as_bytes())? + 1).to_le_bytes(),
+    NAMES.as_bytes(),
+    [0],
+    1u32.to_le_bytes(),
+    capacity.to_le_bytes(),
+    1u32.to_le_bytes(),
+    (1u32 << bucket).to_le_bytes(),
+    [0; 4],
+    [0; 4],
+    NAMES_STREAM.to_le_bytes(),
+    [0; 4],
+    20_140_508u32.to_le_bytes()
+  );
+  Ok(info)
+}
+fn module_stream<'a>(
+  funcs: &'a [PdbFunc],
+  name_offsets: &[u32],
+  text_h: &SectionHeader,
+  module_name: &'a str,
+) -> ErrOR<(Vec<u8>, ModuleInfo<'a>)> {
+  let text_rva = text_h.v_addr;
+  let mut obj = vec![0; 4];
+  extend!(obj, module_name.as_bytes(), [0]);
+  let mut syms = vec![];
+  extend!(syms, 4u32.to_le_bytes(), symbol(S_OBJNAME, &obj)?);
+  let mut procs = vec![];
+  for func in funcs {
+    let start = len_u32(&syms)?;
+    let mut body = vec![0; 12];
+    extend!(
+      body,
+      func.size.to_le_bytes(),
+      [0; 4],
+      func.size.to_le_bytes(),
+      [0; 4],
+      (func.rva - text_rva).to_le_bytes(),
+      1u16.to_le_bytes(),
+      [0],
+      func.name.as_bytes(),
+      [0]
+    );
+    let mut proc = symbol(S_GPROC32, &body)?;
+    let end = start + len_u32(&proc)?;
+    proc[8..12].copy_from_slice(&end.to_le_bytes());
+    extend!(syms, proc, symbol(S_END, &[])?);
+    procs.push((start, func.name.as_str()));
+  }
+  let mut checksums = vec![];
+  for offset in name_offsets {
+    extend!(checksums, offset.to_le_bytes(), [0; 4]);
+  }
+  let mut c13 = subsection(DEBUG_S_FILECHKSMS, &checksums)?;
+  for func in funcs.iter().filter(|func| !func.lines.is_empty()) {
+    let mut blocks: Vec<(FileIdx, Vec<(u32, u32)>)> = vec![];
+    for (rva, file, line) in &func.lines {
+      let entry = (rva - func.rva, line | 0x8000_0000);
+      match blocks.last_mut() {
+        Some((last_file, entries)) if last_file == file => entries.push(entry),
+        Some(_) | None => blocks.push((*file, vec![entry])),
+      }
+    }
+    let mut lines = vec![];
+    extend!(lines, (func.rva - text_rva).to_le_bytes(), 1u16.to_le_bytes(), [0; 2]);
+    extend!(lines, func.size.to_le_bytes());
+    for (file, entries) in blocks {
+      let count = len_u32(&entries)?;
+      extend!(lines, (file * 8).to_le_bytes(), count.to_le_bytes(), (12 + count * 8).to_le_bytes());
+      for (offset, flags) in entries {
+        extend!(lines, offset.to_le_bytes(), flags.to_le_bytes());
+      }
+    }
+    extend!(c13, subsection(DEBUG_S_LINES, &lines)?);
+  }
+  let (sym_size, c13_size) = (len_u32(&syms)?, len_u32(&c13)?);
+  extend!(syms, c13, [0; 4]);
+  Ok((syms, ModuleInfo { c13_size, module_name, procs, sym_size, text_size: text_h.v_size }))
+}
+fn msf(streams: &[Vec<u8>]) -> ErrOR<Vec<u8>> {
+  let mut file = vec![0; 3 * BLOCK_SIZE];
+  let push_blocks = |out: &mut Vec<u8>, data: &[u8]| -> ErrOR<Vec<u8>> {
+    let mut blocks = vec![];
+    for chunk in data.chunks(BLOCK_SIZE) {
+      extend!(blocks, u32::try_from(out.len() / BLOCK_SIZE)?.to_le_bytes());
+      extend!(out, chunk);
+      out.resize(align_up(out.len(), BLOCK_SIZE)?, 0);
+    }
+    Ok(blocks)
+  };
+  let mut directory = vec![];
+  let mut block_lists = vec![];
+  extend!(directory, len_u32(streams)?.to_le_bytes());
+  for stream in streams {
+    extend!(directory, len_u32(stream)?.to_le_bytes());
+    extend!(block_lists, push_blocks(&mut file, stream)?);
+  }
+  extend!(directory, block_lists);
+  let directory_blocks = push_blocks(&mut file, &directory)?;
+  let block_map = push_blocks(&mut file, &directory_blocks)?;
+  let block_count = file.len() / BLOCK_SIZE;
+  if block_count > BLOCK_SIZE || directory_blocks.len() > BLOCK_SIZE {
+    return Err(Platform("The PDB exceeds the size of a single free block map".into()));
+  }
+  let mut superblock = vec![];
+  extend!(
+    superblock,
+    *MSF_MAGIC,
+    u32::try_from(BLOCK_SIZE)?.to_le_bytes(),
+    1u32.to_le_bytes(),
+    u32::try_from(block_count)?.to_le_bytes(),
+    len_u32(&directory)?.to_le_bytes(),
+    [0; 4],
+    block_map
+  );
+  file[..superblock.len()].copy_from_slice(&superblock);
+  for fpm in file[BLOCK_SIZE..BLOCK_SIZE * 3].chunks_mut(BLOCK_SIZE) {
+    fpm.fill(0xFF);
+    for block in 0..block_count {
+      fpm[block / 8] &= !(1 << (block % 8));
+    }
+  }
+  Ok(file)
+}
+fn section_contribution(text_size: u32) -> ErrOR<Vec<u8>> {
+  let mut contribution = vec![];
+  extend!(
+    contribution,
+    1u16.to_le_bytes(),
+    [0; 6],
+    text_size.to_le_bytes(),
+    Text.characteristics().to_le_bytes(),
+    [0; 12]
+  );
+  Ok(contribution)
+}
+fn string_table(strings: &[String]) -> ErrOR<(Vec<u8>, Vec<u32>)> {
+  let mut buffer = vec![0];
+  let mut offsets = vec![];
+  for string in strings {
+    offsets.push(len_u32(&buffer)?);
+    extend!(buffer, string.as_bytes(), [0]);
+  }
+  buffer.resize(align_up(buffer.len(), 4)?, 0);
+  let mut buckets = vec![0u32; strings.len() * 2 + 1];
+  for (string, offset) in strings.iter().zip(&offsets) {
+    let mut bucket = hash_v1(string) as usize % buckets.len();
+    while buckets[bucket] != 0 {
+      bucket = (bucket + 1) % buckets.len();
+    }
+    buckets[bucket] = *offset;
+  }
+  let mut table = vec![];
+  extend!(
+    table,
+    0xEFFE_EFFEu32.to_le_bytes(),
+    1u32.to_le_bytes(),
+    len_u32(&buffer)?.to_le_bytes(),
+    buffer,
+    len_u32(&buckets)?.to_le_bytes()
+  );
+  for offset in buckets {
+    extend!(table, offset.to_le_bytes());
+  }
+  extend!(table, len_u32(strings)?.to_le_bytes());
+  Ok((table, offsets))
+}
+fn subsection(kind: u32, data: &[u8]) -> ErrOR<Vec<u8>> {
+  let mut out = vec![];
+  extend!(out, kind.to_le_bytes(), len_u32(data)?.to_le_bytes(), data);
+  out.resize(align_up(out.len(), 4)?, 0);
+  Ok(out)
+}
+fn symbol(kind: u16, body: &[u8]) -> ErrOR<Vec<u8>> {
+  let len = align_up(body.len() + 4, 4)?;
+  let mut record = vec![];
+  extend!(record, u16::try_from(len - 2)?.to_le_bytes(), kind.to_le_bytes(), body);
+  record.resize(len, 0);
+  Ok(record)
+}
+fn type_stream() -> Vec<u8> {
+  let mut tpi = vec![];
+  extend!(
+    tpi,
+    20_040_203u32.to_le_bytes(),
+    56u32.to_le_bytes(),
+    0x1000u32.to_le_bytes(),
+    0x1000u32.to_le_bytes(),
+    [0; 4],
+    u16::MAX.to_le_bytes(),
+    u16::MAX.to_le_bytes(),
+    4u32.to_le_bytes(),
+    0x3_FFFFu32.to_le_bytes(),
+    [0; 24]
+  );
+  tpi
+}
@@ -1,7 +1,46 @@
 use super::utility::*;
 use crate::prelude::*;
-use io::{Seek as _, Write as _};
 impl Assembler {
+  pub(crate) fn build_edata(
+    &self,
+    exports: &[(String, LabelId)],
+    dll_name: &str,
+    v_addr: u32,
+  ) -> ErrOR<Vec<u8>> {
+    let count = len_u32(exports)?;
+    let eat = v_addr + 40;
+    let names = eat + count * 4;
+    let ordinals = names + count * 4;
+    let strings_start = ordinals + count * 2;
+    let mut eat_bytes = vec![];
+    let mut name_ptrs = vec![];
+    let mut ordinal_bytes = vec![];
+    let mut strings = vec![];
+    extend!(strings, dll_name.as_bytes(), [0]);
+    for (idx, (name, id)) in exports.iter().enumerate() {
+      eat_bytes.extend_from_slice(&self.get_rva(*id)?.to_le_bytes());
+      name_ptrs.extend_from_slice(&(strings_start + len_u32(&strings)?).to_le_bytes());
+      ordinal_bytes.extend_from_slice(&u16::try_from(idx)?.to_le_bytes());
+      extend!(strings, name.as_bytes(), [0]);
+    }
+    let mut edata = vec![];
+    extend!(
+      edata,
+      [0; 12],
+      strings_start.to_le_bytes(),
+      1u32.to_le_bytes(),
+      count.to_le_bytes(),
+      count.to_le_bytes(),
+      eat.to_le_bytes(),
+      names.to_le_bytes(),
+      ordinals.to_le_bytes(),
+      eat_bytes,
+      name_ptrs,
+      ordinal_bytes,
+      strings,
+    );
+    Ok(edata)
+  }
   pub(crate) fn build_idata(&self) -> ErrOR<Vec<u8>> {
     let idt_size = self.sizeof_idt()?;
     let i_la_t_size = self.sizeof_iat()?;
@@ -34,75 +73,104 @@ impl Assembler {
     extend!(idata, idt, ilt_iat, hint_name);
     Ok(idata)
   }
-  pub(crate) fn build_pdata(&self, seh: &mut Vec<(u32, u32, i32)>) -> ErrOR<(Vec<u8>, Vec<i32>)> {
+  pub(crate) fn build_pdata(&self, seh: &mut Seh) -> ErrOR<(Vec<u8>, Vec<UnwindFrame>)> {
     let mut pdata = vec![];
-    let mut stack_sizes = vec![];
+    let mut frames = vec![];
     seh.sort_by(|lhs, rhs| self.labels[&lhs.0].1.cmp(&self.labels[&rhs.0].1));
-    for (id, end, size) in seh {
+    for (id, end, size, saved) in seh {
       extend!(pdata, self.get_rva(*id)?.to_le_bytes(), self.get_rva(*end)?.to_le_bytes(), [0; 4]);
-      stack_sizes.push(*size);
+      frames.push((*size, take(saved)));
     }
-    Ok((pdata, stack_sizes))
+    Ok((pdata, frames))
+  }
+  // Code is RIP-relative only, so the block is empty.
+  pub(crate) fn build_reloc(&self) -> Vec<u8> {
+    let mut reloc = vec![];
+    extend!(reloc, self.rva[Text as usize].to_le_bytes(), 12u32.to_le_bytes(), [0; 4]);
+    reloc
   }
   pub(crate) fn build_xdata(
     &self,
     xdata_v_addr: u32,
     pdata: &mut [u8],
-    stack_sizes: &[i32],
-  ) -> ErrOR<Vec<u8>> {
+    frames: &[UnwindFrame],
+  ) -> ErrOR<(Vec<u8>, Vec<u32>)> {
     let mut xdata = vec![];
+    let mut handler_fields = vec![];
     #[expect(clippy::cast_possible_truncation)]
-    for (idx, &size) in stack_sizes.iter().enumerate() {
+    for (idx, (size, saved)) in frames.iter().enumerate() {
       xdata.resize(align_up(xdata.len(), 4)?, 0);
       let unwind_info = idx * 12 + 8;
       let xdata_offset = xdata_v_addr + len_u32(&xdata)?;
       pdata[unwind_info..unwind_info + 4].copy_from_slice(&xdata_offset.to_le_bytes());
       let push_rbp = self.sizeof_inst(&Push(Rbp), 0)?;
-      let mov_rbp_rsp = push_rbp + self.sizeof_inst(&mov_q(Rbp, Rsp), push_rbp)?;
+      let mut pushes = vec![];
+      let mut prolog = push_rbp;
+      for reg in saved {
+        prolog += self.sizeof_inst(&Push(*reg), prolog)?;
+        pushes.push([u8::try_from(prolog)?, (*reg as u8) << 4u8]);
+      }
+      let mov_rbp_rsp = prolog + self.sizeof_inst(&mov_q(Rbp, Rsp), prolog)?;
       let sub_rsp_size = u8::try_from(
         mov_rbp_rsp + self.sizeof_inst(&SubRId(Rsp, size.cast_unsigned()), mov_rbp_rsp)?,
       )?;
+      let codes = 4 + u8::try_from(saved.len())?;
       extend!(
         xdata,
-        [0o11, sub_rsp_size, 4, Rbp as u8, sub_rsp_size, 1],
+        [0o11, sub_rsp_size, codes, Rbp as u8, sub_rsp_size, 1],
         ((size >> 3).cast_unsigned() as u16).to_le_bytes(),
-        [u8::try_from(mov_rbp_rsp)?, 3, u8::try_from(push_rbp)?, (Rbp as u8) << 4u8],
-        self.get_rva(self.handlers.seh)?.to_le_bytes()
+        [u8::try_from(mov_rbp_rsp)?, 3]
       );
+      for push in pushes.iter().rev() {
+        xdata.extend_from_slice(push);
+      }
+      extend!(
+        xdata,
+        [u8::try_from(push_rbp)?, (Rbp as u8) << 4u8],
+        [0; 2].repeat(usize::from(codes % 2))
+      );
+      handler_fields.push(len_u32(&xdata)?);
+      xdata.extend_from_slice(&self.get_rva(self.handlers.seh)?.to_le_bytes());
     }
-    Ok(xdata)
+    Ok((xdata, handler_fields))
   }
-  pub(crate) fn link(self, sect: &[(Vec<u8>, SectionHeader); 7], input: &str) -> ErrOR<()> {
+  pub(crate) fn link(self, sect: &[(Vec<u8>, SectionHeader)]) -> ErrOR<Vec<u8>> {
     const PE32PLUS: u16 = 0x020B;
     const MACHINE_X64: u16 = 0x8664;
     const COFF_CHARACTERISTICS: u16 = 0x0222;
-    let data_size = sect[Data as usize].1.v_size
-      + sect[RData as usize].1.v_size
-      + sect[PData as usize].1.v_size
-      + sect[XData as usize].1.v_size
-      + sect[IData as usize].1.v_size;
+    const IMAGE_FILE_DLL: u16 = 0x2000;
+    const DLL_CHARACTERISTICS: u16 = 0x0160;
+    let find =
+      |kind: Section| sect.iter().find(|(_, header)| header.is(kind)).map(|(_, header)| *header);
+    let edata_h = find(EData);
+    let last_h = sect[sect.len() - 1].1;
+    let data_size = [Data, RData, PData, XData, IData, Reloc, Rsrc, EData]
+      .into_iter()
+      .filter_map(find)
+      .map(|header| header.v_size)
+      .sum::<u32>();
+    let (rsrc_rva, rsrc_size) = find(Rsrc).map_or((0, 0), |header| (header.v_addr, header.v_size));
     let headers_size = align_up_u32(HEADERS_SIZE, FILE_ALIGNMENT)?;
-    let image_size = sect[IData as usize].1.next_v_addr()?;
-    let file_size = sect[IData as usize].1.next_r_ptr();
-    let mut out = Vec::with_capacity(file_size as usize);
-    out.extend_from_slice(b"MZ");
-    out.resize(PE_HEADER_OFFSET as usize, 0);
-    out[0x3C..0x40].copy_from_slice(&PE_HEADER_OFFSET.to_le_bytes());
-    let exe_path = Path::new(input).with_extension("exe");
-    let exe = exe_path.to_string_lossy().to_string();
-    let mut writer = io::BufWriter::with_capacity(file_size as usize, fs::File::create(exe)?);
-    write_all!(
-      writer,
+    let image_size = last_h.next_v_addr()?;
+    let file_size = last_h.next_r_ptr();
+    let (characteristics, image_base) = match edata_h {
+      Some(_) => (COFF_CHARACTERISTICS | IMAGE_FILE_DLL, DLL_IMAGE_BASE),
+      None => (COFF_CHARACTERISTICS, IMAGE_BASE),
+    };
+    let (export_rva, export_size) = edata_h.map_or((0, 0), |header| (header.v_addr, header.v_size));
+    let mut image = Vec::with_capacity(file_size as usize);
+    extend!(
+      image,
       *b"MZ",
       [0; PE_HEADER_OFFSET as usize - 6],
       PE_HEADER_OFFSET.to_le_bytes(),
       *b"PE\0\0",
       MACHINE_X64.to_le_bytes(),
-      NUMBER_OF_SECTIONS.to_le_bytes(),
-      (now().as_secs() as u32).to_le_bytes(),
+      u16::try_from(sect.len())?.to_le_bytes(),
+      self.timestamp.to_le_bytes(),
       [0; 8],
       OPTIONAL_HEADER_SIZE.to_le_bytes(),
-      COFF_CHARACTERISTICS.to_le_bytes(),
+      characteristics.to_le_bytes(),
       PE32PLUS.to_le_bytes(),
       VER_MAJOR_MINOR,
       sect[Text as usize].1.v_size.to_le_bytes(),
@@ -110,50 +178,51 @@ impl Assembler {
       sect[Bss as usize].1.v_size.to_le_bytes(),
       self.get_rva(self.root_id)?.to_le_bytes(),
       sect[Text as usize].1.v_addr.to_le_bytes(),
-      IMAGE_BASE.to_le_bytes(),
+      image_base.to_le_bytes(),
       SECTION_ALIGNMENT.to_le_bytes(),
       FILE_ALIGNMENT.to_le_bytes(),
       4u64.to_le_bytes(),
       0x2_0005u64.to_le_bytes(),
       image_size.to_le_bytes(),
       headers_size.to_le_bytes(),
-      0x03_0000_0000u64.to_le_bytes(),
+      [0; 4],
+      self.subsystem.value().to_le_bytes(),
+      DLL_CHARACTERISTICS.to_le_bytes(),
       0x00_0020_0000u64.to_le_bytes(),
       0x00_0000_1000u64.to_le_bytes(),
       0x00_0010_0000u64.to_le_bytes(),
       0x00_0000_1000u64.to_le_bytes(),
       0x10_0000_0000u64.to_le_bytes(),
-      [0; 8],
+      export_rva.to_le_bytes(),
+      export_size.to_le_bytes(),
       sect[IData as usize].1.v_addr.to_le_bytes(),
       self.sizeof_idt()?.to_le_bytes(),
-      [0; 8],
+      rsrc_rva.to_le_bytes(),
+      rsrc_size.to_le_bytes(),
       sect[PData as usize].1.v_addr.to_le_bytes(),
       sect[PData as usize].1.v_size.to_le_bytes(),
-      [0; 64],
+      [0; 8],
+      sect[Reloc as usize].1.v_addr.to_le_bytes(),
+      sect[Reloc as usize].1.v_size.to_le_bytes(),
+      self.debug_dir.map_or(0, |offset| self.rva[RData as usize] + offset as u32).to_le_bytes(),
+      (if self.debug_dir.is_some() { 28u32 } else { 0 }).to_le_bytes(),
+      [0; 40],
       (self.rva[IData as usize] + self.sizeof_idt()?).to_le_bytes(),
       self.sizeof_iat()?.to_le_bytes(),
       [0; 24],
-      sect[Text as usize].1.encode(),
-      sect[Data as usize].1.encode(),
-      sect[RData as usize].1.encode(),
-      sect[PData as usize].1.encode(),
-      sect[XData as usize].1.encode(),
-      sect[Bss as usize].1.encode(),
-      sect[IData as usize].1.encode(),
     );
-    let mut file = writer.into_inner().map_err(|err| err.into_error())?;
-    file.set_len(u64::from(file_size))?;
-    write_section(&mut file, &sect[Text as usize])?;
-    write_section(&mut file, &sect[Data as usize])?;
-    write_section(&mut file, &sect[RData as usize])?;
-    write_section(&mut file, &sect[PData as usize])?;
-    write_section(&mut file, &sect[XData as usize])?;
-    write_section(&mut file, &sect[IData as usize])?;
-    Ok(())
+    for (_, header) in sect {
+      extend!(image, header.encode());
+    }
+    image.resize(file_size as usize, 0);
+    for (data, header) in sect.iter().filter(|(data, _)| !data.is_empty()) {
+      let offset = header.r_ptr as usize;
+      image[offset..offset + data.len()].copy_from_slice(data);
+    }
+    Ok(image)
   }
 }
-fn write_section(file: &mut fs::File, (data, header): &(Vec<u8>, SectionHeader)) -> ErrOR<()> {
-  file.seek(io::SeekFrom::Start(u64::from(header.r_ptr)))?;
-  file.write_all(data)?;
-  Ok(())
+pub(crate) fn sizeof_unwind_info(saved: usize) -> ErrOR<u32> {
+  let codes = 4 + u32::try_from(saved)?;
+  Ok(4 + (codes + codes % 2) * 2 + 4)
 }
@@ -0,0 +1,45 @@
+use crate::prelude::*;
+impl Assembler {
+  pub(crate) fn peephole(&mut self, insts: &mut [Vec<Inst>]) -> ErrOR<u32> {
+    let (before, _) = self.layout_text(insts)?;
+    self.labels.clear();
+    for block in insts.iter_mut() {
+      while peephole_pass(block) {}
+    }
+    let (after, _) = self.layout_text(insts)?;
+    self.labels.clear();
+    Ok(before - after)
+  }
+}
+fn peephole_pass(block: &mut Vec<Inst>) -> bool {
+  let mut changed = false;
+  let mut out: Vec<Inst> = Vec::with_capacity(block.len());
+  for inst in take(block) {
+    match (out.iter().rfind(|prev| !matches!(prev, Loc(_))), inst) {
+      (Some(MovQQ((Mem(stored), Reg(src)))), MovQQ((Reg(dst), Mem(loaded))))
+        if *stored == loaded =>
+      {
+        let src_reg = *src;
+        if dst != src_reg {
+          out.push(mov_q(dst, src_reg));
+        }
+        changed = true;
+      }
+      (Some(Clear(prev)), Clear(reg)) if *prev == reg => changed = true,
+      (_, Lbl(id)) => {
+        let jump = out.iter().rposition(|prev| !matches!(prev, Lbl(_) | Loc(_)));
+        if let Some(idx) = jump
+          && matches!(out[idx], Jmp(target) | JCc(_, target) if target == id)
+        {
+          out[idx..].rotate_left(1);
+          out.pop();
+          changed = true;
+        }
+        out.push(Lbl(id));
+      }
+      (_, other) => out.push(other),
+    }
+  }
+  *block = out;
+  changed
+}
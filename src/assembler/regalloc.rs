@@ -0,0 +1,214 @@
+use crate::prelude::*;
+const HOMES: [Register; 3] = [R13, R14, R15];
+struct Interval {
+  end: usize,
+  home: Option<Register>,
+  slot: (i32, i32),
+  start: usize,
+  weight: u64,
+}
+pub(crate) fn allocate_registers(insts: &mut [Vec<Inst>], seh: &mut Seh) -> ErrOR<()> {
+  for block in insts.iter_mut() {
+    let (Some(&Lbl(id)), Some(&SubRId(Rsp, size))) = (block.first(), block.get(3)) else {
+      continue;
+    };
+    if !matches!(block[1..3], [Push(Rbp), MovQQ((Reg(Rbp), Reg(Rsp)))]) {
+      continue;
+    }
+    let saved = allocate_block(block);
+    if saved.is_empty() {
+      continue;
+    }
+    let shift = i32::try_from(saved.len() * 8)?;
+    let new_size = size + if saved.len() % 2 == 1 { 8 } else { 0 };
+    for inst in block.iter_mut() {
+      for addr in addresses(inst) {
+        if let Local(lifetime, offset) = *addr
+          && offset > 0
+        {
+          *addr = Local(lifetime, offset + shift);
+        }
+      }
+    }
+    let mut out = Vec::with_capacity(block.len() + saved.len() * 3);
+    out.extend_from_slice(&block[..2]);
+    out.extend(saved.iter().map(|reg| Push(*reg)));
+    out.extend_from_slice(&[block[2], SubRId(Rsp, new_size)]);
+    let mut idx = 4;
+    while let Some(inst) = block.get(idx) {
+      if matches!(inst, AddRId(Rsp, add) if *add == size)
+        && matches!(block.get(idx + 1), Some(Pop(Rbp)))
+      {
+        out.push(AddRId(Rsp, new_size));
+        out.extend(saved.iter().rev().map(|reg| Pop(*reg)));
+        out.push(Pop(Rbp));
+        idx += 2;
+      } else {
+        out.push(*inst);
+        idx += 1;
+      }
+    }
+    *block = out;
+    for entry in seh.iter_mut().filter(|entry| entry.0 == id) {
+      entry.2 = new_size.cast_signed();
+      entry.3.clone_from(&saved);
+    }
+  }
+  Ok(())
+}
+fn allocate_block(block: &mut [Inst]) -> Vec<Register> {
+  let mut intervals = intervals(block);
+  intervals.sort_by_key(|interval| interval.start);
+  let mut active: Vec<usize> = vec![];
+  for idx in 0..intervals.len() {
+    let start = intervals[idx].start;
+    active.retain(|&other| start <= intervals[other].end);
+    let used = active.iter().filter_map(|&other| intervals[other].home).collect::<Vec<_>>();
+    if let Some(free) = HOMES.into_iter().find(|reg| !used.contains(reg)) {
+      intervals[idx].home = Some(free);
+      active.push(idx);
+      continue;
+    }
+    if let Some(pos) = (0..active.len()).min_by_key(|&pos| intervals[active[pos]].weight)
+      && intervals[active[pos]].weight < intervals[idx].weight
+    {
+      let victim = active.swap_remove(pos);
+      intervals[idx].home = intervals[victim].home.take();
+      active.push(idx);
+    }
+  }
+  let homes = intervals
+    .iter()
+    .filter_map(|interval| Some((interval.slot, interval.home?)))
+    .collect::<BTreeMap<(i32, i32), Register>>();
+  for inst in block.iter_mut() {
+    if let Some((slot, promotable)) = slot_access(*inst)
+      && promotable
+      && let Some(home) = homes.get(&slot)
+    {
+      *inst = promote(*inst, *home);
+    }
+  }
+  HOMES.into_iter().filter(|reg| homes.values().any(|home| home == reg)).collect()
+}
+fn intervals(block: &[Inst]) -> Vec<Interval> {
+  let mut labels = BTreeMap::new();
+  let mut lea_floor = i32::MAX;
+  for (idx, inst) in block.iter().enumerate() {
+    if let Lbl(id) = inst {
+      labels.insert(*id, idx);
+    }
+    if let LeaRM(_, Local(_, offset)) = inst {
+      lea_floor = lea_floor.min(*offset);
+    }
+    if matches!(inst, MovQQ((_, Reg(Rbp) | Ref(Rbp)))) {
+      return vec![];
+    }
+  }
+  let mut loops = vec![];
+  for (idx, inst) in block.iter().enumerate() {
+    if let Jmp(target) | JCc(_, target) = inst
+      && let Some(&start) = labels.get(target)
+      && start < idx
+    {
+      loops.push((start, idx));
+    }
+  }
+  let mut slots: BTreeMap<(i32, i32), Interval> = BTreeMap::new();
+  let mut invalid = BTreeSet::new();
+  for (idx, inst) in block.iter().enumerate() {
+    let Some((slot, promotable)) = slot_access(*inst) else {
+      continue;
+    };
+    if !promotable || slot.0 >= 0 || slot.0 >= lea_floor {
+      invalid.insert(slot);
+    }
+    let depth = loops.iter().filter(|(start, end)| (*start..=*end).contains(&idx)).count();
+    let weight = 8u64.pow(u32::try_from(depth.min(6)).unwrap_or(6));
+    let interval =
+      slots.entry(slot).or_insert(Interval { end: idx, home: None, slot, start: idx, weight: 0 });
+    interval.end = idx;
+    interval.weight += weight;
+  }
+  let mut max_end = (i32::MIN, (0, 0));
+  for &(offset, size) in slots.keys() {
+    if offset < max_end.0 {
+      invalid.insert((offset, size));
+      invalid.insert(max_end.1);
+    }
+    if offset + size > max_end.0 {
+      max_end = (offset + size, (offset, size));
+    }
+  }
+  let mut intervals =
+    slots.into_values().filter(|interval| !invalid.contains(&interval.slot)).collect::<Vec<_>>();
+  let mut changed = true;
+  while changed {
+    changed = false;
+    for interval in &mut intervals {
+      for &(start, end) in &loops {
+        if interval.start <= end
+          && start <= interval.end
+          && (start < interval.start || interval.end < end)
+        {
+          interval.start = interval.start.min(start);
+          interval.end = interval.end.max(end);
+          changed = true;
+        }
+      }
+    }
+  }
+  intervals
+}
+fn slot_access(mut inst: Inst) -> Option<((i32, i32), bool)> {
+  if let MovQQ((Mem(Local(_, offset)), Reg(_)) | (Reg(_), Mem(Local(_, offset))))
+  | MovMSd(Local(_, offset), _)
+  | MovSdM(_, Local(_, offset)) = inst
+  {
+    return Some(((offset, 8), true));
+  }
+  if let MovBB((Mem(Local(_, offset)), Reg(_) | Imm(_)) | (Reg(_), Mem(Local(_, offset)))) = inst {
+    return Some(((offset, 1), true));
+  }
+  let offset = addresses(&mut inst).into_iter().find_map(|addr| match *addr {
+    Local(_, offset) => Some(offset),
+    Global(_) => None,
+  })?;
+  Some(((offset, 8), false))
+}
+fn promote(inst: Inst, home: Register) -> Inst {
+  if let MovMSd(_, xmm) = inst {
+    return MovRSd(home, xmm);
+  }
+  if let MovSdM(xmm, _) = inst {
+    return MovSdR(xmm, home);
+  }
+  if let MovQQ((dst, src)) = inst {
+    return MovQQ(if let Mem(_) = dst { (Reg(home), src) } else { (dst, Reg(home)) });
+  }
+  if let MovBB((dst, src)) = inst {
+    return MovBB(if let Mem(_) = dst { (Reg(home), src) } else { (dst, Reg(home)) });
+  }
+  inst
+}
+fn addresses(inst: &mut Inst) -> Vec<&mut Address> {
+  match inst {
+    DecMd(addr)
+    | IncMd(addr)
+    | LeaRM(_, addr)
+    | MovMSd(addr, _)
+    | MovSdM(_, addr)
+    | MovSxDRMd(_, addr) => vec![addr],
+    MovBB((dst, src)) => [dst, src].into_iter().filter_map(mem).collect(),
+    MovDD((dst, src)) => [dst, src].into_iter().filter_map(mem).collect(),
+    MovQQ((dst, src)) => [dst, src].into_iter().filter_map(mem).collect(),
+    AddRId(..) | AddRR(..) | ArithSd(..) | CMovCc(..) | Call(_) | CallApi(_) | CallApiCheck(_)
+    | Clear(_) | Custom(_) | CvtSi2Sd(..) | CvtTSd2Si(..) | DecR(_) | IDivR(_) | IMulRR(..)
+    | IncR(_) | JCc(..) | Jmp(_) | Lbl(_) | Loc(_) | LogicRR(..) | LogicRbRb(..) | MovRSd(..)
+    | MovRefSd(..) | MovSdR(..) | MovSdRef(..) | Pop(_) | Push(_) | SetCc(..) | ShiftR(..)
+    | SqrtSd(..) | SubRId(..) | SubRR(..) | UComISd(..) | UnaryR(..) | UnaryRb(..) => vec![],
+  }
+}
+fn mem<T>(operand: &mut Operand<T>) -> Option<&mut Address> {
+  if let Mem(addr) = operand { Some(addr) } else { None }
+}
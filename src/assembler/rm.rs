@@ -5,7 +5,6 @@ pub(crate) enum Scale {
   #[expect(dead_code)]
   S2 = 1,
   S4 = 2,
-  #[expect(dead_code)]
   S8 = 3,
 }
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -0,0 +1,194 @@
+use super::utility::align_up;
+use crate::prelude::*;
+const LANG_EN_US: u16 = 0x0409;
+const MANIFEST: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<assembly xmlns="urn:schemas-microsoft-com:asm.v1" manifestVersion="1.0">
+  <compatibility xmlns="urn:schemas-microsoft-com:compatibility.v1">
+    <application>
+      <supportedOS Id="{8e0f7a12-bfb3-4fe8-b9a5-48fd50a15a9a}"/>
+    </application>
+  </compatibility>
+  <application xmlns="urn:schemas-microsoft-com:asm.v3">
+    <windowsSettings>
+      <dpiAware xmlns="http://schemas.microsoft.com/SMI/2005/WindowsSettings">true/pm</dpiAware>
+      <dpiAwareness xmlns="http://schemas.microsoft.com/SMI/2016/WindowsSettings">PerMonitorV2</dpiAwareness>
+      <activeCodePage xmlns="http://schemas.microsoft.com/SMI/2019/WindowsSettings">UTF-8</activeCodePage>
+    </windowsSettings>
+  </application>
+</assembly>
+"#;
+const RT_GROUP_ICON: u16 = 14;
+const RT_ICON: u16 = 3;
+const RT_MANIFEST: u16 = 24;
+const RT_VERSION: u16 = 16;
+const UNICODE_CP: u16 = 1200;
+#[derive(Debug, Default)]
+pub(crate) struct Resources {
+  pub icon: Option<String>,
+  pub manifest: bool,
+  pub version: Vec<(String, String)>,
+}
+impl Resources {
+  pub(crate) fn is_empty(&self) -> bool {
+    self.icon.is_none() && !self.manifest && self.version.is_empty()
+  }
+}
+type ResourceTree = BTreeMap<u16, BTreeMap<u16, Vec<u8>>>;
+pub(crate) fn build_rsrc(resources: &Resources, v_addr: u32, is_dll: bool) -> ErrOR<Vec<u8>> {
+  let mut tree = ResourceTree::new();
+  if let Some(path) = &resources.icon {
+    icon_resources(&fs::read(path)?, path, &mut tree)?;
+  }
+  if resources.manifest {
+    tree.entry(RT_MANIFEST).or_default().insert(if is_dll { 2 } else { 1 }, MANIFEST.into());
+  }
+  if !resources.version.is_empty() {
+    tree.entry(RT_VERSION).or_default().insert(1, version_info(&resources.version, is_dll)?);
+  }
+  let leaves = tree.values().map(BTreeMap::len).sum::<usize>();
+  let mut dir_size = 16 + 8 * tree.len();
+  for ids in tree.values() {
+    dir_size += 16 + 8 * ids.len() + (16 + 8) * ids.len();
+  }
+  let mut data_offset = align_up(dir_size + 16 * leaves, 8)?;
+  let mut rsrc = vec![];
+  let mut entries = vec![];
+  let mut blobs = vec![];
+  let mut next_dir = 16 + 8 * tree.len();
+  extend!(rsrc, directory_header(tree.len())?);
+  for (kind, ids) in &tree {
+    extend!(rsrc, directory_entry(*kind, next_dir, true)?);
+    next_dir += 16 + 8 * ids.len();
+  }
+  let mut next_entry = dir_size;
+  for ids in tree.values() {
+    extend!(rsrc, directory_header(ids.len())?);
+    for id in ids.keys() {
+      extend!(rsrc, directory_entry(*id, next_dir, true)?);
+      next_dir += 16 + 8;
+    }
+  }
+  for data in tree.values().flat_map(BTreeMap::values) {
+    extend!(rsrc, directory_header(1)?, directory_entry(LANG_EN_US, next_entry, false)?);
+    next_entry += 16;
+    extend!(
+      entries,
+      (v_addr + u32::try_from(data_offset)?).to_le_bytes(),
+      len_u32(data)?.to_le_bytes(),
+      [0; 8]
+    );
+    extend!(blobs, data);
+    blobs.resize(align_up(blobs.len(), 8)?, 0);
+    data_offset += align_up(data.len(), 8)?;
+  }
+  extend!(rsrc, entries);
+  rsrc.resize(align_up(rsrc.len(), 8)?, 0);
+  extend!(rsrc, blobs);
+  Ok(rsrc)
+}
+fn directory_entry(id: u16, offset: usize, is_dir: bool) -> ErrOR<Vec<u8>> {
+  let field = u32::try_from(offset)? | if is_dir { 0x8000_0000 } else { 0 };
+  let mut entry = vec![];
+  extend!(entry, u32::from(id).to_le_bytes(), field.to_le_bytes());
+  Ok(entry)
+}
+fn directory_header(id_entries: usize) -> ErrOR<Vec<u8>> {
+  let mut header = vec![0; 14];
+  extend!(header, u16::try_from(id_entries)?.to_le_bytes());
+  Ok(header)
+}
+fn icon_resources(ico: &[u8], path: &str, tree: &mut ResourceTree) -> ErrOR<()> {
+  let invalid = || Platform(format!("Invalid icon file: `{path}`"));
+  let u16_at = |offset: usize| {
+    ico
+      .get(offset..offset + 2)
+      .and_then(|bytes| bytes.try_into().ok())
+      .map(u16::from_le_bytes)
+      .ok_or_else(invalid)
+  };
+  let u32_at = |offset: usize| -> ErrOR<u32> {
+    Ok(u32::from(u16_at(offset)?) | (u32::from(u16_at(offset + 2)?) << 16))
+  };
+  if u16_at(0)? != 0 || u16_at(2)? != 1 || u16_at(4)? == 0 {
+    return Err(invalid());
+  }
+  let count = u16_at(4)?;
+  let mut group = vec![];
+  extend!(group, [0, 0, 1, 0], count.to_le_bytes());
+  for idx in 0..count {
+    let entry = 6 + usize::from(idx) * 16;
+    let (size, offset) = (u32_at(entry + 8)? as usize, u32_at(entry + 12)? as usize);
+    let image = ico.get(offset..offset + size).ok_or_else(invalid)?;
+    extend!(group, ico[entry..entry + 12], (idx + 1).to_le_bytes());
+    tree.entry(RT_ICON).or_default().insert(idx + 1, image.to_vec());
+  }
+  tree.entry(RT_GROUP_ICON).or_default().insert(1, group);
+  Ok(())
+}
+fn parse_version(version: &str) -> ErrOR<[u32; 2]> {
+  let invalid = || Platform(format!("Invalid version: `{version}`"));
+  let mut parts = [0u16; 4];
+  for (idx, part) in version.split('.').enumerate() {
+    *parts.get_mut(idx).ok_or_else(invalid)? = part.trim().parse().ok().ok_or_else(invalid)?;
+  }
+  Ok([
+    (u32::from(parts[0]) << 16) | u32::from(parts[1]),
+    (u32::from(parts[2]) << 16) | u32::from(parts[3]),
+  ])
+}
+fn version_info(strings: &[(String, String)], is_dll: bool) -> ErrOR<Vec<u8>> {
+  let lookup =
+    |key: &str| strings.iter().find(|(name, _)| name == key).map(|(_, value)| value.as_str());
+  let file_version = parse_version(lookup("FileVersion").unwrap_or("0"))?;
+  let product_version = match lookup("ProductVersion") {
+    Some(version) => parse_version(version)?,
+    None => file_version,
+  };
+  let mut fixed = vec![];
+  extend!(fixed, 0xFEEF_04BDu32.to_le_bytes(), 0x1_0000u32.to_le_bytes());
+  for half in file_version.iter().chain(&product_version) {
+    extend!(fixed, half.to_le_bytes());
+  }
+  extend!(
+    fixed,
+    0x3Fu32.to_le_bytes(),
+    [0; 4],
+    0x4_0004u32.to_le_bytes(),
+    (if is_dll { 2u32 } else { 1 }).to_le_bytes(),
+    [0; 12]
+  );
+  let mut entries = vec![];
+  for (key, value) in strings {
+    let text = value.encode_utf16().chain([0]).flat_map(u16::to_le_bytes).collect::<Vec<u8>>();
+    entries.push(version_node(key, &text, u16::try_from(text.len() / 2)?, true, &[])?);
+  }
+  let table = version_node(&format!("{LANG_EN_US:04X}{UNICODE_CP:04X}"), &[], 0, true, &entries)?;
+  let string_info = version_node("StringFileInfo", &[], 0, true, &[table])?;
+  let mut translation = vec![];
+  extend!(translation, LANG_EN_US.to_le_bytes(), UNICODE_CP.to_le_bytes());
+  let var = version_node("Translation", &translation, 4, false, &[])?;
+  let var_info = version_node("VarFileInfo", &[], 0, true, &[var])?;
+  version_node("VS_VERSION_INFO", &fixed, 52, false, &[string_info, var_info])
+}
+fn version_node(
+  key: &str,
+  value: &[u8],
+  value_len: u16,
+  is_text: bool,
+  children: &[Vec<u8>],
+) -> ErrOR<Vec<u8>> {
+  let mut node = vec![0; 2];
+  extend!(node, value_len.to_le_bytes(), u16::from(is_text).to_le_bytes());
+  for unit in key.encode_utf16().chain([0]) {
+    extend!(node, unit.to_le_bytes());
+  }
+  node.resize(align_up(node.len(), 4)?, 0);
+  extend!(node, value);
+  for child in children {
+    node.resize(align_up(node.len(), 4)?, 0);
+    extend!(node, child);
+  }
+  let len = u16::try_from(node.len())?.to_le_bytes();
+  node[..2].copy_from_slice(&len);
+  Ok(node)
+}
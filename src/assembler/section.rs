@@ -10,6 +10,9 @@ pub(crate) enum Section {
   XData,
   Bss,
   IData,
+  Reloc,
+  Rsrc,
+  EData,
 }
 #[derive(Debug, Clone, Copy)]
 #[expect(clippy::arbitrary_source_item_ordering)]
@@ -19,7 +22,7 @@ pub(crate) struct SectionHeader {
   pub v_addr: u32,
   pub r_size: u32,
   pub r_ptr: u32,
-  characteristics: u32,
+  pub characteristics: u32,
 }
 impl SectionHeader {
   pub(crate) fn encode(&self) -> Vec<u8> {
@@ -46,6 +49,9 @@ impl SectionHeader {
       characteristics: sect.characteristics(),
     }
   }
+  pub(crate) fn is(&self, sect: Section) -> bool {
+    self.name == sect.name()
+  }
   pub(crate) fn next(&self, sect: Section, v_size: u32) -> ErrOR<Self> {
     Ok(SectionHeader {
       name: sect.name(),
@@ -69,7 +75,8 @@ impl Section {
       Text => 0x6000_0020,
       Data | IData => 0xC000_0040,
       Bss => 0xC000_0080,
-      RData | PData | XData => 0x4000_0040,
+      RData | PData | XData | Rsrc | EData => 0x4000_0040,
+      Reloc => 0x4200_0040,
     }
   }
   pub(crate) fn name(self) -> [u8; 8] {
@@ -81,6 +88,9 @@ impl Section {
       XData => *b".xdata\0\0",
       Bss => *b".bss\0\0\0\0",
       IData => *b".idata\0\0",
+      Reloc => *b".reloc\0\0",
+      Rsrc => *b".rsrc\0\0\0",
+      EData => *b".edata\0\0",
     }
   }
 }
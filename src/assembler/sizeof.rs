@@ -16,7 +16,7 @@ impl Assembler {
       Custom(bytes) => len_u32(bytes)?,
       UnaryR(..) | LogicRR(..) | IncR(_) | DecR(_) | IDivR(_) | SubRR(..) | AddRR(..) => 3,
       CMovCc(..) | IMulRR(..) => 4,
-      CvtSi2Sd(..) | CvtTSd2Si(..) | Call(_) => 5,
+      CvtSi2Sd(..) | CvtTSd2Si(..) | MovRSd(..) | MovSdR(..) | Call(_) => 5,
       CallApi(_) => 6,
       SubRId(..) | AddRId(..) => 7,
       JCc(_, id) => self.sizeof_jmp(*id, size, 6)?,
@@ -27,7 +27,7 @@ impl Assembler {
         1 + (xmm.rex_size() | xmm2.rex_size()) + 2 + 1
       }
       MovSdRef(xmm, reg) | MovRefSd(reg, xmm) => {
-        1 + (reg.rex_size() | xmm.rex_size()) + 2 + 1 + Disp::Zero.sizeof(reg.reg_bits())
+        1 + (reg.rex_size() | xmm.rex_size()) + 2 + 1 + sizeof_ref(*reg)
       }
       MovSxDRMd(_, addr) | LeaRM(_, addr) => 2 + addr.modrm_sib_disp(),
       MovSdM(xmm, addr) | MovMSd(addr, xmm) => 1 + xmm.rex_size() + 2 + addr.modrm_sib_disp(),
@@ -39,34 +39,46 @@ impl Assembler {
       LogicRbRb(_, dst, src) => (dst.rex_size() | src.rex_size()) + 2,
       Pop(reg) | Push(reg) => reg.rex_size() + 1,
       SetCc(reg, _) => reg.rex_size() + 3,
-      Lbl(_) => 0,
+      Lbl(_) | Loc(_) => 0,
     })
   }
   fn sizeof_jmp(&self, id: LabelId, size: u32, long: u32) -> ErrOR<u32> {
     if let Some((Text, offset)) = self.labels.get(&id)
-      && i8::try_from(i32::try_from(*offset)? - 2 - i32::try_from(size)?).is_ok_and(i8::is_negative)
+      && i8::try_from(i64::from(*offset) - 2 - i64::from(size)).is_ok()
     {
       Ok(2)
     } else {
       Ok(long)
     }
   }
+  pub(crate) fn sizeof_relaxed(&self, inst: &Inst, offset: u32, prev_size: u32) -> ErrOR<u32> {
+    if let Jmp(id) | JCc(_, id) = inst
+      && let Some((Text, label)) = self.labels.get(id)
+      && *label > offset
+      && i8::try_from(label - offset - prev_size).is_ok()
+    {
+      return Ok(2);
+    }
+    self.sizeof_inst(inst, offset)
+  }
 }
 pub(crate) fn sizeof_mov_q(operands: (Operand<u64>, Operand<u64>)) -> ErrOR<u32> {
   Ok(match operands {
     (Reg(_), Reg(_)) => 1 + 2,
-    (Reg(_), Ref(reg)) | (Ref(reg), Reg(_)) => 1 + 2 + u32::from(reg.reg_bits() == Rsp as u8),
+    (Reg(_), Ref(reg)) | (Ref(reg), Reg(_)) => 1 + 2 + sizeof_ref(reg),
     (Reg(_), Mem(addr)) | (Mem(addr), Reg(_)) => 1 + 1 + addr.modrm_sib_disp(),
     (Reg(_), Args(nth)) | (Args(nth), Reg(_)) => 4 + Disp::from((nth - 1) << 3).sizeof(Rsp as u8),
     (Reg(_), Imm(_)) => 1 + 1 + 8,
-    (SibDisp(sib, disp), Reg(_)) => 1 + 3 + disp.sizeof(sib.base.reg_bits()),
+    (Reg(_), SibDisp(sib, disp)) | (SibDisp(sib, disp), Reg(_)) => {
+      1 + 3 + disp.sizeof(sib.base.reg_bits())
+    }
     _ => return Err(Internal(InvalidInst(format!("MovQQ{operands:?}")))),
   })
 }
 pub(crate) fn sizeof_mov_b(operands: (Operand<u8>, Operand<u8>)) -> ErrOR<u32> {
   Ok(match operands {
     (Reg(reg), Ref(mem)) | (Ref(mem), Reg(reg)) => {
-      (reg.rex_size() | mem.rex_size()) + 2 + Disp::Zero.sizeof(mem.reg_bits())
+      (reg.rex_size() | mem.rex_size()) + 2 + sizeof_ref(mem)
     }
     (Reg(dst), Reg(src)) => (dst.rex_size() | src.rex_size()) + 2,
     (Reg(reg), Mem(addr)) | (Mem(addr), Reg(reg)) => reg.rex_size() + 1 + addr.modrm_sib_disp(),
@@ -84,7 +96,7 @@ pub(crate) fn sizeof_mov_d(operands: (Operand<u32>, Operand<u32>)) -> ErrOR<u32>
   Ok(match operands {
     (Reg(dst), Reg(src)) => (dst.rex_size() | src.rex_size()) + 2,
     (Reg(reg), Ref(mem)) | (Ref(mem), Reg(reg)) => {
-      (reg.rex_size() | mem.rex_size()) + 2 + Disp::Zero.sizeof(mem.reg_bits())
+      (reg.rex_size() | mem.rex_size()) + 2 + sizeof_ref(mem)
     }
     (Reg(reg), Mem(addr)) | (Mem(addr), Reg(reg)) => reg.rex_size() + 1 + addr.modrm_sib_disp(),
     (Mem(addr), Imm(_)) => 1 + addr.modrm_sib_disp() + 4,
@@ -97,3 +109,6 @@ pub(crate) fn sizeof_mov_d(operands: (Operand<u32>, Operand<u32>)) -> ErrOR<u32>
     _ => return Err(Internal(InvalidInst(format!("MovDD{operands:?}")))),
   })
 }
+fn sizeof_ref(base: Register) -> u32 {
+  u32::from(base.reg_bits() == Rsp as u8) + Disp::Zero.sizeof(base.reg_bits())
+}
@@ -0,0 +1,69 @@
+use super::utility::align_up;
+use crate::prelude::*;
+impl Assembler {
+  pub(crate) fn fill_src_map(&self, rdata: &mut [u8], ranges: &SourceMap) -> ErrOR<()> {
+    let Some(id) = self.src_map else {
+      return Ok(());
+    };
+    let map_rva = self.get_rva(id)?;
+    let mut table = vec![];
+    extend!(table, map_rva.to_le_bytes(), len_u32(ranges)?.to_le_bytes());
+    for (start, end, loc) in ranges {
+      extend!(table, start.to_le_bytes(), end.to_le_bytes(), self.get_rva(*loc)?.to_le_bytes());
+    }
+    let offset = (map_rva - self.rva[RData as usize]) as usize;
+    rdata
+      .get_mut(offset..offset + table.len())
+      .ok_or(Internal(UnknownLabel))?
+      .copy_from_slice(&table);
+    Ok(())
+  }
+  pub(crate) fn function_ranges(
+    &self,
+    insts: &[Vec<Inst>],
+    sizes: &[u32],
+  ) -> Vec<(LabelId, u32, u32)> {
+    let mut functions = vec![];
+    let mut offset = self.rva[Text as usize];
+    let mut sizes_iter = sizes.iter();
+    for block in insts {
+      let size = sizes_iter.by_ref().take(block.len()).sum::<u32>();
+      if let Some(Lbl(id)) = block.first() {
+        functions.push((*id, offset, size));
+      }
+      offset += size;
+    }
+    functions
+  }
+  pub(crate) fn reserve_src_map(&mut self, rdata: &mut Vec<u8>, count: usize) -> ErrOR<()> {
+    if let Some(id) = self.src_map {
+      rdata.resize(align_up(rdata.len(), 4)?, 0);
+      self.labels.insert(id, (RData, len_u32(rdata)?));
+      rdata.resize(rdata.len() + 8 + 12 * count, 0);
+    }
+    Ok(())
+  }
+  pub(crate) fn source_ranges(&self, insts: &[Vec<Inst>], sizes: &[u32]) -> SourceMap {
+    let mut ranges = vec![];
+    let mut offset = self.rva[Text as usize];
+    let mut sizes_iter = sizes.iter();
+    for block in insts {
+      let mut current = None;
+      for (inst, size) in block.iter().zip(sizes_iter.by_ref()) {
+        if let Loc(id) = inst
+          && let Some((start, loc)) = current.replace((offset, *id))
+          && start < offset
+        {
+          ranges.push((start, offset, loc));
+        }
+        offset += size;
+      }
+      if let Some((start, loc)) = current
+        && start < offset
+      {
+        ranges.push((start, offset, loc));
+      }
+    }
+    ranges
+  }
+}
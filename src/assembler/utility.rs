@@ -18,8 +18,28 @@ impl Assembler {
     }
     Ok(self.rva[IData as usize] + lookup_offset + func_idx * 8)
   }
-  pub(crate) fn new(dlls: Vec<Dll>, root_id: LabelId, handlers: Handlers) -> Self {
-    Self { labels: HashMap::new(), rva: [0; NUMBER_OF_SECTIONS as usize], dlls, root_id, handlers }
+  pub(crate) fn new(
+    dlls: Vec<Dll>,
+    root_id: LabelId,
+    handlers: Handlers,
+    target: Target,
+    subsystem: Subsystem,
+    pdb: Option<String>,
+    timestamp: u32,
+  ) -> Self {
+    Self {
+      debug_dir: None,
+      labels: HashMap::new(),
+      pdb,
+      rva: [0; NUMBER_OF_SECTIONS as usize],
+      src_map: None,
+      dlls,
+      root_id,
+      handlers,
+      subsystem,
+      target,
+      timestamp,
+    }
   }
 }
 impl Inst {
@@ -38,6 +58,7 @@ impl Inst {
       | LeaRM(dst, _)
       | LogicRR(And | Or | Xor, dst, _)
       | LogicRbRb(And | Or | Xor, dst, _)
+      | MovRSd(dst, _)
       | MovSxDRMd(dst, _)
       | UnaryR(_, dst)
       | UnaryRb(_, dst)
@@ -64,9 +85,11 @@ impl Inst {
       | MovMSd(..)
       | MovRefSd(..)
       | MovSdM(..)
+      | MovSdR(..)
       | MovSdRef(..)
       | Push(_)
       | Jmp(_)
+      | Loc(_)
       | UComISd(..) => vec![],
       Call(_) | CallApi(_) | CallApiCheck(_) => vec![Rax, Rcx, Rdx, R8, R9, R10, R11],
       Custom(_) | Lbl(_) => {
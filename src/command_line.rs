@@ -1,24 +1,43 @@
 use crate::prelude::*;
-use std::process::Command;
-macro_rules! next_file {
-  ($args:ident, $program_name:ident) => {{
-    let Some(next_file) = $args.next() else {
-      help_message(&$program_name);
-      return Ok(None);
-    };
-    next_file
-  }};
-}
-struct CmdLineInfo<I>
-where
-  I: Iterator<Item = String>,
-{
+use std::{path::PathBuf, process::Command};
+#[derive(Default)]
+struct CmdLineInfo {
   build_only: bool,
-  exe_args: I,
+  deny_warnings: bool,
+  deterministic: bool,
+  emit: Emit,
+  exe_args: Vec<String>,
   file: String,
+  interpret: bool,
+  out_dir: Option<String>,
+  output: Option<String>,
+  pdb: bool,
   release: bool,
+  resources: Resources,
+  source_map: bool,
+  subsystem: Option<Subsystem>,
+  target: Target,
+  verbose: bool,
 }
 impl Jsonpiler {
+  pub(crate) fn default_subsystem(&self) -> Subsystem {
+    let imports = |func: &str| {
+      self.dlls.iter().any(|(dll, funcs)| dll == USER32 && funcs.iter().any(|name| name == func))
+    };
+    let console_io = self.symbols.contains_key(PRINT) || self.symbols.contains_key(INPUT);
+    if !console_io && (imports("CreateWindowExW") || imports("MessageBoxW")) {
+      Subsystem::Windows
+    } else {
+      Subsystem::Console
+    }
+  }
+  fn deny_warnings(&self) -> ErrOR<()> {
+    let count = self.parsers.iter().map(|parser| parser.val.warns.len()).sum::<usize>();
+    if count == 0 {
+      return Ok(());
+    }
+    Err(Platform(format!("{count} warning(s) denied by `--deny-warnings`")))
+  }
   #[inline]
   pub fn main<I>(&mut self, args: I) -> Result<i32, String>
   where
@@ -30,18 +49,38 @@ impl Jsonpiler {
   where
     I: IntoIterator<Item = String>,
   {
-    let Some(CmdLineInfo { file, build_only, release, exe_args }) = parse_command_line(args)?
+    let Some(CmdLineInfo {
+      build_only,
+      deny_warnings,
+      deterministic,
+      emit,
+      exe_args,
+      file,
+      interpret,
+      out_dir,
+      output,
+      pdb,
+      release,
+      resources,
+      source_map,
+      subsystem: forced_subsystem,
+      target,
+      verbose,
+    }) = parse_command_line(args, &self.extensions)?
     else {
       return Ok(0);
     };
+    self.emit = emit;
     self.release = release;
-    if fs::metadata(&file)?.len() > u64::from(GB) {
-      return Err(Compilation(TooLargeFile, vec![]));
-    }
-    let source = fs::read_to_string(&file)?;
-    let exe_path = Path::new(&file).with_extension("exe");
-    let exe = exe_path.to_string_lossy().to_string();
-    let full = full_path(&file)?;
+    self.target = target;
+    let source = self.provider.read(&file, GB.into()).map_err(|err| {
+      if err.kind() == io::ErrorKind::FileTooLarge {
+        Compilation(TooLargeFile, vec![])
+      } else {
+        err.into()
+      }
+    })?;
+    let full = self.provider.resolve("", &file)?;
     let first_parser = self.push_parser(source, full.clone())?;
     let parsed = match Path::new(&file).extension().map(|ext| ext.to_string_lossy()) {
       Some(jspl) if jspl == "jspl" => first_parser.parse_jspl(),
@@ -49,27 +88,114 @@ impl Jsonpiler {
       _ => return Err(Compilation(UnsupportedFile, vec![])),
     }
     .map_err(Into::<JsonpilerErr>::into)?;
+    if interpret {
+      return self.interpret(parsed);
+    }
     self.compile(parsed)?;
-    let (insts, seh) = self.build_functions()?;
-    let assembler =
-      Assembler::new(take(&mut self.dlls), self.first_parser()?.val.dep.id, self.handlers);
-    assembler.assemble(&insts, take(&mut self.data), &full, seh)?;
-    if build_only {
+    if deny_warnings {
+      self.deny_warnings()?;
+    }
+    let mut exports = vec![];
+    let mut entry = self.first_parser()?.val.dep.id;
+    let windows_only = match emit {
+      Emit::Dll => Some("build dll"),
+      Emit::Obj => Some("--emit obj"),
+      Emit::Asm | Emit::Exe | Emit::Map => None,
+    };
+    let resource_option = (!resources.is_empty()).then_some("--icon/--manifest/--version-info");
+    let subsystem_option = forced_subsystem.is_some().then_some("--subsystem");
+    let pdb_option = pdb.then_some("--pdb");
+    if let Some(mode) = windows_only.or(resource_option).or(subsystem_option).or(pdb_option)
+      && target != Target::Windows
+    {
+      return Err(Platform(format!("`{mode}` requires the x86_64-windows target")));
+    }
+    match emit {
+      Emit::Dll => (entry, exports) = self.dll_entry()?,
+      Emit::Obj => (entry, exports) = self.obj_entry()?,
+      Emit::Asm | Emit::Exe | Emit::Map => (),
+    }
+    let (mut insts, mut seh) = self.build_functions()?;
+    if target == Target::Linux {
+      entry = self.link_linux_runtime(&mut insts)?;
+    }
+    if release {
+      allocate_registers(&mut insts, &mut seh)?;
+      let saved = Assembler::new(vec![], entry, self.handlers, target, Subsystem::Console, None, 0)
+        .peephole(&mut insts)?;
+      if verbose {
+        optimized_message(saved);
+      }
+    }
+    let default_path = Path::new(&full).with_extension(emit.extension(target));
+    let out_path = match (output, out_dir) {
+      (Some(path), _) => PathBuf::from(path),
+      (None, Some(dir)) => Path::new(&dir).join(default_path.file_name().unwrap_or_default()),
+      (None, None) => default_path,
+    };
+    if emit == Emit::Asm {
+      let listing = self.listing(&insts, &self.data)?;
+      fs::write(&out_path, listing)?;
+      return Ok(0);
+    }
+    let subsystem = forced_subsystem.unwrap_or_else(|| self.default_subsystem());
+    let pdb_path = pdb.then(|| out_path.with_extension("pdb").to_string_lossy().to_string());
+    let assembler = Assembler::new(
+      take(&mut self.dlls),
+      entry,
+      self.handlers,
+      target,
+      subsystem,
+      pdb_path.clone(),
+      build_timestamp(deterministic)?,
+    );
+    if emit == Emit::Obj {
+      assembler.assemble_obj(&insts, take(&mut self.data), &out_path, seh, &exports)?;
+      return Ok(0);
+    }
+    let dll_exports = (emit == Emit::Dll).then_some(exports.as_slice());
+    let image =
+      assembler.assemble(&insts, take(&mut self.data), &full, seh, dll_exports, &resources)?;
+    if emit == Emit::Map {
+      self.write_source_map(&image.ranges, &out_path)?;
+      return Ok(0);
+    }
+    fs::write(&out_path, &image.bytes)?;
+    #[cfg(unix)]
+    if target == Target::Linux {
+      use std::os::unix::fs::PermissionsExt as _;
+      fs::set_permissions(&out_path, fs::Permissions::from_mode(0o755))?;
+    }
+    if source_map {
+      self.write_source_map(&image.ranges, &out_path.with_extension("map.json"))?;
+    }
+    if let Some(path) = pdb_path {
+      self.write_pdb_file(&image, &path)?;
+    }
+    if build_only || emit == Emit::Dll {
       return Ok(0);
     }
-    check_platform()?;
-    let exe_full = env::current_dir()?.join(exe);
+    check_platform(target)?;
+    let exe_full = env::current_dir()?.join(out_path);
     let status = Command::new(exe_full).args(exe_args).status()?;
     Ok(status.code().unwrap_or(0))
   }
 }
 #[expect(clippy::print_stdout)]
 fn help_message(program_name: &str) {
-  println!("Usage: {program_name} <input.jspl | input.json> [args for .exe]{COMMAND}");
+  println!(
+    "Usage: {program_name} [command] [options] <input.jspl | input.json> [-- args]{COMMAND}"
+  );
 }
-fn check_platform() -> ErrOR<()> {
-  if !cfg!(target_os = "windows") {
-    return Err(platform_err("Windows x64"));
+#[expect(clippy::print_stderr)]
+fn optimized_message(saved: u32) {
+  eprintln!("Peephole optimizer saved {saved} bytes of .text");
+}
+fn check_platform(target: Target) -> ErrOR<()> {
+  match target {
+    Target::Linux if !cfg!(target_os = "linux") => return Err(platform_err("Linux x64")),
+    Target::Windows if !cfg!(target_os = "windows") => return Err(platform_err("Windows x64")),
+    Target::Linux | Target::Windows => (),
   }
   if !cfg!(target_arch = "x86_64") {
     return Err(platform_err("x86_64 architecture"));
@@ -82,57 +208,128 @@ fn check_platform() -> ErrOR<()> {
 fn platform_err(requirement: &'static str) -> JsonpilerErr {
   Platform(format!("The generated executable requires {requirement}"))
 }
+pub(crate) fn build_timestamp(deterministic: bool) -> ErrOR<u32> {
+  if let Ok(epoch) = env::var("SOURCE_DATE_EPOCH") {
+    return epoch
+      .trim()
+      .parse()
+      .ok()
+      .ok_or_else(|| Platform(format!("Invalid SOURCE_DATE_EPOCH: `{epoch}`")));
+  }
+  Ok(if deterministic { 0 } else { u32::try_from(now().as_secs())? })
+}
 fn full_path(file: &str) -> ErrOR<String> {
   Ok(env::current_dir()?.join(file).canonicalize()?.to_string_lossy().to_string())
 }
+fn option_value<I>(args: &mut I, option: &str) -> ErrOR<String>
+where
+  I: Iterator<Item = String>,
+{
+  args.next().ok_or_else(|| Platform(format!("Missing value for `{option}`")))
+}
 #[expect(clippy::print_stdout)]
-fn parse_command_line<I>(args: I) -> ErrOR<Option<CmdLineInfo<I::IntoIter>>>
+fn parse_command_line<I>(args: I, extensions: &[Extension]) -> ErrOR<Option<CmdLineInfo>>
 where
   I: IntoIterator<Item = String>,
 {
   let mut args_iter = args.into_iter();
   let program_name = args_iter.next().unwrap_or(PKG_NAME.into());
-  let mut file = next_file!(args_iter, program_name);
-  let mut build_only = false;
-  let mut release = false;
-  match file.as_ref() {
-    "server" => {
-      let mut server = Server::new();
-      server.main();
-    }
-    "help" => help_message(&program_name),
-    "version" => println!("{PKG_NAME} version {VERSION}"),
+  let Some(first) = args_iter.next() else {
+    help_message(&program_name);
+    return Ok(None);
+  };
+  match first.as_ref() {
+    "server" => Server::new(extensions.to_vec()).main(),
+    "help" | "-h" | "--help" => {
+      help_message(&program_name);
+      return Ok(None);
+    }
+    "version" | "-V" | "--version" => {
+      println!("{PKG_NAME} version {VERSION}");
+      return Ok(None);
+    }
+    "disasm" => {
+      let file = option_value(&mut args_iter, &first)?;
+      print!("{}", disassemble(&fs::read(&file)?)?);
+      return Ok(None);
+    }
     "format" => {
-      file = next_file!(args_iter, program_name);
+      let file = option_value(&mut args_iter, &first)?;
       let source = fs::read_to_string(&file)?;
       let full_path = full_path(&file)?;
       let mut parser = <Pos<Parser>>::new(source, 0, full_path, 0);
       if let Some(out) = parser.format() {
         fs::write(file, out)?;
       }
+      return Ok(None);
     }
-    _ => {
-      match file.as_ref() {
-        "build" => {
-          build_only = true;
-          file = next_file!(args_iter, program_name);
-          if file == "release" {
-            release = true;
-            file = next_file!(args_iter, program_name);
-          }
-        }
-        "release" => {
-          release = true;
-          file = next_file!(args_iter, program_name);
-          if file == "build" {
-            build_only = true;
-            file = next_file!(args_iter, program_name);
-          }
-        }
-        _ => (),
+    _ => (),
+  }
+  let mut info = CmdLineInfo::default();
+  let mut input = None;
+  let mut rest = iter::once(first).chain(args_iter);
+  while let Some(arg) = rest.next() {
+    match arg.as_ref() {
+      "--" => {
+        info.exe_args.extend(rest.by_ref());
+        break;
+      }
+      "build" if input.is_none() => info.build_only = true,
+      "dll" if input.is_none() && info.build_only => info.emit = Emit::Dll,
+      "interpret" if input.is_none() => info.interpret = true,
+      "release" if input.is_none() => info.release = true,
+      "-W" | "--deny-warnings" => info.deny_warnings = true,
+      "-h" | "--help" => {
+        help_message(&program_name);
+        return Ok(None);
+      }
+      "-o" | "--output" => info.output = Some(option_value(&mut rest, &arg)?),
+      "--deterministic" => info.deterministic = true,
+      "--emit" => {
+        let name = option_value(&mut rest, &arg)?;
+        info.emit =
+          Emit::from_name(&name).ok_or_else(|| Platform(format!("Unknown emit kind: `{name}`")))?;
+      }
+      "--icon" => info.resources.icon = Some(option_value(&mut rest, &arg)?),
+      "--manifest" => info.resources.manifest = true,
+      "--out-dir" => info.out_dir = Some(option_value(&mut rest, &arg)?),
+      "--pdb" => info.pdb = true,
+      "--release" => info.release = true,
+      "--source-map" => info.source_map = true,
+      "--subsystem" => {
+        let name = option_value(&mut rest, &arg)?;
+        info.subsystem = Some(
+          Subsystem::from_name(&name)
+            .ok_or_else(|| Platform(format!("Unknown subsystem: `{name}`")))?,
+        );
+      }
+      "--target" => {
+        let name = option_value(&mut rest, &arg)?;
+        info.target =
+          Target::from_name(&name).ok_or_else(|| Platform(format!("Unknown target: `{name}`")))?;
+      }
+      "-v" | "--verbose" => info.verbose = true,
+      "--version-info" => {
+        let pair = option_value(&mut rest, &arg)?;
+        let (key, value) = pair
+          .split_once('=')
+          .ok_or_else(|| Platform(format!("Expected `<key>=<value>`: `{pair}`")))?;
+        info.resources.version.push((key.to_owned(), value.to_owned()));
+      }
+      option if option.starts_with('-') => {
+        return Err(Platform(format!("Unknown option: `{option}`")));
+      }
+      _ if input.is_none() => input = Some(arg),
+      _ => {
+        return Err(Platform(format!(
+          "Unexpected argument `{arg}`; pass arguments for the executable after `--`"
+        )));
       }
-      return Ok(Some(CmdLineInfo { file, build_only, release, exe_args: args_iter }));
     }
   }
-  Ok(None)
+  info.file = input.ok_or_else(|| Platform("Missing input file".to_owned()))?;
+  if info.output.is_some() && info.out_dir.is_some() {
+    return Err(Platform("`-o` and `--out-dir` cannot be used together".to_owned()));
+  }
+  Ok(Some(info))
 }
@@ -1,4 +1,6 @@
 mod arithmetic;
+mod array;
+mod closure;
 mod compare;
 mod compound;
 mod control;
@@ -8,7 +10,9 @@ mod gui;
 mod intrinsic;
 mod io;
 mod logic;
+mod map;
 mod module;
+mod record;
 mod string;
 mod variable;
 use crate::prelude::*;
@@ -52,6 +56,7 @@ impl Jsonpiler {
     scope.free(tmp, MemoryType { heap: Value, size: Small(RQ) });
     scope.check_free()?;
     self.check_unused_functions(0)?;
+    self.check_unused_structs()?;
     let stack_size = scope.resolve_stack_size()?;
     let mut insts = self.startup()?;
     insts.push(scope.take_body());
@@ -60,9 +65,9 @@ impl Jsonpiler {
       insts.iter().map(|vec| vec.as_slice()).collect::<Vec<&[Inst]>>().as_slice(),
       stack_size,
       true,
-      FN_NOT_RETURN,
+      if matches!(self.emit, Emit::Dll | Emit::Obj) { FN_RETURN } else { FN_NOT_RETURN },
     );
-    Ok(())
+    self.embed_locations()
   }
   fn get_std_any(&mut self, std_id: u32, std_n: LabelId) -> Vec<Inst> {
     vec![
@@ -70,8 +75,9 @@ impl Jsonpiler {
       CallApi(self.api(KERNEL32, "GetStdHandle")),
       Clear(Rcx),
       DecR(Rcx),
+      Clear(Rdx),
       LogicRR(Cmp, Rax, Rcx),
-      JCc(E, self.handlers.win),
+      CMovCc(E, Rax, Rdx),
       mov_q(Global(std_n), Rax),
     ]
   }
@@ -80,18 +86,25 @@ impl Jsonpiler {
   pub fn new(analysis: bool) -> Self {
     let mut jsonpiler = Self {
       analysis: analysis.then(|| Analysis { symbols: vec![] }),
-      builtin: HashMap::new(),
+      builtin: BTreeMap::new(),
+      closures: BTreeMap::new(),
       data: vec![],
       dlls: vec![],
+      emit: Emit::default(),
+      extensions: vec![],
       functions: BTreeMap::new(),
       globals: BTreeMap::new(),
       id_seed: 0,
+      locations: BTreeMap::new(),
       parsers: vec![],
+      provider: Box::new(FileSystem),
       release: false,
       startup: vec![],
-      str_cache: HashMap::new(),
-      symbols: HashMap::new(),
+      str_cache: BTreeMap::new(),
+      structs: vec![],
+      symbols: BTreeMap::new(),
       handlers: Handlers::default(),
+      target: Target::default(),
       user_defined: BTreeMap::new(),
     };
     jsonpiler.register_builtin();
@@ -101,13 +114,17 @@ impl Jsonpiler {
   }
   pub(crate) fn register_builtin(&mut self) {
     self.arithmetic();
+    self.array();
+    self.closure();
     self.compare();
     self.compound();
     self.control();
     self.define();
     self.module();
+    self.record();
     self.gui();
     self.logic();
+    self.map();
     self.io();
     self.string();
     self.variable();
@@ -139,9 +156,9 @@ impl Jsonpiler {
     Ok(vec![
       vec![
         mov_d(Rcx, CP_UTF8),
-        CallApiCheck(self.api(KERNEL32, "SetConsoleCP")),
+        CallApi(self.api(KERNEL32, "SetConsoleCP")),
         mov_d(Rcx, CP_UTF8),
-        CallApiCheck(self.api(KERNEL32, "SetConsoleOutputCP")),
+        CallApi(self.api(KERNEL32, "SetConsoleOutputCP")),
         CallApiCheck(self.api(KERNEL32, "GetProcessHeap")),
         mov_q(Global(heap), Rax),
         LeaRM(Rcx, Global(self.handlers.ctrl_c)),
@@ -0,0 +1,191 @@
+use crate::prelude::*;
+built_in! {self, func, scope, array;
+  get => {"get", COMMON, Range(2, 3), {
+    let arg = func.arg()?;
+    match (arg.val.as_type(), &arg.val) {
+      (MapT(value_type), Object(Var(map))) => return self.map_get((*map, *value_type), func, scope),
+      (ArrayT(_), _) => func.validate_args(Exact(2))?,
+      (arg_type, _) => {
+        return Err(func.args_err(vec![ArrayT(None), JsonType::any_map()], arg.pos.with(arg_type)));
+      }
+    }
+    let (array, elem_type) = self.array_arg(arg, func, scope)?;
+    let index = arg!(func, (Int(x)) => x);
+    scope.extend(&mov_int(Rdx, index.val));
+    self.bounds_check(array, index.pos, scope)?;
+    scope.push(mov_q(Rcx, array_elem(Rcx, Rdx)));
+    self.copy_elem(&elem_type, func.pos, scope)?;
+    scope.ret_json_take(&func.pos.with(elem_type), Rax)
+  }},
+  pop => {"pop", COMMON, Exact(1), {
+    let (array, elem_type) = self.array_arg(func.arg()?, func, scope)?;
+    let empty = self.custom_err(RuntimeEmptyArray, None, func.pos, scope.id)?;
+    scope.extend(&mov_memory(Rcx, array));
+    scope.extend(&[
+      mov_q(Rax, Ref(Rcx)),
+      LogicRR(Test, Rax, Rax),
+      JCc(E, empty),
+      DecR(Rax),
+      mov_q(Ref(Rcx), Rax),
+      mov_q(Rax, array_elem(Rcx, Rax)),
+    ]);
+    scope.ret_json_take(&func.pos.with(elem_type), Rax)
+  }},
+  push => {"push", COMMON, Exact(2), {
+    let (array, elem_type) = self.array_arg(func.arg()?, func, scope)?;
+    let value = self.elem_arg(&elem_type, func, scope)?;
+    let array_push = self.array_push(scope.id)?;
+    scope.extend(&self.mov_json(Rdx, value, Some(scope.id))?);
+    scope.extend(&mov_memory(Rcx, array));
+    scope.push(Call(array_push));
+    scope.extend(&ret_memory(array, Rcx, Rax)?);
+    Ok(Null(Lit(())))
+  }},
+  set => {"set", COMMON, Exact(3), {
+    let (array, elem_type) = self.array_arg(func.arg()?, func, scope)?;
+    let index = arg!(func, (Int(x)) => x);
+    let value = self.elem_arg(&elem_type, func, scope)?;
+    let tmp_value = scope.tmp(8, 8, func)?;
+    scope.extend(&self.mov_json(Rax, value, Some(scope.id))?);
+    scope.push(mov_q(tmp_value, Rax));
+    scope.extend(&mov_int(Rdx, index.val));
+    self.bounds_check(array, index.pos, scope)?;
+    if matches!(elem_type, StrT | ArrayT(_) | MapT(_)) {
+      let tmp_index = scope.tmp(8, 8, func)?;
+      scope.extend(&[mov_q(tmp_index, Rdx), mov_q(Rcx, array_elem(Rcx, Rdx))]);
+      self.drop_elem(&elem_type, func.pos, scope)?;
+      scope.extend(&mov_memory(Rcx, array));
+      scope.push(mov_q(Rdx, tmp_index));
+    }
+    scope.extend(&[mov_q(Rax, tmp_value), mov_q(array_elem(Rcx, Rdx), Rax)]);
+    Ok(Null(Lit(())))
+  }},
+}
+impl Jsonpiler {
+  fn array_arg(
+    &mut self,
+    arg: Pos<Json>,
+    func: &mut Pos<BuiltIn>,
+    scope: &mut Scope,
+  ) -> ErrOR<(Memory, JsonType)> {
+    let arg_type = arg.val.as_type();
+    let (Array(array), ArrayT(Some(elem_type))) = (arg.val, &arg_type) else {
+      return Err(func.args_err(vec![ArrayT(None)], arg.pos.with(arg_type)));
+    };
+    let memory = match array {
+      Var(memory) => memory,
+      Lit(elems) => {
+        let memory = self.new_array(elem_type, elems, arg.pos, scope)?;
+        func.push_free_tmp(Some(memory));
+        memory
+      }
+    };
+    Ok((memory, (**elem_type).clone()))
+  }
+  fn bounds_check(&mut self, array: Memory, pos: Position, scope: &mut Scope) -> ErrOR<()> {
+    let out_of_bounds = self.custom_err(RuntimeIndexOutOfBounds, None, pos, scope.id)?;
+    scope.extend(&mov_memory(Rcx, array));
+    scope.extend(&[mov_q(Rax, Ref(Rcx)), LogicRR(Cmp, Rdx, Rax), JCc(Ae, out_of_bounds)]);
+    Ok(())
+  }
+  pub(crate) fn copy_elem(
+    &mut self,
+    elem_type: &JsonType,
+    pos: Position,
+    scope: &mut Scope,
+  ) -> ErrOR<()> {
+    match elem_type {
+      StrT => scope.push(Call(self.str_retain(scope.id)?)),
+      ArrayT(Some(inner)) => {
+        let array_copy = self.array_copy(scope.id)?;
+        scope.extend(&[mov_q(Rdx, inner.shape(pos)?), Call(array_copy)]);
+      }
+      MapT(value) => {
+        let map_copy = self.map_copy(scope.id)?;
+        scope.extend(&[mov_q(Rdx, value.shape(pos)?), Call(map_copy)]);
+      }
+      StructT(_, id) => scope.push(Call(self.record_copy(*id, scope.id)?)),
+      FuncT(_) => scope.push(Call(self.closure_retain(scope.id)?)),
+      ArrayT(None) | BoolT | CustomT(_) | FloatT | IntT | NullT | ObjectT => {
+        scope.push(mov_q(Rax, Rcx));
+      }
+    }
+    Ok(())
+  }
+  pub(crate) fn drop_elem(
+    &mut self,
+    elem_type: &JsonType,
+    pos: Position,
+    scope: &mut Scope,
+  ) -> ErrOR<()> {
+    match elem_type {
+      StrT => scope.push(Call(self.str_release(scope.id)?)),
+      ArrayT(Some(inner)) => {
+        let array_drop = self.array_drop(scope.id)?;
+        scope.extend(&[mov_q(Rdx, inner.shape(pos)?), Call(array_drop)]);
+      }
+      MapT(value) => {
+        let map_drop = self.map_drop(scope.id)?;
+        scope.extend(&[mov_q(Rdx, value.shape(pos)?), Call(map_drop)]);
+      }
+      StructT(_, id) => scope.push(Call(self.record_drop(*id, scope.id)?)),
+      FuncT(_) => scope.push(Call(self.closure_release(scope.id)?)),
+      ArrayT(None) | BoolT | CustomT(_) | FloatT | IntT | NullT | ObjectT => (),
+    }
+    Ok(())
+  }
+  pub(crate) fn elem_arg(
+    &mut self,
+    elem_type: &JsonType,
+    func: &mut Pos<BuiltIn>,
+    scope: &mut Scope,
+  ) -> ErrOR<Pos<Json>> {
+    let value = func.arg()?;
+    if value.val.as_type() != *elem_type {
+      return Err(func.args_err(vec![elem_type.clone()], value.map_ref(Json::as_type)));
+    }
+    let runtime = self.runtime_array(value, scope)?;
+    func.push_free_tmp(runtime.val.memory());
+    Ok(runtime)
+  }
+  pub(crate) fn new_array(
+    &mut self,
+    elem_type: &JsonType,
+    elems: Vec<Pos<Json>>,
+    pos: Position,
+    scope: &mut Scope,
+  ) -> ErrOR<Memory> {
+    let mem_type = MemoryType { heap: HeapPtr, size: ArrayOf(elem_type.shape(pos)?) };
+    let memory = Memory(Local(Tmp, scope.alloc(8, 8)?), mem_type);
+    let len = len_u32(&elems)?;
+    scope.extend(&[
+      mov_q(Rcx, Global(self.symbols[HEAP])),
+      mov_d(Rdx, 8),
+      mov_d(R8, len * 8 + ARRAY_HEADER as u32),
+      CallApi(self.api(KERNEL32, "HeapAlloc")),
+      IncMd(Global(self.symbols[LEAK_CNT])),
+      mov_q(memory.0, Rax),
+      mov_d(Rcx, len),
+      mov_q(Ref(Rax), Rcx),
+      AddRId(Rax, 8),
+      mov_q(Ref(Rax), Rcx),
+    ]);
+    for (idx, elem) in (0..len).zip(elems) {
+      let runtime = self.runtime_array(elem, scope)?;
+      scope.extend(&self.mov_json(Rax, runtime.clone(), Some(scope.id))?);
+      scope.extend(&[mov_q(Rcx, memory.0), mov_d(Rdx, idx), mov_q(array_elem(Rcx, Rdx), Rax)]);
+      self.drop_json(runtime.val, false, scope)?;
+    }
+    Ok(memory)
+  }
+  pub(crate) fn runtime_array(&mut self, json: Pos<Json>, scope: &mut Scope) -> ErrOR<Pos<Json>> {
+    let json_type = json.val.as_type();
+    match (json.val, json_type) {
+      (Array(Lit(elems)), ArrayT(Some(elem_type))) => {
+        Ok(json.pos.with(Array(Var(self.new_array(&elem_type, elems, json.pos, scope)?))))
+      }
+      (val @ Array(Lit(_)), _) => err!(json.pos, UnsupportedType(val.describe())),
+      (val, _) => Ok(json.pos.with(val)),
+    }
+  }
+}
@@ -0,0 +1,198 @@
+use crate::prelude::*;
+built_in! {self, func, scope, closure;
+  f_fn => {"fn", SPECIAL, Exact(3), {
+    let type_annotations = arg_custom!(
+      func, vec![CustomT("TypeAnnotations".into())], (Object(Lit(x))) => x
+    );
+    let ret_name = func.arg()?.into_ident("Type annotation")?;
+    let body = func.arg()?;
+    let mut bound = type_annotations.val.iter().map(|(param, _)| param.val.clone()).collect();
+    let mut names = vec![];
+    free_names(&body, &mut bound, &mut names);
+    let mut captures = vec![];
+    for name in names {
+      if let Some(variable) = scope.get_var_local(&name) {
+        captures.push((name.val, variable.map_ref(|var| var.val.clone())));
+      }
+    }
+    let id = self.id();
+    let old_scope = scope.change(id);
+    self.closures.insert(id, old_scope.id);
+    let (params, args) = self.function_params(type_annotations, scope)?;
+    let ret_type = self.parse_type(&ret_name)?;
+    let env = Local(Tmp, scope.alloc(8, 8)?);
+    scope.push(mov_q(env, R10));
+    let mut capture_types = vec![];
+    for (idx, (name, value)) in (CLOSURE_HEADER..).zip(&captures) {
+      let capture_type = value.val.as_type();
+      let mem_type = capture_type.mem_type(value.pos)?;
+      let addr = Local(Long, scope.alloc(mem_type.size(), mem_type.size())?);
+      scope.extend(&[mov_q(Rax, env), mov_d(Rdx, idx), mov_q(Rcx, record_field(Rax, Rdx))]);
+      scope.extend(&self.field_copy(&capture_type, id)?);
+      scope.extend(&ret_memory(Memory(addr, mem_type), Rcx, Rax)?);
+      let json = capture_type.to_json(value.pos, addr)?;
+      scope.innermost().insert(name.clone(), value.pos.with(Variable::new(json, LocalVar)));
+      capture_types.push(capture_type);
+    }
+    if let Local(_, offset) = env {
+      scope.free(offset, MemoryType { heap: Value, size: Small(RQ) });
+    }
+    let sig = Signature { params, ret_type: ret_type.clone() };
+    self.link_user_function((id, "fn"), (old_scope, args), body, ret_type, scope)?;
+    self.use_function(scope.id, id);
+    let drop = self.closure_drop(&capture_types, scope.id)?;
+    let values = captures.into_iter().map(|(_, value)| value).collect();
+    let memory = self.new_closure(id, drop, values, scope)?;
+    Ok(Func(Box::new(sig.unnamed()), memory))
+  }},
+}
+impl Jsonpiler {
+  pub(crate) fn call_value(
+    &mut self,
+    (sig, memory): (Signature, Memory),
+    (name, args): KeyVal,
+    scope: &mut Scope,
+  ) -> ErrOR<Json> {
+    let ret = name.pos.with(sig.ret_type);
+    let mut func = self.func_info((name, args), false, scope)?;
+    func.push_free_tmp(Some(memory));
+    self.pass_args(sig.params, &mut func, scope)?;
+    scope.extend(&[mov_q(R10, memory.0), mov_q(Rax, Ref(R10)), Custom(CALL_RAX)]);
+    let ret_json = scope.ret_json_take(&ret, Rax)?;
+    self.free_all(&mut func, scope)?;
+    Ok(ret_json)
+  }
+  pub(crate) fn callee(
+    &mut self,
+    name: &Pos<String>,
+    scope: &mut Scope,
+  ) -> ErrOR<Option<(Signature, Memory)>> {
+    let (value, kind) = if let Some(path) = self.field_path(name, scope)? {
+      (self.get_field(path, name.pos, scope)?, StructField)
+    } else if let Some(variable) = scope.get_var_local(name).or_else(|| self.globals.get_var(name))
+    {
+      (variable.val.val.clone(), variable.val.kind)
+    } else {
+      return Ok(None);
+    };
+    if let Func(sig, memory) = value {
+      return Ok(Some((*sig, memory)));
+    }
+    let expected = vec![CustomT("Func".into())];
+    Err(type_err(format_variable(&name.val, kind), expected, name.pos.with(value.as_type())))
+  }
+  pub(crate) fn function_value(
+    &mut self,
+    name: &Pos<String>,
+    scope: &mut Scope,
+  ) -> ErrOR<Option<Json>> {
+    let Some(u_d) = self.user_defined.get_mut(&name.val) else {
+      return Ok(None);
+    };
+    u_d.val.refs.push(name.pos);
+    let UserDefinedInfo { dep, sig, .. } = u_d.val.clone();
+    self.use_function(scope.id, dep.id);
+    self.use_u_d(scope.id, dep.id)?;
+    let memory = self.new_closure(dep.id, None, vec![], scope)?;
+    Ok(Some(Func(Box::new(sig.unnamed()), memory)))
+  }
+  fn new_closure(
+    &mut self,
+    code: LabelId,
+    drop: Option<LabelId>,
+    captures: Vec<Pos<Json>>,
+    scope: &mut Scope,
+  ) -> ErrOR<Memory> {
+    let memory =
+      Memory(Local(Tmp, scope.alloc(8, 8)?), MemoryType { heap: HeapPtr, size: Closure });
+    scope.extend(&[
+      mov_q(Rcx, Global(self.symbols[HEAP])),
+      mov_d(Rdx, 8),
+      mov_d(R8, (CLOSURE_HEADER + len_u32(&captures)?) * 8),
+      CallApi(self.api(KERNEL32, "HeapAlloc")),
+      IncMd(Global(self.symbols[LEAK_CNT])),
+      mov_q(memory.0, Rax),
+      LeaRM(Rcx, Global(code)),
+      mov_q(Ref(Rax), Rcx),
+      mov_d(Rcx, 1),
+      mov_d(Rdx, CLOSURE_COUNT),
+      mov_q(record_field(Rax, Rdx), Rcx),
+    ]);
+    if let Some(drop_id) = drop {
+      scope.extend(&[
+        LeaRM(Rcx, Global(drop_id)),
+        mov_d(Rdx, CLOSURE_DROP),
+        mov_q(record_field(Rax, Rdx), Rcx),
+      ]);
+    }
+    for (idx, capture) in (CLOSURE_HEADER..).zip(captures) {
+      scope.extend(&self.mov_json(Rax, capture, Some(scope.id))?);
+      scope.extend(&[mov_q(Rcx, memory.0), mov_d(Rdx, idx), mov_q(record_field(Rcx, Rdx), Rax)]);
+    }
+    Ok(memory)
+  }
+}
+fn bind_assign(assign: &Pos<Json>, bound: &mut BTreeSet<String>, names: &mut Vec<Pos<String>>) {
+  if let Object(Lit(entries)) = &assign.val
+    && let [(op, Pos { val: Array(Lit(pair)), .. })] = entries.as_slice()
+    && op.val == "="
+    && let [target, value] = pair.as_slice()
+    && let Object(Lit(ident)) = &target.val
+    && let [(dollar, Pos { val: Str(Lit(var)), .. })] = ident.as_slice()
+    && dollar.val == "$"
+  {
+    free_names(value, bound, names);
+    bound.insert(var.clone());
+  } else {
+    free_names(assign, bound, names);
+  }
+}
+fn free_names(json: &Pos<Json>, bound: &mut BTreeSet<String>, names: &mut Vec<Pos<String>>) {
+  match &json.val {
+    Array(Lit(items)) => {
+      for item in items {
+        free_names(item, bound, names);
+      }
+    }
+    Object(Lit(entries)) => {
+      for (key, val) in entries {
+        match (key.val.as_str(), &val.val) {
+          ("$", Str(Lit(name))) => push_name(name, val.pos, bound, names),
+          ("let" | "global", Array(Lit(assigns))) => {
+            for assign in assigns {
+              bind_assign(assign, bound, names);
+            }
+          }
+          ("let" | "global", Object(_)) => bind_assign(val, bound, names),
+          ("fn", Array(Lit(fn_args))) => {
+            let mut inner = bound.clone();
+            if let Some(Pos { val: Object(Lit(params)), .. }) = fn_args.first() {
+              inner.extend(params.iter().map(|(param, _)| param.val.clone()));
+            }
+            for arg in fn_args.iter().skip(1) {
+              free_names(arg, &mut inner, names);
+            }
+          }
+          _ => {
+            push_name(&key.val, key.pos, bound, names);
+            free_names(val, bound, names);
+          }
+        }
+      }
+    }
+    Bool(_) | Float(_) | Func(..) | Int(_) | Null(_) | Str(_) | Struct(..) | Array(Var(_))
+    | Object(Var(_)) => (),
+  }
+}
+fn push_name(
+  name: &str,
+  mut pos: Position,
+  bound: &BTreeSet<String>,
+  names: &mut Vec<Pos<String>>,
+) {
+  let root = name.split('.').next().unwrap_or(name);
+  if !bound.contains(root) && !names.iter().any(|known| known.val == root) {
+    pos.size = u32::try_from(root.len()).unwrap_or(pos.size);
+    names.push(pos.with(root.to_owned()));
+  }
+}
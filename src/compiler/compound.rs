@@ -24,11 +24,15 @@ impl Jsonpiler {
     scope: &mut Scope,
   ) -> ErrOR<Json> {
     let var = func.arg()?.into_ident("Variable name")?;
-    let variable = &self.get_var(&var, scope)?.val;
+    let value = self.eval(func.arg()?, scope)?;
+    let variable = if let Some(path) = self.field_path(&var, scope)? {
+      self.field_place(&var, path, func, scope)?
+    } else {
+      self.get_var(&var, scope)?.val
+    };
     let Some(memory) = variable.val.memory() else {
       return err!(var.pos, UndefinedVar(var.val));
     };
-    let value = self.eval(func.arg()?, scope)?;
     match &value {
       Pos { val: Int(int), .. } => {
         if variable.val.as_type() != IntT {
@@ -76,8 +80,7 @@ impl Jsonpiler {
         let str_len = self.str_len(scope.id)?;
         let tmp = scope.tmp(8, 8, func)?;
         let tmp2 = scope.tmp(8, 8, func)?;
-        let heap = Global(self.symbols[HEAP]);
-        let leak = Global(self.symbols[LEAK_CNT]);
+        let str_alloc = self.str_alloc(scope.id)?;
         scope.extend(&[
           self.mov_str(Rcx, Var(dst_str)),
           Call(str_len),
@@ -85,13 +88,10 @@ impl Jsonpiler {
           self.mov_str(Rcx, string.clone()),
           Call(str_len),
           mov_q(tmp2, Rax),
-          mov_q(Rcx, heap),
-          mov_q(Rdx, 8),
-          mov_q(R8, tmp),
-          AddRR(R8, Rax),
-          IncR(R8),
-          CallApi(self.api(KERNEL32, "HeapAlloc")),
-          IncMd(leak),
+          mov_q(Rcx, tmp),
+          AddRR(Rcx, Rax),
+          IncR(Rcx),
+          Call(str_alloc),
           mov_q(Rcx, tmp),
           self.mov_str(Rsi, Var(dst_str)),
           mov_q(Rdi, Rax),
@@ -105,7 +105,7 @@ impl Jsonpiler {
           mov_b(Ref(Rcx), Rdx),
           mov_q(tmp, Rax),
         ]);
-        self.heap_free(dst_str, scope);
+        self.heap_free(dst_str, scope)?;
         scope.extend(&[mov_q(Rax, tmp), mov_q(dst_str.0, Rax)]);
       }
       other => {
@@ -115,7 +115,7 @@ impl Jsonpiler {
         ));
       }
     }
-    self.drop_json(value.val, false, scope);
+    self.drop_json(value.val, false, scope)?;
     Ok(Null(Lit(())))
   }
 }
@@ -30,7 +30,7 @@ built_in! {self, func, scope, control;
         return Err(type_err(
           "`if` expression".into(),
           if_expr_t,
-          if_expr.pos.with(ArrayT)
+          if_expr.pos.with(ArrayT(None))
         ));
       }
       let mut cond = if_expr.val.remove(0);
@@ -89,8 +89,8 @@ built_in! {self, func, scope, control;
       }
     }
     let json = self.eval_with_scope(body, scope)?.val;
-    self.drop_json(json, false, scope);
-    self.free_all(func, scope);
+    self.drop_json(json, false, scope)?;
+    self.free_all(func, scope)?;
     scope.extend(&[Jmp(start), Lbl(end)]);
     scope.loop_labels.pop();
     Ok(Null(Lit(())))
@@ -114,8 +114,8 @@ impl Jsonpiler {
   ) -> ErrOR<()> {
     func.push_free_tmp(memory_opt);
     let json = self.eval_with_scope(expr, scope)?.val;
-    self.drop_json(json, false, scope);
-    self.free_all(func, scope);
+    self.drop_json(json, false, scope)?;
+    self.free_all(func, scope)?;
     scope.push(if is_end { Lbl(end) } else { Jmp(end) });
     Ok(())
   }
@@ -131,7 +131,7 @@ impl Jsonpiler {
     for locals in scope.locals.get(idx..).unwrap_or_default().to_owned() {
       for local in locals.into_values() {
         if let Some(memory) = local.val.val.memory() {
-          self.heap_free(memory, scope);
+          self.heap_free(memory, scope)?;
         }
       }
     }
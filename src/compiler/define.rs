@@ -1,44 +1,46 @@
 use crate::prelude::*;
-built_in! {self, func, scope, define;
-  f_define => {"define", SPECIAL, Exact(4), {
-    let id = self.id();
-    let old_scope = scope.change(id);
-    let name = func.arg()?.into_ident("Function name")?;
-    self.check_defined(&name, name.pos, scope)?;
-    let type_annotations = arg_custom!(
-      func, vec![CustomT("TypeAnnotations".into())], (Object(Lit(x))) => x
-    );
+impl Jsonpiler {
+  pub(crate) fn function_params(
+    &mut self,
+    type_annotations: Pos<Vec<KeyVal>>,
+    scope: &mut Scope,
+  ) -> ErrOR<(Params, Vec<Memory>)> {
     let mut params = vec![];
     let mut args = vec![];
     for (var_name, param) in type_annotations.val {
       let param_type_str = param.into_ident("Type annotation")?;
-      let json_type = JsonType::from_string(&param_type_str.val);
+      let json_type = self.parse_type(&param_type_str)?;
       let mem_type = json_type.mem_type(param_type_str.pos)?;
       let arg = Local(Long, scope.alloc(mem_type.size(), mem_type.size())?);
       let json = json_type.to_json(param_type_str.pos, arg)?;
-      scope.innermost().insert(
-        var_name.val.clone(), var_name.pos.with(Variable::new(json.clone(), Argument))
-      );
+      scope
+        .innermost()
+        .insert(var_name.val.clone(), var_name.pos.with(Variable::new(json.clone(), Argument)));
       args.push(Memory(arg, mem_type));
       params.push((var_name.val, json_type));
     }
     scope.update_args_count(len_u32(&params)?);
-    let ret_type = JsonType::from_string(&func.arg()?.into_ident("Type annotation")?.val);
+    Ok((params, args))
+  }
+  pub(crate) fn link_user_function(
+    &mut self,
+    (id, name): (LabelId, &str),
+    (old_scope, args): (Scope, Vec<Memory>),
+    body_expr: Pos<Json>,
+    ret_type: JsonType,
+    scope: &mut Scope,
+  ) -> ErrOR<()> {
     let epilogue = self.id();
     scope.epilogue = Some((epilogue, ret_type.clone()));
-    self.user_defined.insert(name.val.clone(), name.pos.with(UserDefinedInfo {
-      sig: Signature { params, ret_type: ret_type.clone() },
-      dep: Dependency::new(id),
-      refs: vec![],
-    }));
-    let ret = self.eval(func.arg()?, scope)?;
-    if ret_type != ret.val.as_type() {
-      return Err(type_err(format_ret_val(&name.val), vec![ret_type], ret.map_ref(Json::as_type)));
+    let body = self.eval(body_expr, scope)?;
+    if ret_type != body.val.as_type() {
+      return Err(type_err(format_ret_val(name), vec![ret_type], body.map_ref(Json::as_type)));
     }
+    let ret = self.runtime_array(body, scope)?;
     let tmp = scope.alloc(8, 8)?;
     scope.extend(&self.mov_json(Rax, ret.clone(), Some(scope.id))?);
     scope.push(mov_q(Local(Tmp, tmp), Rax));
-    self.drop_json(ret.val, false, scope);
+    self.drop_json(ret.val, false, scope)?;
     self.drop_all_local(scope)?;
     scope.push(mov_q(Rax, Local(Tmp, tmp)));
     scope.free(tmp, MemoryType { heap: Value, size: Small(RQ) });
@@ -55,28 +57,51 @@ built_in! {self, func, scope, define;
     insts.extend_from_slice(&scope.replace(old_scope));
     insts.push(Lbl(epilogue));
     self.link_function(id, &insts, stack_size);
+    Ok(())
+  }
+}
+built_in! {self, func, scope, define;
+  f_define => {"define", SPECIAL, Exact(4), {
+    let id = self.id();
+    let old_scope = scope.change(id);
+    let name = func.arg()?.into_ident("Function name")?;
+    self.check_defined(&name, name.pos, scope)?;
+    let type_annotations = arg_custom!(
+      func, vec![CustomT("TypeAnnotations".into())], (Object(Lit(x))) => x
+    );
+    let (params, args) = self.function_params(type_annotations, scope)?;
+    let ret_type = self.parse_type(&func.arg()?.into_ident("Type annotation")?)?;
+    self.user_defined.insert(name.val.clone(), name.pos.with(UserDefinedInfo {
+      sig: Signature { params, ret_type: ret_type.clone() },
+      dep: Dependency::new(id),
+      refs: vec![],
+    }));
+    let body = func.arg()?;
+    self.link_user_function((id, &name.val), (old_scope, args), body, ret_type, scope)?;
     Ok(Null(Lit(())))
   }},
   ret => {"ret", COMMON, Exact(1), {
-    let ret = func.arg()?;
+    let value = func.arg()?;
     let Some((epilogue, ret_type)) = scope.epilogue.as_ref() else {
-      return err!(ret.pos, OutSideError { name: func.val.name.clone(), place: "function" });
+      return err!(value.pos, OutSideError { name: func.val.name.clone(), place: "function" });
     };
     let epi = *epilogue;
-    if *ret_type != ret.val.as_type() {
+    if *ret_type != value.val.as_type() {
       let ret_val = format!("Function `{}`'s return value", func.val.name);
-      return Err(type_err(ret_val, vec![ret_type.clone()], ret.map_ref(Json::as_type)));
+      return Err(type_err(ret_val, vec![ret_type.clone()], value.map_ref(Json::as_type)));
     }
+    let ret = self.runtime_array(value, scope)?;
+    func.push_free_tmp(ret.val.memory());
     for (_, local) in scope.locals.clone().into_iter().chain(iter::once(scope.local_top.clone())).flatten() {
       if let Some(memory) = local.val.val.memory() && Some(memory) != ret.val.memory() {
-        self.heap_free(memory, scope);
+        self.heap_free(memory, scope)?;
       }
     }
     scope.extend(&self.mov_json(Rax, ret.clone(), Some(scope.id))?);
     if let Some(memory @ Memory(Local(_, _), MemoryType { heap: HeapPtr, .. })) = ret.val.memory() {
       let tmp = scope.tmp(8, 8, func)?;
       scope.push(mov_q(tmp, Rax));
-      self.heap_free(memory, scope);
+      self.heap_free(memory, scope)?;
       scope.push(mov_q(Rax, tmp));
       }
     scope.push(Jmp(epi));
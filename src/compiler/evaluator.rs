@@ -1,5 +1,24 @@
 use crate::prelude::*;
 impl Jsonpiler {
+  fn construct_array(
+    &mut self,
+    (name, val): KeyVal,
+    elem_type: &JsonType,
+    scope: &mut Scope,
+  ) -> ErrOR<Json> {
+    let args = if let Array(Lit(args)) = val.val { args } else { vec![val] };
+    let elems = self.eval_args(args, scope)?;
+    for (nth, elem) in (1..).zip(&elems) {
+      if elem.val.as_type() != *elem_type {
+        return Err(type_err(
+          format_nth_args(nth, &name.val),
+          vec![elem_type.clone()],
+          elem.map_ref(Json::as_type),
+        ));
+      }
+    }
+    Ok(Array(Var(self.new_array(elem_type, elems, name.pos, scope)?)))
+  }
   pub(crate) fn eval(&mut self, json: Pos<Json>, scope: &mut Scope) -> ErrOR<Pos<Json>> {
     Ok(if let Array(Lit(array)) = json.val {
       json.pos.with(Array(Lit(self.eval_args(array, scope)?)))
@@ -15,7 +34,7 @@ impl Jsonpiler {
     }
     Ok(args)
   }
-  fn eval_func(&mut self, (name, args): KeyVal, scope: &mut Scope) -> ErrOR<Json> {
+  fn eval_call(&mut self, (name, args): KeyVal, scope: &mut Scope) -> ErrOR<Json> {
     if let Some(builtin) = self.builtin.get(&name.val.as_ref()) {
       let BuiltInInfo { scoped, skip_eval, builtin_ptr, arity } = *builtin;
       if let Some(symbol) = self.analysis.as_mut().and_then(|analysis| {
@@ -35,11 +54,21 @@ impl Jsonpiler {
       if scoped {
         self.drop_scope(scope)?;
       }
-      self.free_all(&mut func, scope);
+      self.free_all(&mut func, scope)?;
       return Ok(result);
     }
     let Some(u_d) = self.user_defined.get_mut(&name.val) else {
-      return err!(name.pos, UndefinedFunc(name.val.clone()));
+      if let Some(callee) = self.callee(&name, scope)? {
+        return self.call_value(callee, (name, args), scope);
+      }
+      return match self.parse_type(&name)? {
+        ArrayT(Some(elem_type)) => self.construct_array((name, args), &elem_type, scope),
+        MapT(value_type) => self.construct_map((name, args), &value_type, scope),
+        StructT(_, id) => self.construct_record((name, args), id, scope),
+        ArrayT(_) | BoolT | CustomT(_) | FloatT | FuncT(_) | IntT | NullT | ObjectT | StrT => {
+          err!(name.pos, UndefinedFunc(name.val.clone()))
+        }
+      };
     };
     u_d.val.refs.push(name.pos);
     let UserDefinedInfo { dep, sig, .. } = u_d.val.clone();
@@ -47,36 +76,36 @@ impl Jsonpiler {
     self.use_u_d(scope.id, dep.id)?;
     let ret = name.pos.with(sig.ret_type);
     let mut func = self.func_info((name, args), false, scope)?;
-    let params_len = len_u32(&sig.params)?;
-    scope.update_args_count(params_len);
-    func.validate_args(Exact(params_len))?;
-    for (_, param_type) in sig.params {
-      let arg = func.arg()?;
-      if arg.val.as_type() != param_type {
-        return Err(func.args_err(vec![param_type], arg.map_ref(Json::as_type)));
-      }
-      self.mov_args_json(func.val.nth - 1, arg, true, scope)?;
-    }
+    self.pass_args(sig.params, &mut func, scope)?;
     scope.push(Call(dep.id));
     let ret_json = scope.ret_json_take(&ret, Rax)?;
-    self.free_all(&mut func, scope);
+    self.free_all(&mut func, scope)?;
     Ok(ret_json)
   }
+  fn eval_func(&mut self, key_val: KeyVal, scope: &mut Scope) -> ErrOR<Json> {
+    let outer = scope.mark(self.source_loc(key_val.0.pos));
+    let result = self.eval_call(key_val, scope)?;
+    if let Some(loc) = outer {
+      scope.mark(loc);
+    }
+    Ok(result)
+  }
   fn eval_object(&mut self, object: Pos<Vec<KeyVal>>, scope: &mut Scope) -> ErrOR<Pos<Json>> {
     let mut tmp_json = object.pos.with(Null(Lit(())));
     for key_val in object.val {
-      self.drop_json(tmp_json.val, false, scope);
+      self.drop_json(tmp_json.val, false, scope)?;
       tmp_json.val = self.eval_func(key_val, scope)?;
     }
     Ok(tmp_json)
   }
-  pub(crate) fn free_all(&mut self, func: &mut Pos<BuiltIn>, scope: &mut Scope) {
+  pub(crate) fn free_all(&mut self, func: &mut Pos<BuiltIn>, scope: &mut Scope) -> ErrOR<()> {
     for memory in &take(&mut func.val.free_list) {
-      self.heap_free(*memory, scope);
+      self.heap_free(*memory, scope)?;
       if let Memory(Local(Tmp, start), mem_type) = memory {
         scope.free(*start, *mem_type);
       }
     }
+    Ok(())
   }
   pub(crate) fn func_info(
     &mut self,
@@ -101,4 +130,28 @@ impl Jsonpiler {
     func.val.args = args.into_iter();
     Ok(func)
   }
+  pub(crate) fn pass_args(
+    &mut self,
+    params: Params,
+    func: &mut Pos<BuiltIn>,
+    scope: &mut Scope,
+  ) -> ErrOR<()> {
+    let params_len = len_u32(&params)?;
+    scope.update_args_count(params_len);
+    func.validate_args(Exact(params_len))?;
+    let mut args_vec = vec![];
+    for (_, param_type) in params {
+      let arg = func.arg()?;
+      if arg.val.as_type() != param_type {
+        return Err(func.args_err(vec![param_type], arg.map_ref(Json::as_type)));
+      }
+      let runtime = self.runtime_array(arg, scope)?;
+      func.push_free_tmp(runtime.val.memory());
+      args_vec.push(runtime);
+    }
+    for (idx, arg) in (0..params_len).zip(args_vec) {
+      self.mov_args_json(idx, arg, true, scope)?;
+    }
+    Ok(())
+  }
 }
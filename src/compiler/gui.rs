@@ -1,8 +1,29 @@
 use crate::prelude::*;
+impl Jsonpiler {
+  fn render_closure(&mut self, slot: Address) -> LabelId {
+    const SIZE: i32 = 0x30;
+    let id = self.id();
+    let insts = &[
+      mov_q(Rax, Local(Tmp, 0x30)),
+      mov_q(Args(5), Rax),
+      mov_q(R10, slot),
+      mov_q(Rax, Ref(R10)),
+      Custom(CALL_RAX),
+    ];
+    self.link_function(id, insts, SIZE);
+    id
+  }
+}
 built_in! {self, func, scope, gui;
 init_gui => {"GUI", SPECIAL, Exact(1), {
-  let name = func.arg()?.into_ident("render")?;
-  let render_id = {
+  self.require_windows(func)?;
+  let render_expr = func.arg()?;
+  let direct = render_expr
+    .clone()
+    .into_ident("render")
+    .ok()
+    .filter(|name| self.user_defined.contains_key(&name.val));
+  let (render_id, title, closure) = if let Some(name) = direct {
     let Some(u_d) = self.user_defined.get_mut(&name.val) else {
       return err!(name.pos, UndefinedFunc(name.val));
     };
@@ -26,11 +47,27 @@ init_gui => {"GUI", SPECIAL, Exact(1), {
     if render.sig.ret_type != IntT {
       return Err(type_err(format_ret_val("render"), vec![IntT], name.pos.with(render.sig.ret_type.clone())));
     }
-    render.dep.id
+    (render.dep.id, name.val, None)
+  } else {
+    let render = self.eval(render_expr, scope)?;
+    let render_type = FuncT(Box::new(Signature {
+      params: vec![(String::new(), IntT); 5],
+      ret_type: IntT,
+    }));
+    if render.val.as_type() != render_type {
+      return Err(func.args_err(vec![render_type], render.map_ref(Json::as_type)));
+    }
+    let slot = Global(self.bss(8, 8));
+    scope.extend(&self.mov_json(Rax, render.clone(), Some(scope.id))?);
+    scope.push(mov_q(slot, Rax));
+    self.drop_json(render.val, false, scope)?;
+    let trampoline = self.render_closure(slot);
+    self.use_function(scope.id, trampoline);
+    (trampoline, TITLE.to_owned(), Some(slot))
   };
   let flag_gui = Global(self.symbols[FLAG_GUI]);
   let class_name = Global(self.global_w_chars(TITLE));
-  let window_name = Global(self.global_w_chars(name.val));
+  let window_name = Global(self.global_w_chars(title));
   let wnd_proc = self.get_wnd_proc(scope.id, render_id)?;
   let msg = scope.tmp(0x30, 8, func)?;
   let hwnd = scope.tmp(8, 8, func)?;
@@ -136,6 +173,10 @@ init_gui => {"GUI", SPECIAL, Exact(1), {
     mov_q(Rdx, Rax),
     CallApiCheck(self.api(USER32, "UnregisterClassW")),
   ]);
+  if let Some(slot) = closure {
+    let closure_release = self.closure_release(scope.id)?;
+    scope.extend(&[mov_q(Rcx, slot), Call(closure_release)]);
+  }
   Ok(Null(Lit(())))
 }}
 }
@@ -41,6 +41,7 @@ impl Jsonpiler {
     func: &mut Pos<BuiltIn>,
     scope: &mut Scope,
   ) -> ErrOR<Json> {
+    self.require_windows(func)?;
     let mut dll = func.arg()?.into_ident("DLL NAME")?.val;
     dll.push_str(".dll");
     let api_name = func.arg()?.into_ident("API NAME")?.val;
@@ -55,6 +56,10 @@ impl Jsonpiler {
       self.mov_args_json(idx, arg, false, scope)?;
     }
     scope.push(if check { CallApiCheck(api) } else { CallApi(api) });
+    if ret_type.val == StrT {
+      // The API owns the returned text, so the program works on a counted copy.
+      scope.extend(&[mov_q(Rcx, Rax), Call(self.copy_str(scope.id)?)]);
+    }
     scope.ret_json_take(&ret_type, Rax)
   }
 }
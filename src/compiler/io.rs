@@ -1,6 +1,7 @@
 use crate::prelude::*;
 built_in! {self, _func, scope, io;
   confirm => {"confirm", COMMON, Exact(2), {
+    self.require_windows(_func)?;
     scope.extend(&[
       self.mov_str(Rcx, arg!(_func, (Str(x)) => x).val),
       self.mov_str(Rdx, arg!(_func, (Str(x)) => x).val),
@@ -19,6 +20,7 @@ built_in! {self, _func, scope, io;
     scope.ret_str(Rax, HeapPtr)
   }},
   message => {"message", COMMON, Exact(2), {
+    self.require_windows(_func)?;
     scope.extend(&[
       self.mov_str(Rcx, arg!(_func, (Str(x)) => x).val),
       self.mov_str(Rdx, arg!(_func, (Str(x)) => x).val),
@@ -35,7 +37,7 @@ built_in! {self, _func, scope, io;
         return Err(_func.args_err(vec![StrT], printable.map_ref(Json::as_type)));
       };
       scope.extend(&[self.mov_str(Rcx, arg.clone()), Call(self.get_print(scope.id)?)]);
-      self.drop_json(Str(arg), false, scope);
+      self.drop_json(Str(arg), false, scope)?;
     }
     Ok(Null(Lit(())))
   }},
@@ -0,0 +1,115 @@
+use crate::prelude::*;
+built_in! {self, func, scope, map;
+  contains => {"contains", COMMON, Exact(2), {
+    let (map, _) = map_arg(func)?;
+    let key = arg!(func, (Str(x)) => x).val;
+    let map_find = self.map_find(scope.id)?;
+    scope.extend(&mov_memory(Rcx, map));
+    scope.extend(&[self.mov_str(Rdx, key), Call(map_find)]);
+    scope.ret_bool(Rax)
+  }},
+  insert => {"insert", COMMON, Exact(3), {
+    let (map, value_type) = map_arg(func)?;
+    let key = arg!(func, (Str(x)) => x).val;
+    let value = self.elem_arg(&value_type, func, scope)?;
+    let map_insert = self.map_insert(scope.id)?;
+    let tmp_value = scope.tmp(8, 8, func)?;
+    scope.extend(&self.mov_json(Rax, value, Some(scope.id))?);
+    scope.push(mov_q(tmp_value, Rax));
+    scope.extend(&self.mov_json(Rdx, func.pos.with(Str(key)), Some(scope.id))?);
+    scope.extend(&mov_memory(Rcx, map));
+    scope.extend(&[
+      mov_q(R8, tmp_value),
+      mov_q(R9, value_type.shape(func.pos)?),
+      Call(map_insert),
+    ]);
+    scope.extend(&ret_memory(map, Rcx, Rax)?);
+    Ok(Null(Lit(())))
+  }},
+  keys => {"keys", COMMON, Exact(1), {
+    let (map, _) = map_arg(func)?;
+    let map_keys = self.map_keys(scope.id)?;
+    scope.extend(&mov_memory(Rcx, map));
+    scope.push(Call(map_keys));
+    scope.ret_json_take(&func.pos.with(ArrayT(Some(Box::new(StrT)))), Rax)
+  }},
+  remove => {"remove", COMMON, Exact(2), {
+    let (map, value_type) = map_arg(func)?;
+    let key = arg!(func, (Str(x)) => x).val;
+    let map_remove = self.map_remove(scope.id)?;
+    scope.extend(&mov_memory(Rcx, map));
+    scope.extend(&[
+      self.mov_str(Rdx, key),
+      mov_q(R8, value_type.shape(func.pos)?),
+      Call(map_remove),
+    ]);
+    scope.ret_bool(Rax)
+  }},
+}
+impl Jsonpiler {
+  pub(crate) fn construct_map(
+    &mut self,
+    (name, val): KeyVal,
+    value_type: &JsonType,
+    scope: &mut Scope,
+  ) -> ErrOR<Json> {
+    let args = if let Array(Lit(args)) = val.val { args } else { vec![val] };
+    if !args.is_empty() {
+      let actual = len_u32(&args)?;
+      return err!(name.pos, ArityError { name: name.val, expected: Exact(0), actual });
+    }
+    let mem_type = MemoryType { heap: HeapPtr, size: MapOf(value_type.shape(name.pos)?) };
+    let memory = Memory(Local(Tmp, scope.alloc(8, 8)?), mem_type);
+    scope.extend(&[
+      mov_q(Rcx, Global(self.symbols[HEAP])),
+      mov_d(Rdx, 8),
+      mov_d(R8, MAP_INIT_CAP * 16 + MAP_HEADER as u32),
+      CallApi(self.api(KERNEL32, "HeapAlloc")),
+      IncMd(Global(self.symbols[LEAK_CNT])),
+      mov_q(memory.0, Rax),
+      mov_d(Rcx, MAP_INIT_CAP),
+      AddRId(Rax, 8),
+      mov_q(Ref(Rax), Rcx),
+    ]);
+    Ok(Object(Var(memory)))
+  }
+  pub(crate) fn map_get(
+    &mut self,
+    (map, value_type): (Memory, JsonType),
+    func: &mut Pos<BuiltIn>,
+    scope: &mut Scope,
+  ) -> ErrOR<Json> {
+    let key = arg!(func, (Str(x)) => x);
+    let default =
+      if func.val.len == 3 { Some(self.elem_arg(&value_type, func, scope)?) } else { None };
+    let map_find = self.map_find(scope.id)?;
+    let found = self.id();
+    let done = self.id();
+    scope.extend(&mov_memory(Rcx, map));
+    scope.extend(&[
+      self.mov_str(Rdx, key.val.clone()),
+      Call(map_find),
+      LogicRbRb(Test, Rax, Rax),
+      JCc(Ne, found),
+    ]);
+    if let Some(value) = default {
+      scope.extend(&self.mov_json(Rax, value, Some(scope.id))?);
+      scope.push(Jmp(done));
+    } else {
+      scope.push(Jmp(self.custom_err(RuntimeMissingKey, Some(key.val), key.pos, scope.id)?));
+    }
+    scope.push(Lbl(found));
+    scope.extend(&mov_memory(Rcx, map));
+    scope.push(mov_q(Rcx, map_value(Rcx, Rdx)));
+    self.copy_elem(&value_type, func.pos, scope)?;
+    scope.push(Lbl(done));
+    scope.ret_json_take(&func.pos.with(value_type), Rax)
+  }
+}
+fn map_arg(func: &mut Pos<BuiltIn>) -> ErrOR<(Memory, JsonType)> {
+  let arg = func.arg()?;
+  match (arg.val.as_type(), arg.val) {
+    (MapT(value_type), Object(Var(memory))) => Ok((memory, *value_type)),
+    (arg_type, _) => Err(func.args_err(vec![JsonType::any_map()], arg.pos.with(arg_type))),
+  }
+}
@@ -43,14 +43,7 @@ impl Jsonpiler {
     }
   }
   fn import_file(&mut self, func: &mut Pos<BuiltIn>, scope: &mut Scope) -> ErrOR<Json> {
-    let file = {
-      let path = arg!(func, (Str(Lit(x))) => x);
-      let folder = Path::new(&self.parsers[path.pos.file as usize].val.file)
-        .parent()
-        .unwrap_or(Path::new("."));
-      let full_path = folder.join(Path::new(&path.val)).canonicalize();
-      path.pos.with(full_path.map_err(|val| path.pos.with(val))?.to_string_lossy().to_string())
-    };
+    let file = self.resolve_import(&arg!(func, (Str(Lit(x))) => x))?;
     let mut imports: BTreeMap<String, Vec<Position>> = BTreeMap::new();
     for _ in 1..func.val.len {
       let import_func = func.arg()?.into_ident("Function name")?;
@@ -65,17 +58,12 @@ impl Jsonpiler {
     }
     let old_globals = take(&mut self.globals);
     let old_user_defined = take(&mut self.user_defined);
-    let file_size = fs::metadata(&file.val).map_err(|val| file.pos.with(val))?.len();
-    let total_size: usize = self.parsers.iter().map(|parser| parser.val.text.len()).sum();
-    if total_size as u64 + file_size > u64::from(GB) {
-      return err!(file.pos, TooLargeFile);
-    }
     let is_jspl = match Path::new(&file.val).extension().map(|ext| ext.to_string_lossy()) {
       Some(ext) if ext == "jspl" => true,
       Some(ext) if ext == "json" => false,
       _ => return err!(file.pos, UnsupportedFile),
     };
-    let source = fs::read_to_string(&file.val).map_err(|val| file.pos.with(val))?;
+    let source = self.read_import(&file)?;
     let file_idx = self.parsers.len();
     let parser = self.push_parser(source, file.val.clone())?;
     let root_id = parser.val.dep.id;
@@ -101,4 +89,19 @@ impl Jsonpiler {
     self.import(imports, file_idx, file.pos, scope)?;
     Ok(Null(Lit(())))
   }
+  pub(crate) fn read_import(&self, file: &Pos<String>) -> ErrOR<String> {
+    let total_size: usize = self.parsers.iter().map(|parser| parser.val.text.len()).sum();
+    let limit = u64::from(GB).saturating_sub(u64::try_from(total_size)?);
+    self.provider.read(&file.val, limit).map_err(|err| {
+      if err.kind() == io::ErrorKind::FileTooLarge {
+        Compilation(TooLargeFile, vec![file.pos])
+      } else {
+        file.pos.with(err).into()
+      }
+    })
+  }
+  pub(crate) fn resolve_import(&self, path: &Pos<String>) -> ErrOR<Pos<String>> {
+    let from = &self.parsers[path.pos.file as usize].val.file;
+    Ok(path.pos.with(self.provider.resolve(from, &path.val).map_err(|err| path.pos.with(err))?))
+  }
 }
@@ -0,0 +1,243 @@
+use crate::prelude::*;
+built_in! {self, func, scope, record;
+  f_struct => {"struct", SPECIAL, Exact(2), {
+    let name = func.arg()?.into_ident("Struct name")?;
+    self.check_defined(&name, name.pos, scope)?;
+    if !matches!(JsonType::from_string(&name.val), CustomT(_)) {
+      return err!(name.pos, DuplicateName(StructType, name.val));
+    }
+    let type_annotations = arg_custom!(
+      func, vec![CustomT("TypeAnnotations".into())], (Object(Lit(x))) => x
+    );
+    let mut fields: Vec<(Pos<String>, JsonType, Vec<Position>)> = vec![];
+    for (field, annotation) in type_annotations.val {
+      let type_name = annotation.into_ident("Type annotation")?;
+      let field_type = self.parse_type(&type_name)?;
+      field_type.mem_type(type_name.pos)?;
+      if fields.iter().any(|(other, ..)| other.val == field.val) {
+        return err!(field.pos, DuplicateName(StructField, field.val));
+      }
+      fields.push((field, field_type, vec![]));
+    }
+    let info = StructInfo { copy: None, drop: None, fields, name: name.val, refs: vec![] };
+    self.structs.push(name.pos.with(info));
+    Ok(Null(Lit(())))
+  }},
+}
+pub(crate) struct FieldPath {
+  field_type: JsonType,
+  indices: Vec<u32>,
+  root: Memory,
+}
+impl FieldPath {
+  fn load(&self) -> Vec<Inst> {
+    let mut insts = vec![mov_q(Rax, self.root.0)];
+    let (last, path) = self.indices.split_last().map_or((0, &[][..]), |(last, path)| (*last, path));
+    for idx in path {
+      insts.extend_from_slice(&[mov_d(Rcx, *idx), mov_q(Rax, record_field(Rax, Rcx))]);
+    }
+    insts.push(mov_d(Rcx, last));
+    insts
+  }
+}
+impl Jsonpiler {
+  pub(crate) fn check_unused_structs(&mut self) -> ErrOR<()> {
+    for (id, record) in (0..).zip(self.structs.clone()) {
+      let StructInfo { fields, name, refs, .. } = record.val;
+      if refs.is_empty() && !name.starts_with('_') {
+        self.warn(record.pos, UnusedName(StructType, name.clone()))?;
+      }
+      for (field, json_type, field_refs) in fields {
+        self.push_symbol(SymbolInfo {
+          definition: Some(field.pos),
+          json_type,
+          kind: StructField,
+          name: field.val,
+          refs: field_refs,
+        });
+      }
+      self.push_symbol(SymbolInfo {
+        definition: Some(record.pos),
+        json_type: StructT(name.clone(), id),
+        kind: StructType,
+        name,
+        refs,
+      });
+    }
+    Ok(())
+  }
+  pub(crate) fn construct_record(
+    &mut self,
+    key_val: KeyVal,
+    id: u32,
+    scope: &mut Scope,
+  ) -> ErrOR<Json> {
+    let field_types = self.field_types(id);
+    let mut func = self.func_info(key_val, false, scope)?;
+    func.validate_args(Exact(len_u32(&field_types)?))?;
+    let mut values = vec![];
+    for field_type in field_types {
+      let value = func.arg()?;
+      if value.val.as_type() != field_type {
+        return Err(func.args_err(vec![field_type], value.map_ref(Json::as_type)));
+      }
+      let runtime = self.runtime_array(value, scope)?;
+      func.push_free_tmp(runtime.val.memory());
+      values.push(runtime);
+    }
+    let memory =
+      Memory(Local(Tmp, scope.alloc(8, 8)?), MemoryType { heap: HeapPtr, size: RecordOf(id) });
+    scope.extend(&[
+      mov_q(Rcx, Global(self.symbols[HEAP])),
+      mov_d(Rdx, 8),
+      mov_d(R8, len_u32(&values)? * 8),
+      CallApi(self.api(KERNEL32, "HeapAlloc")),
+      IncMd(Global(self.symbols[LEAK_CNT])),
+      mov_q(memory.0, Rax),
+    ]);
+    for (idx, value) in (0..).zip(values) {
+      scope.extend(&self.mov_json(Rax, value, Some(scope.id))?);
+      scope.extend(&[mov_q(Rcx, memory.0), mov_d(Rdx, idx), mov_q(record_field(Rcx, Rdx), Rax)]);
+    }
+    let name = func.val.name.clone();
+    self.free_all(&mut func, scope)?;
+    Ok(Struct(name, memory))
+  }
+  pub(crate) fn field_path(
+    &mut self,
+    var: &Pos<String>,
+    scope: &mut Scope,
+  ) -> ErrOR<Option<FieldPath>> {
+    let Some((root_name, fields)) = var.val.split_once('.') else {
+      return Ok(None);
+    };
+    if scope.iter_locals().any(|locals| locals.contains_key(&var.val))
+      || self.globals.contains_key(&var.val)
+    {
+      return Ok(None);
+    }
+    let mut pos = var.pos;
+    pos.size = u32::try_from(root_name.len())?;
+    let root = self.get_var(&pos.with(root_name.to_owned()), scope)?;
+    let (Struct(_, memory), mut field_type) = (&root.val.val, root.val.val.as_type()) else {
+      let expected = vec![CustomT("Struct".into())];
+      let name = format_variable(root_name, root.val.kind);
+      return Err(type_err(name, expected, pos.with(root.val.val.as_type())));
+    };
+    let mut indices = vec![];
+    let mut path_name = root_name.to_owned();
+    for field in fields.split('.') {
+      pos.offset = pos.end() + 1;
+      pos.size = u32::try_from(field.len())?;
+      let StructT(struct_name, id) = field_type else {
+        let name = format_variable(&path_name, StructField);
+        return Err(type_err(name, vec![CustomT("Struct".into())], pos.with(field_type)));
+      };
+      path_name.push('.');
+      path_name.push_str(field);
+      let record = &mut self.structs[id as usize].val;
+      let Some(idx) = record.fields.iter().position(|(name, ..)| name.val == field) else {
+        return err!(pos, UndefinedField(struct_name, field.to_owned()));
+      };
+      record.fields[idx].2.push(pos);
+      field_type = record.fields[idx].1.clone();
+      indices.push(u32::try_from(idx)?);
+    }
+    Ok(Some(FieldPath { field_type, indices, root: *memory }))
+  }
+  pub(crate) fn field_place(
+    &mut self,
+    var: &Pos<String>,
+    path: FieldPath,
+    func: &mut Pos<BuiltIn>,
+    scope: &mut Scope,
+  ) -> ErrOR<Variable> {
+    let place = scope.tmp(8, 8, func)?;
+    let memory = Memory(place, MemoryType { heap: HeapPtr, size: Small(RQ) });
+    let json = match path.field_type {
+      IntT => Int(Var(memory)),
+      FloatT => Float(Var(memory)),
+      other @ (ArrayT(_) | BoolT | CustomT(_) | FuncT(_) | MapT(_) | NullT | ObjectT | StrT
+      | StructT(..)) => {
+        let name = format_variable(&var.val, StructField);
+        return Err(type_err(name, vec![IntT, FloatT], var.pos.with(other)));
+      }
+    };
+    scope.extend(&path.load());
+    scope.extend(&[ShiftR(Shl, Rcx, Shift::Ib(3)), AddRR(Rax, Rcx), mov_q(place, Rax)]);
+    Ok(Variable::new(json, StructField))
+  }
+  pub(crate) fn field_types(&self, id: u32) -> Vec<JsonType> {
+    self.structs[id as usize].val.fields.iter().map(|(_, json_type, _)| json_type.clone()).collect()
+  }
+  pub(crate) fn get_field(
+    &mut self,
+    path: FieldPath,
+    pos: Position,
+    scope: &mut Scope,
+  ) -> ErrOR<Json> {
+    scope.extend(&path.load());
+    scope.push(mov_q(Rcx, record_field(Rax, Rcx)));
+    self.copy_elem(&path.field_type, pos, scope)?;
+    scope.ret_json_take(&pos.with(path.field_type), Rax)
+  }
+  pub(crate) fn parse_type(&mut self, name: &Pos<String>) -> ErrOR<JsonType> {
+    self.resolve_type(JsonType::from_string(&name.val), name.pos)
+  }
+  fn resolve_type(&mut self, json_type: JsonType, pos: Position) -> ErrOR<JsonType> {
+    Ok(match json_type {
+      ArrayT(Some(elem)) => ArrayT(Some(Box::new(self.resolve_type(*elem, pos)?))),
+      MapT(value) => MapT(Box::new(self.resolve_type(*value, pos)?)),
+      FuncT(sig) => {
+        let mut params = vec![];
+        for (name, param) in sig.params {
+          params.push((name, self.resolve_type(param, pos)?));
+        }
+        let ret_type = self.resolve_type(sig.ret_type, pos)?;
+        FuncT(Box::new(Signature { params, ret_type }))
+      }
+      CustomT(name) => {
+        let Some(id) = self.structs.iter().position(|record| record.val.name == name) else {
+          return Ok(CustomT(name));
+        };
+        self.structs[id].val.refs.push(pos);
+        StructT(name, u32::try_from(id)?)
+      }
+      other @ (ArrayT(None) | BoolT | FloatT | IntT | NullT | ObjectT | StrT | StructT(..)) => {
+        other
+      }
+    })
+  }
+  pub(crate) fn set_field(
+    &mut self,
+    var: &Pos<String>,
+    path: FieldPath,
+    value: Pos<Json>,
+    func: &mut Pos<BuiltIn>,
+    scope: &mut Scope,
+  ) -> ErrOR<()> {
+    if value.val.as_type() != path.field_type {
+      let name = format_variable(&var.val, StructField);
+      return Err(type_err(name, vec![path.field_type], value.map_ref(Json::as_type)));
+    }
+    let runtime = self.runtime_array(value, scope)?;
+    func.push_free_tmp(runtime.val.memory());
+    let tmp_value = scope.tmp(8, 8, func)?;
+    scope.extend(&self.mov_json(Rax, runtime, Some(scope.id))?);
+    scope.push(mov_q(tmp_value, Rax));
+    scope.extend(&path.load());
+    if matches!(path.field_type, StrT | ArrayT(_) | MapT(_) | StructT(..) | FuncT(_)) {
+      let tmp_field = scope.tmp(8, 8, func)?;
+      scope.extend(&[
+        ShiftR(Shl, Rcx, Shift::Ib(3)),
+        AddRR(Rax, Rcx),
+        mov_q(tmp_field, Rax),
+        mov_q(Rcx, Ref(Rax)),
+      ]);
+      self.drop_elem(&path.field_type, var.pos, scope)?;
+      scope.extend(&[mov_q(Rax, tmp_field), Clear(Rcx)]);
+    }
+    scope.extend(&[mov_q(Rdx, tmp_value), mov_q(record_field(Rax, Rcx), Rdx)]);
+    Ok(())
+  }
+}
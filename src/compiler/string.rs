@@ -6,8 +6,21 @@ built_in! {self, func, scope, string;
     scope.ret_str(Rax, HeapPtr)
   }},
   len => {"len", COMMON, Exact(1), {
-    let str_chars_len = self.str_chars_len(scope.id)?;
-    scope.extend(&[self.mov_str(Rcx, arg!(func, (Str(x)) => x).val), Call(str_chars_len)]);
+    match func.arg()? {
+      Pos { val: Str(string), .. } => {
+        let str_chars_len = self.str_chars_len(scope.id)?;
+        scope.extend(&[self.mov_str(Rcx, string), Call(str_chars_len)]);
+      }
+      Pos { val: Array(Lit(elems)), .. } => return Ok(Int(Lit(i64::from(len_u32(&elems)?)))),
+      Pos { val: Array(Var(memory)) | Object(Var(memory)), .. } => {
+        scope.extend(&mov_memory(Rax, memory));
+        scope.push(mov_q(Rax, Ref(Rax)));
+      }
+      other => {
+        let expected = vec![StrT, ArrayT(None), JsonType::any_map()];
+        return Err(func.args_err(expected, other.map_ref(Json::as_type)));
+      }
+    }
     Ok(Int(Var(scope.ret(Rax)?)))
   }},
   slice => {"slice", COMMON, Range(2, 3), {
@@ -54,14 +67,11 @@ impl Jsonpiler {
         string = arg!(func, (Str(x)) => x).val;
       }
     }
-    let leak = Global(self.symbols[LEAK_CNT]);
+    let str_alloc = self.str_alloc(scope.id)?;
     scope.extend(&[
-      mov_q(Rcx, Global(self.symbols[HEAP])),
-      mov_d(Rdx, 8),
-      mov_q(R8, acc_len),
-      IncR(R8),
-      CallApi(self.api(KERNEL32, "HeapAlloc")),
-      IncMd(leak),
+      mov_q(Rcx, acc_len),
+      IncR(Rcx),
+      Call(str_alloc),
       mov_q(buffer, Rax),
       mov_q(Rdi, Rax),
     ]);
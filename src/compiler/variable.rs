@@ -5,7 +5,7 @@ impl Jsonpiler {
   pub(crate) fn assign(
     &mut self,
     is_global_opt: Option<bool>,
-    (var, val): KeyVal,
+    (var, value_expr): KeyVal,
     scope: &mut Scope,
   ) -> ErrOR<bool> {
     let reassign = if let Some(is_g) = is_global_opt {
@@ -13,11 +13,11 @@ impl Jsonpiler {
       Err(is_g)
     } else {
       let variable = self.get_var(&var, scope)?.val;
-      if variable.val.as_type() != val.val.as_type() {
+      if variable.val.as_type() != value_expr.val.as_type() {
         return Err(type_err(
           format_variable(&var.val, variable.kind),
           vec![variable.val.as_type()],
-          val.map_ref(Json::as_type),
+          value_expr.map_ref(Json::as_type),
         ));
       }
       Ok(
@@ -27,6 +27,7 @@ impl Jsonpiler {
           .ok_or_else(|| Compilation(UndefinedVar(var.val.clone()), vec![var.pos]))?,
       )
     };
+    let val = self.runtime_array(value_expr, scope)?;
     let is_global = reassign.is_err_and(|is_g| is_g);
     let call_once = scope.loop_labels.is_empty() && scope.epilogue.is_none();
     let data_sect = is_global && call_once;
@@ -34,7 +35,8 @@ impl Jsonpiler {
       Bool(Lit(lit)) if data_sect => Bool(Var(self.global_b(*lit))),
       Int(Lit(int)) if data_sect => Int(Var(self.global_q(int.cast_unsigned()))),
       Float(Lit(lit)) if data_sect => Float(Var(self.global_q(lit.to_bits()))),
-      Null(_) | Array(_) | Bool(_) | Float(_) | Int(_) | Object(_) | Str(_) => {
+      Null(_) | Array(_) | Bool(_) | Float(_) | Func(..) | Int(_) | Object(_) | Str(_)
+      | Struct(..) => {
         if is_global {
           self.critical_sect(scope, ENTER);
         }
@@ -42,7 +44,7 @@ impl Jsonpiler {
         let size = val_type.mem_type(val.pos)?.size();
         let memory = match reassign {
           Ok(memory) => {
-            self.heap_free(memory, scope);
+            self.heap_free(memory, scope)?;
             memory
           }
           Err(is_g) => Memory(
@@ -65,7 +67,7 @@ impl Jsonpiler {
         let value = val_type.to_json(val.pos, memory.0)?;
         scope.extend(&self.mov_json(Rax, val.clone(), Some(scope.id))?);
         scope.extend(&ret_memory(memory, Rcx, Rax)?);
-        self.drop_json(val.val, false, scope);
+        self.drop_json(val.val, false, scope)?;
         if is_global {
           self.critical_sect(scope, LEAVE);
         }
@@ -114,6 +116,10 @@ built_in! {self, func, scope, variable;
   reassign => {"=", SPECIAL, Exact(2), {
     let var = func.arg()?.into_ident("Variable name")?;
     let val = self.eval(func.arg()?, scope)?;
+    if let Some(path) = self.field_path(&var, scope)? {
+      self.set_field(&var, path, val, func, scope)?;
+      return Ok(Null(Lit(())));
+    }
     if self.assign(None, (var.clone(), val), scope)? {
       Ok(Null(Lit(())))
     } else {
@@ -121,7 +127,14 @@ built_in! {self, func, scope, variable;
     }
   }},
   reference => {"$", COMMON, Exact(1), {
-    Ok(self.get_var(&arg!(func, (Str(Lit(x))) => x), scope)?.val.val.clone())
+    let var = arg!(func, (Str(Lit(x))) => x);
+    if let Some(path) = self.field_path(&var, scope)? {
+      return self.get_field(path, var.pos, scope);
+    }
+    match self.get_var(&var, scope) {
+      Ok(variable) => Ok(variable.val.val),
+      Err(err) => self.function_value(&var, scope)?.ok_or(err),
+    }
   }},
   scope => {"scope", SP_SCOPE, Exact(1), {
     Ok(self.eval(func.arg()?, scope)?.val)
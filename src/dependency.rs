@@ -53,7 +53,7 @@ impl Jsonpiler {
     for compiled in self.functions.values().filter(|compiled| reachable.contains(&compiled.dep.id))
     {
       if let Some((end, stack_size)) = compiled.seh {
-        seh.push((compiled.dep.id, end, stack_size));
+        seh.push((compiled.dep.id, end, stack_size, vec![]));
       }
     }
     let insts = reachable
@@ -154,6 +154,8 @@ impl Jsonpiler {
     } else if let Some(u_d) = self.user_defined.values_mut().find(|u_d| u_d.val.dep.id == caller) {
       u_d.val.dep.add(id);
       Ok(())
+    } else if let Some(parent) = self.closures.get(&caller).copied() {
+      self.use_u_d(parent, id)
     } else {
       Err(Internal(UnknownLabel))
     }
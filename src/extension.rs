@@ -0,0 +1,368 @@
+use crate::api::Diagnostic;
+use crate::prelude::*;
+use std::rc::Rc;
+/// Generates the code of an embedder-registered built-in for one call.
+pub(crate) type Codegen = Rc<dyn Fn(&mut BuiltInCall<'_>) -> Result<Output, CallError>>;
+/// An embedder-registered built-in, kept so the language server can register it again.
+#[derive(Clone)]
+pub(crate) struct Extension {
+  pub arity: Arity,
+  pub codegen: Codegen,
+  pub doc: String,
+  pub flags: Flags,
+  pub name: &'static str,
+}
+/// A call of a registered built-in, through which its code is generated.
+pub struct BuiltInCall<'call> {
+  func: &'call mut Pos<BuiltIn>,
+  jsonpiler: &'call mut Jsonpiler,
+  /// Registers written so far, which are saved while a lazy argument is evaluated.
+  live: Vec<Reg>,
+  scope: &'call mut Scope,
+  skip_eval: bool,
+}
+/// A compilation error raised while generating a built-in call.
+#[derive(Debug)]
+pub struct CallError(JsonpilerErr);
+/// How the arguments of a built-in are evaluated.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Flags {
+  /// Variables declared in the arguments are dropped when the call ends.
+  pub scoped: bool,
+  /// Arguments are evaluated only when [`BuiltInCall::arg`] takes them. Registers written by
+  /// earlier arguments and [`Op`]s are saved on the stack while the argument is evaluated, so an
+  /// argument that calls a function does not clobber them.
+  pub skip_eval: bool,
+}
+/// The value a built-in call evaluates to.
+#[derive(Debug)]
+pub struct Output(Json);
+/// The number of arguments a built-in accepts.
+#[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
+pub enum Arity {
+  AtLeast(u32),
+  AtMost(u32),
+  Exact(u32),
+  Range(u32, u32),
+}
+/// Type of an argument loaded into a register; a `Float` is loaded as its bits and a `Str` as a
+/// pointer to its NUL-terminated UTF-8 text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ArgType {
+  Bool,
+  Float,
+  Int,
+  Str,
+}
+/// Condition of [`Op::Set`], comparing the operands of the preceding [`Op::Cmp`] as signed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Cond {
+  Eq,
+  Ge,
+  Gt,
+  Le,
+  Lt,
+  Ne,
+}
+/// The instructions a built-in may emit, on 64-bit registers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Op {
+  Add(Reg, Reg),
+  And(Reg, Reg),
+  /// Calls `dll!func` with the arguments in `Rcx`, `Rdx`, `R8` and `R9`; the result is in `Rax`.
+  /// Windows only.
+  CallApi(&'static str, &'static str),
+  Cmp(Reg, Reg),
+  Dec(Reg),
+  Inc(Reg),
+  Mov(Reg, Reg),
+  MovImm(Reg, i64),
+  Mul(Reg, Reg),
+  Neg(Reg),
+  Not(Reg),
+  Or(Reg, Reg),
+  Sar(Reg, u8),
+  /// Sets the low byte of the register to 1 if the condition holds, and to 0 otherwise.
+  Set(Cond, Reg),
+  Shl(Reg, u8),
+  Shr(Reg, u8),
+  Sub(Reg, Reg),
+  Xor(Reg, Reg),
+}
+/// The registers a built-in may clobber.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Reg {
+  R10,
+  R11,
+  R8,
+  R9,
+  Rax,
+  Rcx,
+  Rdx,
+}
+impl BuiltInCall<'_> {
+  /// Takes the next argument and loads it into `dst`.
+  ///
+  /// # Errors
+  /// When the argument does not compile or is not a `Bool`, `Float`, `Int` or `Str`.
+  #[inline]
+  pub fn arg(&mut self, dst: Reg) -> Result<ArgType, CallError> {
+    let mut arg = self.func.arg()?;
+    if self.skip_eval {
+      let mut saved = vec![];
+      for reg in &self.live {
+        let offset = self.scope.alloc(8, 8)?;
+        self.scope.push(mov_q(Local(Tmp, offset), Register::from(*reg)));
+        saved.push((*reg, offset));
+      }
+      arg = self.jsonpiler.eval(arg, self.scope)?;
+      self.func.push_free_tmp(arg.val.memory());
+      for (reg, offset) in saved {
+        self.scope.push(mov_q(Register::from(reg), Local(Tmp, offset)));
+        self.scope.free(offset, MemoryType { heap: Value, size: Small(RQ) });
+      }
+    }
+    let arg_type = match arg.val {
+      Bool(_) => ArgType::Bool,
+      Float(_) => ArgType::Float,
+      Int(_) => ArgType::Int,
+      Str(_) => ArgType::Str,
+      Array(_) | Func(..) | Null(_) | Object(_) | Struct(..) => {
+        return Err(
+          self.func.args_err(vec![BoolT, FloatT, IntT, StrT], arg.map_ref(Json::as_type)).into(),
+        );
+      }
+    };
+    let insts = self.jsonpiler.mov_json(dst.into(), arg, None)?;
+    self.scope.extend(&insts);
+    self.written(dst);
+    Ok(arg_type)
+  }
+  /// Number of arguments passed to this call.
+  #[must_use]
+  #[inline]
+  pub fn arg_count(&self) -> u32 {
+    self.func.val.len
+  }
+  /// Takes the next argument, which must be of type `expected`, and loads it into `dst`.
+  ///
+  /// # Errors
+  /// When the argument does not compile or has another type.
+  #[inline]
+  pub fn arg_of(&mut self, dst: Reg, expected: ArgType) -> Result<(), CallError> {
+    let pos = self.func.val.args.as_slice().first().map(|arg| arg.pos);
+    let actual = self.arg(dst)?;
+    if actual == expected {
+      return Ok(());
+    }
+    let json_type = pos.unwrap_or(self.func.pos).with(actual.into());
+    Err(self.func.args_err(vec![expected.into()], json_type).into())
+  }
+  /// The call evaluates to the `Bool` in the low byte of `src`.
+  ///
+  /// # Errors
+  /// When the stack frame overflows.
+  #[inline]
+  pub fn bool(&mut self, src: Reg) -> Result<Output, CallError> {
+    Ok(Output(self.scope.ret_bool(src.into())?))
+  }
+  /// Appends `op` to the generated code.
+  ///
+  /// # Errors
+  /// When `op` is [`Op::CallApi`] and the target is not Windows.
+  #[inline]
+  pub fn emit(&mut self, op: Op) -> Result<(), CallError> {
+    let inst = match op {
+      Op::Add(dst, src) => AddRR(dst.into(), src.into()),
+      Op::And(dst, src) => LogicRR(And, dst.into(), src.into()),
+      Op::CallApi(dll, func) => {
+        self.jsonpiler.require_windows(self.func)?;
+        self.scope.update_args_count(4);
+        CallApi(self.jsonpiler.api(dll, func))
+      }
+      Op::Cmp(lhs, rhs) => LogicRR(Cmp, lhs.into(), rhs.into()),
+      Op::Dec(dst) => DecR(dst.into()),
+      Op::Inc(dst) => IncR(dst.into()),
+      Op::Mov(dst, src) => mov_q(Register::from(dst), Register::from(src)),
+      Op::MovImm(dst, imm) => mov_imm(dst.into(), imm),
+      Op::Mul(dst, src) => IMulRR(dst.into(), src.into()),
+      Op::Neg(dst) => UnaryR(Neg, dst.into()),
+      Op::Not(dst) => UnaryR(Not, dst.into()),
+      Op::Or(dst, src) => LogicRR(Or, dst.into(), src.into()),
+      Op::Sar(dst, imm) => ShiftR(ShiftDirection::Sar, dst.into(), Shift::Ib(imm)),
+      Op::Set(cond, dst) => SetCc(dst.into(), cond.into()),
+      Op::Shl(dst, imm) => ShiftR(Shl, dst.into(), Shift::Ib(imm)),
+      Op::Shr(dst, imm) => ShiftR(Shr, dst.into(), Shift::Ib(imm)),
+      Op::Sub(dst, src) => SubRR(dst.into(), src.into()),
+      Op::Xor(dst, src) => LogicRR(Xor, dst.into(), src.into()),
+    };
+    self.scope.push(inst);
+    match op {
+      Op::CallApi(..) => self.written(Reg::Rax),
+      Op::Add(dst, _)
+      | Op::And(dst, _)
+      | Op::Dec(dst)
+      | Op::Inc(dst)
+      | Op::Mov(dst, _)
+      | Op::MovImm(dst, _)
+      | Op::Mul(dst, _)
+      | Op::Neg(dst)
+      | Op::Not(dst)
+      | Op::Or(dst, _)
+      | Op::Sar(dst, _)
+      | Op::Set(_, dst)
+      | Op::Shl(dst, _)
+      | Op::Shr(dst, _)
+      | Op::Sub(dst, _)
+      | Op::Xor(dst, _) => self.written(dst),
+      Op::Cmp(..) => (),
+    }
+    Ok(())
+  }
+  /// An error reported at this call.
+  #[must_use]
+  #[inline]
+  pub fn error(&self, message: &str) -> CallError {
+    CallError(Compilation(ExtensionError(message.to_owned()), vec![self.func.pos]))
+  }
+  /// The call evaluates to the `Float` whose bits are in `src`.
+  ///
+  /// # Errors
+  /// When the stack frame overflows.
+  #[inline]
+  pub fn float(&mut self, src: Reg) -> Result<Output, CallError> {
+    Ok(Output(Float(Var(self.scope.ret(src.into())?))))
+  }
+  /// The call evaluates to the `Int` in `src`.
+  ///
+  /// # Errors
+  /// When the stack frame overflows.
+  #[inline]
+  pub fn int(&mut self, src: Reg) -> Result<Output, CallError> {
+    Ok(Output(Int(Var(self.scope.ret(src.into())?))))
+  }
+  /// Name under which the built-in was registered.
+  #[must_use]
+  #[inline]
+  pub fn name(&self) -> &str {
+    &self.func.val.name
+  }
+  /// The call evaluates to `null`.
+  #[must_use]
+  #[inline]
+  pub fn null(&self) -> Output {
+    Output(Null(Lit(())))
+  }
+  fn written(&mut self, reg: Reg) {
+    if self.skip_eval && !self.live.contains(&reg) {
+      self.live.push(reg);
+    }
+  }
+}
+impl Flags {
+  /// Flags for a built-in whose arguments are evaluated before the call in the caller's scope.
+  pub const COMMON: Self = Self { scoped: false, skip_eval: false };
+  /// Flags with the given scoping and evaluation.
+  #[must_use]
+  #[inline]
+  pub const fn new(scoped: bool, skip_eval: bool) -> Self {
+    Self { scoped, skip_eval }
+  }
+}
+impl From<ArgType> for JsonType {
+  fn from(arg_type: ArgType) -> Self {
+    match arg_type {
+      ArgType::Bool => BoolT,
+      ArgType::Float => FloatT,
+      ArgType::Int => IntT,
+      ArgType::Str => StrT,
+    }
+  }
+}
+impl From<Cond> for ConditionCode {
+  fn from(cond: Cond) -> Self {
+    match cond {
+      Cond::Eq => E,
+      Cond::Ge => Ge,
+      Cond::Gt => G,
+      Cond::Le => Le,
+      Cond::Lt => L,
+      Cond::Ne => Ne,
+    }
+  }
+}
+impl From<JsonpilerErr> for CallError {
+  #[inline]
+  fn from(err: JsonpilerErr) -> Self {
+    CallError(err)
+  }
+}
+impl From<Reg> for Register {
+  fn from(reg: Reg) -> Self {
+    match reg {
+      Reg::R10 => R10,
+      Reg::R11 => R11,
+      Reg::R8 => R8,
+      Reg::R9 => R9,
+      Reg::Rax => Rax,
+      Reg::Rcx => Rcx,
+      Reg::Rdx => Rdx,
+    }
+  }
+}
+impl fmt::Display for CallError {
+  #[inline]
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}", self.0)
+  }
+}
+impl Jsonpiler {
+  /// Registers the built-in `name`, whose calls are compiled by `codegen`.
+  ///
+  /// `doc` is Markdown shown by the language server on hover. Registered built-ins are also
+  /// known to `jsonpiler server` when it is started through [`Jsonpiler::main`], but not to
+  /// `interpret`.
+  ///
+  /// # Errors
+  /// When `name` is already a built-in.
+  #[inline]
+  pub fn add_builtin<F>(
+    &mut self,
+    name: &'static str,
+    arity: Arity,
+    flags: Flags,
+    doc: &str,
+    codegen: F,
+  ) -> Result<(), Diagnostic>
+  where
+    F: Fn(&mut BuiltInCall<'_>) -> Result<Output, CallError> + 'static,
+  {
+    if self.builtin.contains_key(name) {
+      return Err(self.diagnostic(&Platform(format!("Built-in `{name}` is already defined"))));
+    }
+    let extension =
+      Extension { arity, codegen: Rc::new(codegen), doc: doc.to_owned(), flags, name };
+    self.register_extension(extension);
+    Ok(())
+  }
+  fn extension_call(&mut self, func: &mut Pos<BuiltIn>, scope: &mut Scope) -> ErrOR<Json> {
+    let Some(extension) = self.extensions.iter().find(|ext| ext.name == func.val.name) else {
+      return err!(func.pos, UndefinedFunc(func.val.name.clone()));
+    };
+    let codegen = Rc::clone(&extension.codegen);
+    let skip_eval = extension.flags.skip_eval;
+    let mut call = BuiltInCall { func, jsonpiler: self, live: vec![], scope, skip_eval };
+    codegen(&mut call).map(|Output(json)| json).map_err(|CallError(err)| err)
+  }
+  pub(crate) fn register_extension(&mut self, extension: Extension) {
+    let flags = (extension.flags.scoped, extension.flags.skip_eval);
+    self.register_func(extension.name, flags, Jsonpiler::extension_call, extension.arity);
+    self.extensions.push(extension);
+  }
+}
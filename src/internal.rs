@@ -1,6 +1,13 @@
+pub(crate) mod array;
+mod closure;
+mod dll;
 pub(crate) mod handler;
 mod input;
+mod linux;
+pub(crate) mod map;
 mod misc;
 mod print_n;
+pub(crate) mod record;
+mod source_map;
 mod str_utility;
 mod wnd_proc;
@@ -0,0 +1,229 @@
+use crate::prelude::*;
+impl Jsonpiler {
+  pub(crate) fn array_copy(&mut self, caller: LabelId) -> ErrOR<LabelId> {
+    const SIZE: i32 = 0x40;
+    let id = symbol!(self, caller, ARRAY_COPY);
+    let elem_copy = self.elem_copy(id)?;
+    let next = self.id();
+    let epilogue = self.id();
+    let tmp_b = Local(Tmp, -0x08);
+    let tmp_s = Local(Tmp, -0x10);
+    let tmp_d = Local(Tmp, -0x18);
+    let tmp_12 = Local(Tmp, -0x20);
+    let insts = &[
+      mov_q(tmp_b, Rbx),
+      mov_q(tmp_s, Rsi),
+      mov_q(tmp_d, Rdi),
+      mov_q(tmp_12, R12),
+      mov_q(Rbx, Rcx),
+      mov_q(Rsi, Rdx),
+      mov_q(Rdi, Ref(Rbx)),
+      mov_q(R8, Rdi),
+      ShiftR(Shl, R8, Shift::Ib(3)),
+      AddRId(R8, ARRAY_HEADER as u32),
+      mov_q(Rcx, Global(self.symbols[HEAP])),
+      mov_d(Rdx, 8),
+      CallApi(self.api(KERNEL32, "HeapAlloc")),
+      IncMd(Global(self.symbols[LEAK_CNT])),
+      mov_q(R12, Rax),
+      mov_q(Ref(R12), Rdi),
+      AddRId(Rax, 8),
+      mov_q(Ref(Rax), Rdi),
+      Lbl(next),
+      LogicRR(Test, Rdi, Rdi),
+      JCc(E, epilogue),
+      DecR(Rdi),
+      mov_q(Rcx, array_elem(Rbx, Rdi)),
+      mov_q(Rdx, Rsi),
+      Call(elem_copy),
+      mov_q(array_elem(R12, Rdi), Rax),
+      Jmp(next),
+      Lbl(epilogue),
+      mov_q(Rax, R12),
+      mov_q(Rbx, tmp_b),
+      mov_q(Rsi, tmp_s),
+      mov_q(Rdi, tmp_d),
+      mov_q(R12, tmp_12),
+    ];
+    self.link_function(id, insts, SIZE);
+    Ok(id)
+  }
+  pub(crate) fn array_drop(&mut self, caller: LabelId) -> ErrOR<LabelId> {
+    const SIZE: i32 = 0x40;
+    let id = symbol!(self, caller, ARRAY_DROP);
+    let elem_drop = self.elem_drop(id)?;
+    let next = self.id();
+    let release = self.id();
+    let tmp_b = Local(Tmp, -0x08);
+    let tmp_s = Local(Tmp, -0x10);
+    let tmp_d = Local(Tmp, -0x18);
+    let insts = &[
+      mov_q(tmp_b, Rbx),
+      mov_q(tmp_s, Rsi),
+      mov_q(tmp_d, Rdi),
+      mov_q(Rbx, Rcx),
+      mov_q(Rsi, Rdx),
+      mov_q(Rdi, Ref(Rbx)),
+      mov_q(Rax, Rsi),
+      mov_d(Rcx, SHAPE_MASK as u32),
+      LogicRR(And, Rax, Rcx),
+      mov_d(Rcx, SHAPE_ARRAY as u32),
+      LogicRR(Cmp, Rax, Rcx),
+      JCc(E, next),
+      mov_d(Rcx, SHAPE_STR as u32),
+      LogicRR(Cmp, Rax, Rcx),
+      JCc(E, next),
+      mov_d(Rcx, SHAPE_MAP as u32),
+      LogicRR(Cmp, Rax, Rcx),
+      JCc(E, next),
+      // Elements of other types own no memory.
+      Clear(Rdi),
+      Lbl(next),
+      LogicRR(Test, Rdi, Rdi),
+      JCc(E, release),
+      DecR(Rdi),
+      mov_q(Rcx, array_elem(Rbx, Rdi)),
+      mov_q(Rdx, Rsi),
+      Call(elem_drop),
+      Jmp(next),
+      Lbl(release),
+      mov_q(R8, Rbx),
+      mov_q(Rcx, Global(self.symbols[HEAP])),
+      Clear(Rdx),
+      CallApiCheck(self.api(KERNEL32, "HeapFree")),
+      DecMd(Global(self.symbols[LEAK_CNT])),
+      mov_q(Rbx, tmp_b),
+      mov_q(Rsi, tmp_s),
+      mov_q(Rdi, tmp_d),
+    ];
+    self.link_function(id, insts, SIZE);
+    Ok(id)
+  }
+  pub(crate) fn array_push(&mut self, caller: LabelId) -> ErrOR<LabelId> {
+    const SIZE: i32 = 0x40;
+    let id = symbol!(self, caller, ARRAY_PUSH);
+    let store = self.id();
+    let tmp_b = Local(Tmp, -0x08);
+    let tmp_s = Local(Tmp, -0x10);
+    let cap = Local(Tmp, -0x18);
+    let insts = &[
+      mov_q(tmp_b, Rbx),
+      mov_q(tmp_s, Rsi),
+      mov_q(Rbx, Rcx),
+      mov_q(Rsi, Rdx),
+      mov_q(Rax, Rbx),
+      AddRId(Rax, 8),
+      mov_q(R9, Ref(Rax)),
+      mov_q(R8, Ref(Rbx)),
+      LogicRR(Cmp, R8, R9),
+      JCc(B, store),
+      AddRR(R9, R9),
+      mov_d(Rax, 4),
+      LogicRR(Cmp, R9, Rax),
+      CMovCc(B, R9, Rax),
+      mov_q(cap, R9),
+      ShiftR(Shl, R9, Shift::Ib(3)),
+      AddRId(R9, ARRAY_HEADER as u32),
+      mov_q(R8, Rbx),
+      mov_q(Rcx, Global(self.symbols[HEAP])),
+      Clear(Rdx),
+      CallApiCheck(self.api(KERNEL32, "HeapReAlloc")),
+      mov_q(Rbx, Rax),
+      AddRId(Rax, 8),
+      mov_q(Rcx, cap),
+      mov_q(Ref(Rax), Rcx),
+      Lbl(store),
+      mov_q(R8, Ref(Rbx)),
+      mov_q(array_elem(Rbx, R8), Rsi),
+      IncR(R8),
+      mov_q(Ref(Rbx), R8),
+      mov_q(Rax, Rbx),
+      mov_q(Rbx, tmp_b),
+      mov_q(Rsi, tmp_s),
+    ];
+    self.link_function(id, insts, SIZE);
+    Ok(id)
+  }
+  pub(crate) fn elem_copy(&mut self, caller: LabelId) -> ErrOR<LabelId> {
+    const SIZE: i32 = 0x20;
+    let id = symbol!(self, caller, ELEM_COPY);
+    let array_copy = self.array_copy(id)?;
+    let map_copy = self.map_copy(id)?;
+    let str_retain = self.str_retain(id)?;
+    let array = self.id();
+    let map = self.id();
+    let string = self.id();
+    let epilogue = self.id();
+    let insts = &[
+      mov_q(Rax, Rdx),
+      mov_d(R8, SHAPE_MASK as u32),
+      LogicRR(And, Rax, R8),
+      mov_d(R8, SHAPE_ARRAY as u32),
+      LogicRR(Cmp, Rax, R8),
+      JCc(E, array),
+      mov_d(R8, SHAPE_MAP as u32),
+      LogicRR(Cmp, Rax, R8),
+      JCc(E, map),
+      mov_d(R8, SHAPE_STR as u32),
+      LogicRR(Cmp, Rax, R8),
+      JCc(E, string),
+      mov_q(Rax, Rcx),
+      Jmp(epilogue),
+      Lbl(array),
+      ShiftR(Shr, Rdx, Shift::Ib(SHAPE_BITS as u8)),
+      Call(array_copy),
+      Jmp(epilogue),
+      Lbl(map),
+      ShiftR(Shr, Rdx, Shift::Ib(SHAPE_BITS as u8)),
+      Call(map_copy),
+      Jmp(epilogue),
+      Lbl(string),
+      Call(str_retain),
+      Lbl(epilogue),
+    ];
+    self.link_function(id, insts, SIZE);
+    Ok(id)
+  }
+  pub(crate) fn elem_drop(&mut self, caller: LabelId) -> ErrOR<LabelId> {
+    const SIZE: i32 = 0x20;
+    let id = symbol!(self, caller, ELEM_DROP);
+    let array_drop = self.array_drop(id)?;
+    let map_drop = self.map_drop(id)?;
+    let str_release = self.str_release(id)?;
+    let array = self.id();
+    let map = self.id();
+    let string = self.id();
+    let epilogue = self.id();
+    let insts = &[
+      mov_q(Rax, Rdx),
+      mov_d(R8, SHAPE_MASK as u32),
+      LogicRR(And, Rax, R8),
+      mov_d(R8, SHAPE_ARRAY as u32),
+      LogicRR(Cmp, Rax, R8),
+      JCc(E, array),
+      mov_d(R8, SHAPE_MAP as u32),
+      LogicRR(Cmp, Rax, R8),
+      JCc(E, map),
+      mov_d(R8, SHAPE_STR as u32),
+      LogicRR(Cmp, Rax, R8),
+      JCc(E, string),
+      Jmp(epilogue),
+      Lbl(array),
+      ShiftR(Shr, Rdx, Shift::Ib(SHAPE_BITS as u8)),
+      Call(array_drop),
+      Jmp(epilogue),
+      Lbl(map),
+      ShiftR(Shr, Rdx, Shift::Ib(SHAPE_BITS as u8)),
+      Call(map_drop),
+      Jmp(epilogue),
+      Lbl(string),
+      Call(str_release),
+      Lbl(epilogue),
+    ];
+    self.link_function(id, insts, SIZE);
+    Ok(id)
+  }
+}
+pub(crate) fn array_elem(base: Register, index: Register) -> Operand<u64> {
+  SibDisp(Sib { base, index, scale: S8 }, Disp::Byte(ARRAY_HEADER))
+}
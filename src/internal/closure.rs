@@ -0,0 +1,75 @@
+use crate::prelude::*;
+impl Jsonpiler {
+  pub(crate) fn closure_drop(
+    &mut self,
+    captures: &[JsonType],
+    caller: LabelId,
+  ) -> ErrOR<Option<LabelId>> {
+    const SIZE: i32 = 0x30;
+    if !captures.iter().any(|capture| {
+      capture.mem_type(Position::default()).is_ok_and(|mem_type| mem_type.heap == HeapPtr)
+    }) {
+      return Ok(None);
+    }
+    let label = self.id();
+    self.use_function(caller, label);
+    let tmp_b = Local(Tmp, -0x08);
+    let mut insts = vec![mov_q(tmp_b, Rbx), mov_q(Rbx, Rcx)];
+    for (idx, capture) in (CLOSURE_HEADER..).zip(captures) {
+      let drop = self.field_drop(capture, label)?;
+      if !drop.is_empty() {
+        insts.extend_from_slice(&[mov_d(Rax, idx), mov_q(Rcx, record_field(Rbx, Rax))]);
+        insts.extend(drop);
+      }
+    }
+    insts.push(mov_q(Rbx, tmp_b));
+    self.link_function(label, &insts, SIZE);
+    Ok(Some(label))
+  }
+  pub(crate) fn closure_release(&mut self, caller: LabelId) -> ErrOR<LabelId> {
+    const SIZE: i32 = 0x30;
+    let id = symbol!(self, caller, CLOSURE_RELEASE);
+    let free = self.id();
+    let epilogue = self.id();
+    let tmp_b = Local(Tmp, -0x08);
+    let insts = &[
+      mov_q(tmp_b, Rbx),
+      mov_q(Rbx, Rcx),
+      mov_d(Rdx, CLOSURE_COUNT),
+      mov_q(Rax, record_field(Rbx, Rdx)),
+      DecR(Rax),
+      mov_q(record_field(Rbx, Rdx), Rax),
+      LogicRR(Test, Rax, Rax),
+      JCc(Ne, epilogue),
+      mov_d(Rdx, CLOSURE_DROP),
+      mov_q(Rax, record_field(Rbx, Rdx)),
+      LogicRR(Test, Rax, Rax),
+      JCc(E, free),
+      mov_q(Rcx, Rbx),
+      Custom(CALL_RAX),
+      Lbl(free),
+      mov_q(R8, Rbx),
+      mov_q(Rcx, Global(self.symbols[HEAP])),
+      Clear(Rdx),
+      CallApiCheck(self.api(KERNEL32, "HeapFree")),
+      DecMd(Global(self.symbols[LEAK_CNT])),
+      Lbl(epilogue),
+      mov_q(Rbx, tmp_b),
+    ];
+    self.link_function(id, insts, SIZE);
+    Ok(id)
+  }
+  pub(crate) fn closure_retain(&mut self, caller: LabelId) -> ErrOR<LabelId> {
+    const SIZE: i32 = 0x20;
+    let id = symbol!(self, caller, CLOSURE_RETAIN);
+    let insts = &[
+      mov_d(Rdx, CLOSURE_COUNT),
+      mov_q(Rax, record_field(Rcx, Rdx)),
+      IncR(Rax),
+      mov_q(record_field(Rcx, Rdx), Rax),
+      mov_q(Rax, Rcx),
+    ];
+    self.link_function(id, insts, SIZE);
+    Ok(id)
+  }
+}
@@ -0,0 +1,81 @@
+use crate::prelude::*;
+impl Jsonpiler {
+  pub(crate) fn dll_entry(&mut self) -> ErrOR<(LabelId, Vec<(String, LabelId)>)> {
+    const PROCESS_ATTACH: u32 = 1;
+    let root = self.first_parser()?.val.dep.id;
+    let dll_main = self.id();
+    let end = self.id();
+    self.use_function(dll_main, root);
+    let insts = [
+      mov_d(Rdx, Rdx),
+      mov_d(Rax, PROCESS_ATTACH),
+      LogicRR(Cmp, Rdx, Rax),
+      JCc(Ne, end),
+      Call(root),
+      Lbl(end),
+      mov_d(Rax, 1),
+    ];
+    self.link_function(dll_main, &insts, 0x20);
+    self.use_function(root, dll_main);
+    let exports = self.export_thunks(root)?;
+    Ok((dll_main, exports))
+  }
+  fn export_thunk(&mut self, func: LabelId, sig: &Signature) -> ErrOR<LabelId> {
+    let thunk = self.id();
+    self.use_function(thunk, func);
+    let params = len_u32(&sig.params)?;
+    let mut insts = vec![];
+    for (idx, (reg, xmm)) in ARG_REGS.into_iter().zip([Rax, Rcx, Rdx, Rbx]).enumerate() {
+      let home = Local(Tmp, 0x10 + i32::try_from(idx)? * 8);
+      if sig.params.get(idx).is_some_and(|(_, param)| *param == FloatT) {
+        insts.extend_from_slice(&[MovRSd(Rax, xmm), mov_q(home, Rax)]);
+      } else {
+        insts.push(mov_q(home, reg));
+      }
+    }
+    for (idx, reg) in NON_VOLATILE_REGS.into_iter().enumerate() {
+      insts.push(mov_q(Local(Tmp, -8 - i32::try_from(idx)? * 8), reg));
+    }
+    for idx in 0..i32::try_from(params)? {
+      insts.extend_from_slice(&[mov_q(Rax, Local(Tmp, 0x10 + idx * 8)), mov_q(Args(idx + 1), Rax)]);
+    }
+    insts.push(Call(func));
+    if sig.ret_type == FloatT {
+      insts.push(MovSdR(Rax, Rax));
+    }
+    for (idx, reg) in NON_VOLATILE_REGS.into_iter().enumerate() {
+      insts.push(mov_q(reg, Local(Tmp, -8 - i32::try_from(idx)? * 8)));
+    }
+    let stack_size =
+      i32::try_from(align_up_u32(len_u32(&NON_VOLATILE_REGS)? * 8 + params * 8, 16)?)?;
+    self.link_function(thunk, &insts, stack_size);
+    Ok(thunk)
+  }
+  fn export_thunks(&mut self, root: LabelId) -> ErrOR<Vec<(String, LabelId)>> {
+    let mut exports = vec![];
+    for (name, u_d) in self.first_parser()?.val.exports.clone() {
+      let sig = &u_d.val.sig;
+      for param in sig.params.iter().map(|(_, param)| param).chain([&sig.ret_type]) {
+        if !matches!(param, BoolT | FloatT | IntT | NullT) {
+          return err!(u_d.pos, UnsupportedType(format!("{} in exported `{name}`", param.name())));
+        }
+      }
+      let thunk = self.export_thunk(u_d.val.dep.id, sig)?;
+      self.use_function(root, thunk);
+      exports.push((name, thunk));
+    }
+    Ok(exports)
+  }
+  pub(crate) fn obj_entry(&mut self) -> ErrOR<(LabelId, Vec<(String, LabelId)>)> {
+    const INIT: &str = "jsonpiler_init";
+    let root = self.first_parser()?.val.dep.id;
+    let init = self.export_thunk(root, &Signature { params: vec![], ret_type: NullT })?;
+    self.use_function(root, init);
+    let mut exports = self.export_thunks(root)?;
+    if exports.iter().any(|(name, _)| name == INIT) {
+      return Err(Platform(format!("`{INIT}` is reserved by `--emit obj`")));
+    }
+    exports.push((INIT.to_owned(), init));
+    Ok((init, exports))
+  }
+}
@@ -151,6 +151,31 @@ impl Jsonpiler {
     let std_e = Global(self.symbols[STD_E]);
     let tmp = Local(Tmp, -0x8);
     let mut insts = vec![];
+    let mut location = vec![];
+    if !self.release {
+      let map = self.id();
+      let no_location = self.id();
+      let str_len = self.str_len(self.handlers.seh)?;
+      self.data.push(SrcMapLbl(map));
+      insts = self.seh_location(map);
+      extend!(
+        location,
+        [LogicRR(Test, Rsi, Rsi), JCc(E, no_location)],
+        self.write_err_msg(ERR_SEPARATE, tmp)?,
+        [
+          mov_q(Rcx, Rsi),
+          Call(str_len),
+          mov_q(R8, Rax),
+          mov_q(Rcx, std_e),
+          mov_q(Rdx, Rsi),
+          LeaRM(R9, tmp),
+          Clear(Rax),
+          mov_q(Args(5), Rax),
+          CallApi(self.api(KERNEL32, "WriteFile")),
+          Lbl(no_location),
+        ]
+      );
+    }
     extend!(
       insts,
       [mov_q(Rbx, Ref(Rcx))],
@@ -170,6 +195,7 @@ impl Jsonpiler {
         mov_q(Args(5), Rax),
         CallApi(self.api(KERNEL32, "WriteFile")),
       ],
+      location,
       self.write_err_msg(ERR_END, tmp)?,
       self.write_err_msg(ISSUE, tmp)?,
       [
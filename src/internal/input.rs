@@ -8,10 +8,9 @@ impl Jsonpiler {
     let read_len = Local(Tmp, -0x10);
     let handle_pipe = self.id();
     let handle_stdin = self.id();
-    let re_alloc = self.id();
-    let epilogue = self.id();
+    let read_done = self.id();
     let u16_to_8 = self.get_u16_to_8(id)?;
-    let str_len = self.str_len(id)?;
+    let copy_str = self.copy_str(id)?;
     let insts = &[
       mov_q(Rcx, Global(self.symbols[HEAP])),
       mov_d(Rdx, 8),
@@ -47,12 +46,15 @@ impl Jsonpiler {
       mov_q(Args(5), Rax),
       CallApi(self.api(KERNEL32, "ReadFile")),
       LogicRR(Test, Rax, Rax),
-      JCc(Ne, re_alloc),
+      JCc(Ne, read_done),
       CallApi(self.api(KERNEL32, "GetLastError")),
       mov_d(Rcx, 0x6d),
       LogicRR(Cmp, Rax, Rcx),
+      JCc(E, read_done),
+      mov_d(Rcx, 6),
+      LogicRR(Cmp, Rax, Rcx),
       JCc(Ne, self.handlers.win),
-      Jmp(re_alloc),
+      Jmp(read_done),
       Lbl(handle_stdin),
       mov_d(Rcx, read_len),
       ShiftR(Shl, Rcx, Shift::One),
@@ -71,20 +73,16 @@ impl Jsonpiler {
       mov_q(buffer, Rax),
       CallApiCheck(self.api(KERNEL32, "HeapFree")),
       DecMd(Global(self.symbols[LEAK_CNT])),
+      Lbl(read_done),
       mov_q(Rcx, buffer),
-      Call(str_len),
-      IncR(Rax),
-      mov_d(read_len, Rax),
-      mov_q(Rax, buffer),
-      Jmp(epilogue),
-      Lbl(re_alloc),
+      Call(copy_str),
       mov_q(Rcx, Global(self.symbols[HEAP])),
-      mov_d(Rdx, 8),
+      Clear(Rdx),
       mov_q(R8, buffer),
-      mov_d(R9, read_len),
-      IncR(R9),
-      CallApi(self.api(KERNEL32, "HeapReAlloc")),
-      Lbl(epilogue),
+      mov_q(buffer, Rax),
+      CallApiCheck(self.api(KERNEL32, "HeapFree")),
+      DecMd(Global(self.symbols[LEAK_CNT])),
+      mov_q(Rax, buffer),
     ];
     self.link_function(id, insts, SIZE);
     Ok(id)
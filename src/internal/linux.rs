@@ -0,0 +1,225 @@
+use crate::prelude::*;
+const SYS_READ: u32 = 0;
+const SYS_WRITE: u32 = 1;
+const SYS_MMAP: u32 = 9;
+const SYS_MUNMAP: u32 = 11;
+const SYS_GETPID: u32 = 39;
+const SYS_GETTID: u32 = 186;
+const SYS_EXIT_GROUP: u32 = 231;
+impl Jsonpiler {
+  fn heap_alloc_shim(&mut self) -> Vec<Inst> {
+    let fail = self.id();
+    let epilogue = self.id();
+    vec![
+      Push(Rdi),
+      Push(Rsi),
+      mov_q(Rsi, R8),
+      AddRId(Rsi, 8),
+      Clear(Rdi),
+      mov_d(Rdx, 3),
+      mov_d(R10, 0x22),
+      Clear(R8),
+      DecR(R8),
+      Clear(R9),
+      mov_d(Rax, SYS_MMAP),
+      Custom(SYSCALL),
+      mov_q(Rcx, (-4096i64).cast_unsigned()),
+      LogicRR(Cmp, Rax, Rcx),
+      JCc(A, fail),
+      mov_q(Ref(Rax), Rsi),
+      AddRId(Rax, 8),
+      Jmp(epilogue),
+      Lbl(fail),
+      Clear(Rax),
+      Lbl(epilogue),
+      Pop(Rsi),
+      Pop(Rdi),
+      Custom(RET),
+    ]
+  }
+  fn heap_free_shim(&mut self) -> Vec<Inst> {
+    let null = self.id();
+    vec![
+      mov_d(Rax, 1),
+      LogicRR(Test, R8, R8),
+      JCc(E, null),
+      Push(Rdi),
+      Push(Rsi),
+      mov_q(Rdi, R8),
+      SubRId(Rdi, 8),
+      mov_q(Rsi, Ref(Rdi)),
+      mov_d(Rax, SYS_MUNMAP),
+      Custom(SYSCALL),
+      mov_q(Rcx, Rax),
+      Clear(Rax),
+      LogicRR(Test, Rcx, Rcx),
+      SetCc(Rax, E),
+      Pop(Rsi),
+      Pop(Rdi),
+      Lbl(null),
+      Custom(RET),
+    ]
+  }
+  fn heap_re_alloc_shim(&mut self, heap_alloc: LabelId, heap_free: LabelId) -> Vec<Inst> {
+    let grow = self.id();
+    let epilogue = self.id();
+    vec![
+      mov_q(R10, R8),
+      SubRId(R10, 8),
+      mov_q(R10, Ref(R10)),
+      SubRId(R10, 8),
+      mov_q(Rax, R8),
+      LogicRR(Cmp, R9, R10),
+      JCc(A, grow),
+      Custom(RET),
+      Lbl(grow),
+      Push(Rdi),
+      Push(Rsi),
+      Push(Rbx),
+      mov_q(Rbx, R8),
+      mov_q(R8, R9),
+      Call(heap_alloc),
+      LogicRR(Test, Rax, Rax),
+      JCc(E, epilogue),
+      mov_q(Rcx, Rbx),
+      SubRId(Rcx, 8),
+      mov_q(Rcx, Ref(Rcx)),
+      SubRId(Rcx, 8),
+      mov_q(Rdi, Rax),
+      mov_q(Rsi, Rbx),
+      Push(Rax),
+      Custom(CLD_REP_MOVSB),
+      mov_q(R8, Rbx),
+      Call(heap_free),
+      Pop(Rax),
+      Lbl(epilogue),
+      Pop(Rbx),
+      Pop(Rsi),
+      Pop(Rdi),
+      Custom(RET),
+    ]
+  }
+  fn io_shim(&mut self, syscall: u32) -> Vec<Inst> {
+    let last_error = Global(self.symbols[LAST_ERROR]);
+    let fail = self.id();
+    let skip = self.id();
+    let epilogue = self.id();
+    vec![
+      Push(Rdi),
+      Push(Rsi),
+      mov_q(Rdi, Rcx),
+      mov_q(Rsi, Rdx),
+      mov_d(Rdx, R8),
+      mov_d(Rax, syscall),
+      Custom(SYSCALL),
+      LogicRR(Test, Rax, Rax),
+      JCc(L, fail),
+      LogicRR(Test, R9, R9),
+      JCc(E, skip),
+      mov_d(Ref(R9), Rax),
+      Lbl(skip),
+      mov_d(Rax, 1),
+      Jmp(epilogue),
+      Lbl(fail),
+      UnaryR(Neg, Rax),
+      mov_d(last_error, Rax),
+      Clear(Rax),
+      Lbl(epilogue),
+      Pop(Rsi),
+      Pop(Rdi),
+      Custom(RET),
+    ]
+  }
+  pub(crate) fn link_linux_runtime(&mut self, insts: &mut Vec<Vec<Inst>>) -> ErrOR<LabelId> {
+    let mut used = BTreeMap::new();
+    for inst in insts.iter().flatten() {
+      if let CallApi(api) | CallApiCheck(api) = inst {
+        used.insert(*api, self.dlls[api.0 as usize].1[api.1 as usize].clone());
+      }
+    }
+    let mut names = used.values().cloned().collect::<BTreeSet<String>>();
+    if names.contains("HeapReAlloc") {
+      names.extend(["HeapAlloc".to_owned(), "HeapFree".to_owned()]);
+    }
+    if !self.symbols.contains_key(LAST_ERROR) {
+      self.bss_symbol(LAST_ERROR, 4);
+    }
+    let mut shims = BTreeMap::new();
+    for name in names {
+      let id = self.id();
+      shims.insert(name, id);
+    }
+    for (name, &id) in &shims {
+      let mut shim = vec![Lbl(id)];
+      shim.extend(self.linux_shim(name, &shims)?);
+      insts.push(shim);
+    }
+    for body in insts.iter_mut() {
+      let mut lowered = Vec::with_capacity(body.len());
+      for inst in body.iter() {
+        if let CallApi(api) = inst {
+          lowered.push(Call(shims[&used[api]]));
+        } else if let CallApiCheck(api) = inst {
+          lowered.extend_from_slice(&[
+            Call(shims[&used[api]]),
+            LogicRR(Test, Rax, Rax),
+            JCc(E, self.handlers.win),
+          ]);
+        } else {
+          lowered.push(*inst);
+        }
+      }
+      *body = lowered;
+    }
+    let entry = self.id();
+    insts.push(vec![Lbl(entry), SubRId(Rsp, 8), Jmp(self.first_parser()?.val.dep.id)]);
+    Ok(entry)
+  }
+  fn linux_shim(&mut self, name: &str, shims: &BTreeMap<String, LabelId>) -> ErrOR<Vec<Inst>> {
+    let last_error = Global(self.symbols[LAST_ERROR]);
+    Ok(match name {
+      "ExitProcess" => vec![mov_d(Rdi, Rcx), mov_d(Rax, SYS_EXIT_GROUP), Custom(SYSCALL)],
+      "GetCurrentProcessId" => vec![mov_d(Rax, SYS_GETPID), Custom(SYSCALL), Custom(RET)],
+      "GetCurrentThreadId" => vec![mov_d(Rax, SYS_GETTID), Custom(SYSCALL), Custom(RET)],
+      "GetLastError" => vec![mov_d(Rax, last_error), Custom(RET)],
+      "GetStdHandle" => vec![mov_d(Rax, (-10i32).cast_unsigned()), SubRR(Rax, Rcx), Custom(RET)],
+      "HeapAlloc" => self.heap_alloc_shim(),
+      "HeapFree" => self.heap_free_shim(),
+      "HeapReAlloc" => self.heap_re_alloc_shim(shims["HeapAlloc"], shims["HeapFree"]),
+      "QueryPerformanceCounter" => vec![
+        Custom(RDTSC),
+        ShiftR(Shl, Rdx, Shift::Ib(32)),
+        LogicRR(Or, Rax, Rdx),
+        mov_q(Ref(Rcx), Rax),
+        mov_d(Rax, 1),
+        Custom(RET),
+      ],
+      "ReadFile" => self.io_shim(SYS_READ),
+      "WriteFile" => self.io_shim(SYS_WRITE),
+      "EnterCriticalSection"
+      | "GetProcessHeap"
+      | "InitializeCriticalSection"
+      | "LeaveCriticalSection"
+      | "LocalFree"
+      | "SetConsoleCP"
+      | "SetConsoleCtrlHandler"
+      | "SetConsoleOutputCP" => vec![mov_d(Rax, 1), Custom(RET)],
+      "FormatMessageW" | "MultiByteToWideChar" | "ReadConsoleW" | "WideCharToMultiByte" => {
+        vec![mov_d(last_error, 1), Clear(Rax), Custom(RET)]
+      }
+      _ => {
+        return Err(Compilation(
+          UnsupportedOnTarget(name.to_owned(), Target::Linux.name()),
+          vec![],
+        ));
+      }
+    })
+  }
+  pub(crate) fn require_windows(&self, func: &Pos<BuiltIn>) -> ErrOR<()> {
+    if self.target == Target::Windows {
+      Ok(())
+    } else {
+      err!(func.pos, UnsupportedOnTarget(func.val.name.clone(), self.target.name()))
+    }
+  }
+}
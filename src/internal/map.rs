@@ -0,0 +1,496 @@
+use crate::prelude::*;
+impl Jsonpiler {
+  pub(crate) fn map_copy(&mut self, caller: LabelId) -> ErrOR<LabelId> {
+    const SIZE: i32 = 0x40;
+    let id = symbol!(self, caller, MAP_COPY);
+    let str_retain = self.str_retain(id)?;
+    let elem_copy = self.elem_copy(id)?;
+    let next = self.id();
+    let epilogue = self.id();
+    let tmp_b = Local(Tmp, -0x08);
+    let tmp_s = Local(Tmp, -0x10);
+    let tmp_d = Local(Tmp, -0x18);
+    let tmp_12 = Local(Tmp, -0x20);
+    let insts = &[
+      mov_q(tmp_b, Rbx),
+      mov_q(tmp_s, Rsi),
+      mov_q(tmp_d, Rdi),
+      mov_q(tmp_12, R12),
+      mov_q(Rbx, Rcx),
+      mov_q(R12, Rdx),
+      mov_q(Rax, Rbx),
+      AddRId(Rax, 8),
+      mov_q(Rdi, Ref(Rax)),
+      mov_q(R8, Rdi),
+      ShiftR(Shl, R8, Shift::Ib(4)),
+      AddRId(R8, MAP_HEADER as u32),
+      mov_q(Rcx, Global(self.symbols[HEAP])),
+      mov_d(Rdx, 8),
+      CallApi(self.api(KERNEL32, "HeapAlloc")),
+      IncMd(Global(self.symbols[LEAK_CNT])),
+      mov_q(Rsi, Rax),
+      mov_q(Rax, Ref(Rbx)),
+      mov_q(Ref(Rsi), Rax),
+      mov_q(Rax, Rsi),
+      AddRId(Rax, 8),
+      mov_q(Ref(Rax), Rdi),
+      Lbl(next),
+      LogicRR(Test, Rdi, Rdi),
+      JCc(E, epilogue),
+      DecR(Rdi),
+      mov_q(Rax, Rdi),
+      AddRR(Rax, Rax),
+      mov_q(Rcx, map_key(Rbx, Rax)),
+      LogicRR(Test, Rcx, Rcx),
+      JCc(E, next),
+      Call(str_retain),
+      mov_q(Rdx, Rdi),
+      AddRR(Rdx, Rdx),
+      mov_q(map_key(Rsi, Rdx), Rax),
+      mov_q(Rcx, map_value(Rbx, Rdx)),
+      mov_q(Rdx, R12),
+      Call(elem_copy),
+      mov_q(Rdx, Rdi),
+      AddRR(Rdx, Rdx),
+      mov_q(map_value(Rsi, Rdx), Rax),
+      Jmp(next),
+      Lbl(epilogue),
+      mov_q(Rax, Rsi),
+      mov_q(Rbx, tmp_b),
+      mov_q(Rsi, tmp_s),
+      mov_q(Rdi, tmp_d),
+      mov_q(R12, tmp_12),
+    ];
+    self.link_function(id, insts, SIZE);
+    Ok(id)
+  }
+  pub(crate) fn map_drop(&mut self, caller: LabelId) -> ErrOR<LabelId> {
+    const SIZE: i32 = 0x40;
+    let id = symbol!(self, caller, MAP_DROP);
+    let elem_drop = self.elem_drop(id)?;
+    let str_release = self.str_release(id)?;
+    let next = self.id();
+    let release = self.id();
+    let heap = Global(self.symbols[HEAP]);
+    let leak = Global(self.symbols[LEAK_CNT]);
+    let heap_free = self.api(KERNEL32, "HeapFree");
+    let tmp_b = Local(Tmp, -0x08);
+    let tmp_s = Local(Tmp, -0x10);
+    let tmp_d = Local(Tmp, -0x18);
+    let insts = &[
+      mov_q(tmp_b, Rbx),
+      mov_q(tmp_s, Rsi),
+      mov_q(tmp_d, Rdi),
+      mov_q(Rbx, Rcx),
+      mov_q(Rsi, Rdx),
+      mov_q(Rax, Rbx),
+      AddRId(Rax, 8),
+      mov_q(Rdi, Ref(Rax)),
+      Lbl(next),
+      LogicRR(Test, Rdi, Rdi),
+      JCc(E, release),
+      DecR(Rdi),
+      mov_q(Rax, Rdi),
+      AddRR(Rax, Rax),
+      mov_q(Rcx, map_key(Rbx, Rax)),
+      LogicRR(Test, Rcx, Rcx),
+      JCc(E, next),
+      Call(str_release),
+      mov_q(Rax, Rdi),
+      AddRR(Rax, Rax),
+      mov_q(Rcx, map_value(Rbx, Rax)),
+      mov_q(Rdx, Rsi),
+      Call(elem_drop),
+      Jmp(next),
+      Lbl(release),
+      mov_q(R8, Rbx),
+      mov_q(Rcx, heap),
+      Clear(Rdx),
+      CallApiCheck(heap_free),
+      DecMd(leak),
+      mov_q(Rbx, tmp_b),
+      mov_q(Rsi, tmp_s),
+      mov_q(Rdi, tmp_d),
+    ];
+    self.link_function(id, insts, SIZE);
+    Ok(id)
+  }
+  pub(crate) fn map_find(&mut self, caller: LabelId) -> ErrOR<LabelId> {
+    const SIZE: i32 = 0x40;
+    let id = symbol!(self, caller, MAP_FIND);
+    let str_hash = self.str_hash(id)?;
+    let str_eq = self.str_eq(id)?;
+    let probe = self.id();
+    let absent = self.id();
+    let epilogue = self.id();
+    let tmp_b = Local(Tmp, -0x08);
+    let tmp_s = Local(Tmp, -0x10);
+    let tmp_d = Local(Tmp, -0x18);
+    let tmp_12 = Local(Tmp, -0x20);
+    let insts = &[
+      mov_q(tmp_b, Rbx),
+      mov_q(tmp_s, Rsi),
+      mov_q(tmp_d, Rdi),
+      mov_q(tmp_12, R12),
+      mov_q(Rbx, Rcx),
+      mov_q(Rsi, Rdx),
+      mov_q(Rcx, Rsi),
+      Call(str_hash),
+      mov_q(Rdi, Rbx),
+      AddRId(Rdi, 8),
+      mov_q(Rdi, Ref(Rdi)),
+      DecR(Rdi),
+      LogicRR(And, Rax, Rdi),
+      mov_q(R12, Rax),
+      Lbl(probe),
+      mov_q(Rax, R12),
+      AddRR(Rax, Rax),
+      mov_q(Rcx, map_key(Rbx, Rax)),
+      LogicRR(Test, Rcx, Rcx),
+      JCc(E, absent),
+      mov_q(Rdx, Rsi),
+      Call(str_eq),
+      LogicRbRb(Test, Rax, Rax),
+      JCc(Ne, epilogue),
+      IncR(R12),
+      LogicRR(And, R12, Rdi),
+      Jmp(probe),
+      Lbl(absent),
+      Clear(Rax),
+      Lbl(epilogue),
+      mov_q(Rdx, R12),
+      AddRR(Rdx, Rdx),
+      mov_q(Rbx, tmp_b),
+      mov_q(Rsi, tmp_s),
+      mov_q(Rdi, tmp_d),
+      mov_q(R12, tmp_12),
+    ];
+    self.link_function(id, insts, SIZE);
+    Ok(id)
+  }
+  fn map_grow(&mut self, caller: LabelId) -> ErrOR<LabelId> {
+    const SIZE: i32 = 0x40;
+    let id = symbol!(self, caller, MAP_GROW);
+    let str_hash = self.str_hash(id)?;
+    let next = self.id();
+    let probe = self.id();
+    let place = self.id();
+    let release = self.id();
+    let heap = Global(self.symbols[HEAP]);
+    let tmp_b = Local(Tmp, -0x08);
+    let tmp_s = Local(Tmp, -0x10);
+    let tmp_d = Local(Tmp, -0x18);
+    let tmp_12 = Local(Tmp, -0x20);
+    let insts = &[
+      mov_q(tmp_b, Rbx),
+      mov_q(tmp_s, Rsi),
+      mov_q(tmp_d, Rdi),
+      mov_q(tmp_12, R12),
+      mov_q(Rbx, Rcx),
+      mov_q(Rax, Rbx),
+      AddRId(Rax, 8),
+      mov_q(Rdi, Ref(Rax)),
+      mov_q(R8, Rdi),
+      ShiftR(Shl, R8, Shift::Ib(5)),
+      AddRId(R8, MAP_HEADER as u32),
+      mov_q(Rcx, heap),
+      mov_d(Rdx, 8),
+      CallApi(self.api(KERNEL32, "HeapAlloc")),
+      IncMd(Global(self.symbols[LEAK_CNT])),
+      mov_q(Rsi, Rax),
+      mov_q(Rax, Ref(Rbx)),
+      mov_q(Ref(Rsi), Rax),
+      mov_q(R12, Rdi),
+      AddRR(R12, R12),
+      mov_q(Rax, Rsi),
+      AddRId(Rax, 8),
+      mov_q(Ref(Rax), R12),
+      DecR(R12),
+      Lbl(next),
+      LogicRR(Test, Rdi, Rdi),
+      JCc(E, release),
+      DecR(Rdi),
+      mov_q(Rax, Rdi),
+      AddRR(Rax, Rax),
+      mov_q(Rcx, map_key(Rbx, Rax)),
+      LogicRR(Test, Rcx, Rcx),
+      JCc(E, next),
+      Call(str_hash),
+      LogicRR(And, Rax, R12),
+      Lbl(probe),
+      mov_q(R8, Rax),
+      AddRR(R8, R8),
+      mov_q(Rcx, map_key(Rsi, R8)),
+      LogicRR(Test, Rcx, Rcx),
+      JCc(E, place),
+      IncR(Rax),
+      LogicRR(And, Rax, R12),
+      Jmp(probe),
+      Lbl(place),
+      mov_q(Rax, Rdi),
+      AddRR(Rax, Rax),
+      mov_q(Rcx, map_key(Rbx, Rax)),
+      mov_q(map_key(Rsi, R8), Rcx),
+      mov_q(Rcx, map_value(Rbx, Rax)),
+      mov_q(map_value(Rsi, R8), Rcx),
+      Jmp(next),
+      Lbl(release),
+      mov_q(R8, Rbx),
+      mov_q(Rcx, heap),
+      Clear(Rdx),
+      CallApiCheck(self.api(KERNEL32, "HeapFree")),
+      DecMd(Global(self.symbols[LEAK_CNT])),
+      mov_q(Rax, Rsi),
+      mov_q(Rbx, tmp_b),
+      mov_q(Rsi, tmp_s),
+      mov_q(Rdi, tmp_d),
+      mov_q(R12, tmp_12),
+    ];
+    self.link_function(id, insts, SIZE);
+    Ok(id)
+  }
+  pub(crate) fn map_insert(&mut self, caller: LabelId) -> ErrOR<LabelId> {
+    const SIZE: i32 = 0x50;
+    let id = symbol!(self, caller, MAP_INSERT);
+    let map_find = self.map_find(id)?;
+    let map_grow = self.map_grow(id)?;
+    let elem_drop = self.elem_drop(id)?;
+    let str_release = self.str_release(id)?;
+    let absent = self.id();
+    let insert = self.id();
+    let store = self.id();
+    let tmp_b = Local(Tmp, -0x08);
+    let tmp_s = Local(Tmp, -0x10);
+    let tmp_d = Local(Tmp, -0x18);
+    let tmp_12 = Local(Tmp, -0x20);
+    let tmp_index = Local(Tmp, -0x28);
+    let insts = &[
+      mov_q(tmp_b, Rbx),
+      mov_q(tmp_s, Rsi),
+      mov_q(tmp_d, Rdi),
+      mov_q(tmp_12, R12),
+      mov_q(Rbx, Rcx),
+      mov_q(Rsi, Rdx),
+      mov_q(Rdi, R8),
+      mov_q(R12, R9),
+      Call(map_find),
+      LogicRbRb(Test, Rax, Rax),
+      JCc(E, absent),
+      mov_q(tmp_index, Rdx),
+      mov_q(Rcx, map_value(Rbx, Rdx)),
+      mov_q(Rdx, R12),
+      Call(elem_drop),
+      mov_q(Rcx, Rsi),
+      Call(str_release),
+      mov_q(Rdx, tmp_index),
+      Jmp(store),
+      Lbl(absent),
+      // Grow once the map would be more than three quarters full.
+      mov_q(Rax, Ref(Rbx)),
+      IncR(Rax),
+      ShiftR(Shl, Rax, Shift::Ib(2)),
+      mov_q(Rcx, Rbx),
+      AddRId(Rcx, 8),
+      mov_q(Rcx, Ref(Rcx)),
+      mov_q(R8, Rcx),
+      AddRR(R8, R8),
+      AddRR(R8, Rcx),
+      LogicRR(Cmp, Rax, R8),
+      JCc(Be, insert),
+      mov_q(Rcx, Rbx),
+      Call(map_grow),
+      mov_q(Rbx, Rax),
+      mov_q(Rcx, Rbx),
+      mov_q(Rdx, Rsi),
+      Call(map_find),
+      Lbl(insert),
+      mov_q(map_key(Rbx, Rdx), Rsi),
+      mov_q(Rax, Ref(Rbx)),
+      IncR(Rax),
+      mov_q(Ref(Rbx), Rax),
+      Lbl(store),
+      mov_q(map_value(Rbx, Rdx), Rdi),
+      mov_q(Rax, Rbx),
+      mov_q(Rbx, tmp_b),
+      mov_q(Rsi, tmp_s),
+      mov_q(Rdi, tmp_d),
+      mov_q(R12, tmp_12),
+    ];
+    self.link_function(id, insts, SIZE);
+    Ok(id)
+  }
+  pub(crate) fn map_keys(&mut self, caller: LabelId) -> ErrOR<LabelId> {
+    const SIZE: i32 = 0x40;
+    let id = symbol!(self, caller, MAP_KEYS);
+    let str_retain = self.str_retain(id)?;
+    let next = self.id();
+    let epilogue = self.id();
+    let tmp_b = Local(Tmp, -0x08);
+    let tmp_s = Local(Tmp, -0x10);
+    let tmp_d = Local(Tmp, -0x18);
+    let tmp_12 = Local(Tmp, -0x20);
+    let insts = &[
+      mov_q(tmp_b, Rbx),
+      mov_q(tmp_s, Rsi),
+      mov_q(tmp_d, Rdi),
+      mov_q(tmp_12, R12),
+      mov_q(Rbx, Rcx),
+      mov_q(R8, Ref(Rbx)),
+      ShiftR(Shl, R8, Shift::Ib(3)),
+      AddRId(R8, ARRAY_HEADER as u32),
+      mov_q(Rcx, Global(self.symbols[HEAP])),
+      mov_d(Rdx, 8),
+      CallApi(self.api(KERNEL32, "HeapAlloc")),
+      IncMd(Global(self.symbols[LEAK_CNT])),
+      mov_q(Rsi, Rax),
+      mov_q(Rcx, Ref(Rbx)),
+      mov_q(Ref(Rsi), Rcx),
+      AddRId(Rax, 8),
+      mov_q(Ref(Rax), Rcx),
+      Clear(R12),
+      mov_q(Rax, Rbx),
+      AddRId(Rax, 8),
+      mov_q(Rdi, Ref(Rax)),
+      Lbl(next),
+      LogicRR(Test, Rdi, Rdi),
+      JCc(E, epilogue),
+      DecR(Rdi),
+      mov_q(Rax, Rdi),
+      AddRR(Rax, Rax),
+      mov_q(Rcx, map_key(Rbx, Rax)),
+      LogicRR(Test, Rcx, Rcx),
+      JCc(E, next),
+      Call(str_retain),
+      mov_q(array_elem(Rsi, R12), Rax),
+      IncR(R12),
+      Jmp(next),
+      Lbl(epilogue),
+      mov_q(Rax, Rsi),
+      mov_q(Rbx, tmp_b),
+      mov_q(Rsi, tmp_s),
+      mov_q(Rdi, tmp_d),
+      mov_q(R12, tmp_12),
+    ];
+    self.link_function(id, insts, SIZE);
+    Ok(id)
+  }
+  pub(crate) fn map_remove(&mut self, caller: LabelId) -> ErrOR<LabelId> {
+    const SIZE: i32 = 0x50;
+    let id = symbol!(self, caller, MAP_REMOVE);
+    let map_find = self.map_find(id)?;
+    let elem_drop = self.elem_drop(id)?;
+    let str_hash = self.str_hash(id)?;
+    let str_release = self.str_release(id)?;
+    let shift = self.id();
+    let clear = self.id();
+    let epilogue = self.id();
+    let tmp_b = Local(Tmp, -0x08);
+    let tmp_s = Local(Tmp, -0x10);
+    let tmp_d = Local(Tmp, -0x18);
+    let tmp_12 = Local(Tmp, -0x20);
+    let shape = Local(Tmp, -0x28);
+    let insts = &[
+      mov_q(tmp_b, Rbx),
+      mov_q(tmp_s, Rsi),
+      mov_q(tmp_d, Rdi),
+      mov_q(tmp_12, R12),
+      mov_q(Rbx, Rcx),
+      mov_q(shape, R8),
+      Call(map_find),
+      LogicRbRb(Test, Rax, Rax),
+      JCc(E, epilogue),
+      mov_q(Rsi, Rdx),
+      ShiftR(Shr, Rsi, Shift::One),
+      mov_q(Rcx, map_key(Rbx, Rdx)),
+      Call(str_release),
+      mov_q(Rax, Rsi),
+      AddRR(Rax, Rax),
+      mov_q(Rcx, map_value(Rbx, Rax)),
+      mov_q(Rdx, shape),
+      Call(elem_drop),
+      mov_q(Rax, Ref(Rbx)),
+      DecR(Rax),
+      mov_q(Ref(Rbx), Rax),
+      mov_q(R12, Rbx),
+      AddRId(R12, 8),
+      mov_q(R12, Ref(R12)),
+      DecR(R12),
+      mov_q(Rdi, Rsi),
+      Lbl(shift),
+      IncR(Rdi),
+      LogicRR(And, Rdi, R12),
+      mov_q(Rax, Rdi),
+      AddRR(Rax, Rax),
+      mov_q(Rcx, map_key(Rbx, Rax)),
+      LogicRR(Test, Rcx, Rcx),
+      JCc(E, clear),
+      Call(str_hash),
+      LogicRR(And, Rax, R12),
+      // The entry moves into the hole when the hole lies between its home slot and its slot.
+      mov_q(Rcx, Rsi),
+      SubRR(Rcx, Rax),
+      LogicRR(And, Rcx, R12),
+      mov_q(Rdx, Rdi),
+      SubRR(Rdx, Rax),
+      LogicRR(And, Rdx, R12),
+      LogicRR(Cmp, Rcx, Rdx),
+      JCc(Ae, shift),
+      mov_q(Rax, Rdi),
+      AddRR(Rax, Rax),
+      mov_q(Rcx, Rsi),
+      AddRR(Rcx, Rcx),
+      mov_q(Rdx, map_key(Rbx, Rax)),
+      mov_q(map_key(Rbx, Rcx), Rdx),
+      mov_q(Rdx, map_value(Rbx, Rax)),
+      mov_q(map_value(Rbx, Rcx), Rdx),
+      mov_q(Rsi, Rdi),
+      Jmp(shift),
+      Lbl(clear),
+      mov_q(Rax, Rsi),
+      AddRR(Rax, Rax),
+      Clear(Rcx),
+      mov_q(map_key(Rbx, Rax), Rcx),
+      mov_q(map_value(Rbx, Rax), Rcx),
+      Clear(Rax),
+      mov_b(Rax, 0xFF),
+      Lbl(epilogue),
+      mov_q(Rbx, tmp_b),
+      mov_q(Rsi, tmp_s),
+      mov_q(Rdi, tmp_d),
+      mov_q(R12, tmp_12),
+    ];
+    self.link_function(id, insts, SIZE);
+    Ok(id)
+  }
+  pub(crate) fn str_hash(&mut self, caller: LabelId) -> ErrOR<LabelId> {
+    const SIZE: i32 = 0x20;
+    let id = symbol!(self, caller, STR_HASH);
+    let next = self.id();
+    let epilogue = self.id();
+    self.link_function(
+      id,
+      &[
+        mov_q(Rax, FNV_OFFSET),
+        mov_q(R9, FNV_PRIME),
+        Lbl(next),
+        Clear(Rdx),
+        mov_b(Rdx, Ref(Rcx)),
+        LogicRbRb(Test, Rdx, Rdx),
+        JCc(E, epilogue),
+        LogicRR(Xor, Rax, Rdx),
+        IMulRR(Rax, R9),
+        IncR(Rcx),
+        Jmp(next),
+        Lbl(epilogue),
+      ],
+      SIZE,
+    );
+    Ok(id)
+  }
+}
+pub(crate) fn map_key(base: Register, index: Register) -> Operand<u64> {
+  SibDisp(Sib { base, index, scale: S8 }, Disp::Byte(MAP_HEADER))
+}
+pub(crate) fn map_value(base: Register, index: Register) -> Operand<u64> {
+  SibDisp(Sib { base, index, scale: S8 }, Disp::Byte(MAP_HEADER + 8))
+}
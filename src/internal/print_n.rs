@@ -60,7 +60,10 @@ impl Jsonpiler {
     let str_len = self.str_len(id)?;
     let std_n_and_tmp = Local(Tmp, -0x08);
     let string = Local(Tmp, -0x10);
+    let no_console = self.id();
     let insts = &[
+      LogicRR(Test, Rdx, Rdx),
+      JCc(E, no_console),
       mov_q(string, Rcx),
       mov_q(std_n_and_tmp, Rdx),
       Call(str_len),
@@ -71,6 +74,7 @@ impl Jsonpiler {
       Clear(Rax),
       mov_q(Args(5), Rax),
       CallApiCheck(self.api(KERNEL32, "WriteFile")),
+      Lbl(no_console),
     ];
     self.link_function(id, insts, SIZE);
     Ok(id)
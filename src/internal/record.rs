@@ -0,0 +1,99 @@
+use crate::prelude::*;
+impl Jsonpiler {
+  pub(crate) fn field_copy(&mut self, field_type: &JsonType, caller: LabelId) -> ErrOR<Vec<Inst>> {
+    Ok(match field_type {
+      StrT => vec![Call(self.str_retain(caller)?)],
+      ArrayT(Some(elem)) => {
+        vec![mov_q(Rdx, elem.shape(Position::default())?), Call(self.array_copy(caller)?)]
+      }
+      MapT(value) => {
+        vec![mov_q(Rdx, value.shape(Position::default())?), Call(self.map_copy(caller)?)]
+      }
+      StructT(_, inner) => vec![Call(self.record_copy(*inner, caller)?)],
+      FuncT(_) => vec![Call(self.closure_retain(caller)?)],
+      ArrayT(None) | BoolT | CustomT(_) | FloatT | IntT | NullT | ObjectT => vec![mov_q(Rax, Rcx)],
+    })
+  }
+  pub(crate) fn field_drop(&mut self, field_type: &JsonType, caller: LabelId) -> ErrOR<Vec<Inst>> {
+    Ok(match field_type {
+      StrT => vec![Call(self.str_release(caller)?)],
+      ArrayT(Some(elem)) => {
+        vec![mov_q(Rdx, elem.shape(Position::default())?), Call(self.array_drop(caller)?)]
+      }
+      MapT(value) => {
+        vec![mov_q(Rdx, value.shape(Position::default())?), Call(self.map_drop(caller)?)]
+      }
+      StructT(_, inner) => vec![Call(self.record_drop(*inner, caller)?)],
+      FuncT(_) => vec![Call(self.closure_release(caller)?)],
+      ArrayT(None) | BoolT | CustomT(_) | FloatT | IntT | NullT | ObjectT => vec![],
+    })
+  }
+  pub(crate) fn record_copy(&mut self, id: u32, caller: LabelId) -> ErrOR<LabelId> {
+    const SIZE: i32 = 0x30;
+    if let Some(label) = self.structs[id as usize].val.copy {
+      self.use_function(caller, label);
+      return Ok(label);
+    }
+    let label = self.id();
+    self.structs[id as usize].val.copy = Some(label);
+    self.use_function(caller, label);
+    let fields = self.field_types(id);
+    let tmp_b = Local(Tmp, -0x08);
+    let tmp_s = Local(Tmp, -0x10);
+    let mut insts = vec![
+      mov_q(tmp_b, Rbx),
+      mov_q(tmp_s, Rsi),
+      mov_q(Rbx, Rcx),
+      mov_q(Rcx, Global(self.symbols[HEAP])),
+      mov_d(Rdx, 8),
+      mov_d(R8, len_u32(&fields)? * 8),
+      CallApi(self.api(KERNEL32, "HeapAlloc")),
+      IncMd(Global(self.symbols[LEAK_CNT])),
+      mov_q(Rsi, Rax),
+    ];
+    for (idx, field_type) in (0..).zip(&fields) {
+      insts.extend_from_slice(&[mov_d(Rax, idx), mov_q(Rcx, record_field(Rbx, Rax))]);
+      insts.extend(self.field_copy(field_type, label)?);
+      insts.extend_from_slice(&[mov_d(Rdx, idx), mov_q(record_field(Rsi, Rdx), Rax)]);
+    }
+    insts.extend_from_slice(&[mov_q(Rax, Rsi), mov_q(Rbx, tmp_b), mov_q(Rsi, tmp_s)]);
+    self.link_function(label, &insts, SIZE);
+    Ok(label)
+  }
+  pub(crate) fn record_drop(&mut self, id: u32, caller: LabelId) -> ErrOR<LabelId> {
+    const SIZE: i32 = 0x30;
+    if let Some(label) = self.structs[id as usize].val.drop {
+      self.use_function(caller, label);
+      return Ok(label);
+    }
+    let label = self.id();
+    self.structs[id as usize].val.drop = Some(label);
+    self.use_function(caller, label);
+    let fields = self.field_types(id);
+    let heap = Global(self.symbols[HEAP]);
+    let leak = Global(self.symbols[LEAK_CNT]);
+    let heap_free = self.api(KERNEL32, "HeapFree");
+    let tmp_b = Local(Tmp, -0x08);
+    let mut insts = vec![mov_q(tmp_b, Rbx), mov_q(Rbx, Rcx)];
+    for (idx, field_type) in (0..).zip(&fields) {
+      let drop = self.field_drop(field_type, label)?;
+      if !drop.is_empty() {
+        insts.extend_from_slice(&[mov_d(Rax, idx), mov_q(Rcx, record_field(Rbx, Rax))]);
+        insts.extend(drop);
+      }
+    }
+    insts.extend_from_slice(&[
+      mov_q(R8, Rbx),
+      mov_q(Rcx, heap),
+      Clear(Rdx),
+      CallApiCheck(heap_free),
+      DecMd(leak),
+      mov_q(Rbx, tmp_b),
+    ]);
+    self.link_function(label, &insts, SIZE);
+    Ok(label)
+  }
+}
+pub(crate) fn record_field(base: Register, index: Register) -> Operand<u64> {
+  SibDisp(Sib { base, index, scale: S8 }, Disp::Zero)
+}
@@ -0,0 +1,95 @@
+use crate::prelude::*;
+impl Jsonpiler {
+  pub(crate) fn embed_locations(&mut self) -> ErrOR<()> {
+    if self.release {
+      return Ok(());
+    }
+    let locations = self.locations.clone();
+    for (id, pos) in locations {
+      let text = self.location_text(pos)?;
+      self.data.push(StrLbl(id, text));
+    }
+    Ok(())
+  }
+  fn location_text(&self, pos: Position) -> ErrOR<String> {
+    let root_file = &self.first_parser()?.val.file;
+    let (file, l_c, ..) = self.parsers[pos.file as usize].err_info(pos, root_file);
+    Ok(format!("{file}{l_c}"))
+  }
+  pub(crate) fn seh_location(&mut self, map: LabelId) -> Vec<Inst> {
+    let next = self.id();
+    let not_found = self.id();
+    vec![
+      Clear(Rsi),
+      mov_q(Rax, Rcx),
+      AddRId(Rax, 0x10),
+      mov_q(Rax, Ref(Rax)),
+      LeaRM(R8, Global(map)),
+      mov_d(R9, Ref(R8)),
+      mov_q(R10, R8),
+      SubRR(R10, R9),
+      SubRR(Rax, R10),
+      AddRId(R8, 4),
+      mov_d(R9, Ref(R8)),
+      Lbl(next),
+      LogicRR(Test, R9, R9),
+      JCc(E, not_found),
+      DecR(R9),
+      AddRId(R8, 4),
+      mov_d(R11, Ref(R8)),
+      AddRId(R8, 4),
+      mov_d(Rdx, Ref(R8)),
+      AddRId(R8, 4),
+      LogicRR(Cmp, Rax, R11),
+      JCc(B, next),
+      LogicRR(Cmp, Rax, Rdx),
+      JCc(Ae, next),
+      mov_d(Rsi, Ref(R8)),
+      AddRR(Rsi, R10),
+      Lbl(not_found),
+    ]
+  }
+  pub(crate) fn source_loc(&mut self, pos: Position) -> LabelId {
+    let id = self.id();
+    self.locations.insert(id, pos);
+    id
+  }
+  pub(crate) fn write_pdb_file(&self, image: &ImageInfo, path: &str) -> ErrOR<()> {
+    let names = self.label_names();
+    let files = self.parsers.iter().map(|parser| parser.val.file.clone()).collect::<Vec<_>>();
+    let mut funcs = vec![];
+    for (id, rva, size) in &image.functions {
+      let Some(name) = names.get(id) else {
+        continue;
+      };
+      let mut lines = vec![];
+      for (start, _, loc) in
+        image.ranges.iter().filter(|range| (*rva..rva + size).contains(&range.0))
+      {
+        let pos = self.locations.get(loc).ok_or(Internal(UnknownLabel))?;
+        if lines.last().is_none_or(|&(_, file, line)| (file, line) != (pos.file, pos.line + 1)) {
+          lines.push((*start, pos.file, pos.line + 1));
+        }
+      }
+      if let Some(first) = lines.first_mut() {
+        first.0 = *rva;
+      }
+      funcs.push(PdbFunc { lines, name: name.clone(), rva: *rva, size: *size });
+    }
+    write_pdb(path, image.guid, &image.sections, &files, &funcs)
+  }
+  pub(crate) fn write_source_map(&self, ranges: &SourceMap, path: &Path) -> ErrOR<()> {
+    let mut entries = vec![];
+    for (start, end, loc) in ranges {
+      let pos = self.locations.get(loc).ok_or(Internal(UnknownLabel))?;
+      entries.push(ObjectN(vec![
+        ("start".to_owned(), IntN(i64::from(*start))),
+        ("end".to_owned(), IntN(i64::from(*end))),
+        ("location".to_owned(), StrN(self.location_text(*pos)?)),
+      ]));
+    }
+    let map = ObjectN(vec![("ranges".to_owned(), ArrayN(entries))]);
+    fs::write(path, format!("{map}\n"))?;
+    Ok(())
+  }
+}
@@ -3,7 +3,7 @@ impl Jsonpiler {
   pub(crate) fn copy_str(&mut self, caller: LabelId) -> ErrOR<LabelId> {
     const SIZE: i32 = 0x50;
     let id = symbol!(self, caller, COPY2HEAP);
-    let heap = Global(self.symbols[HEAP]);
+    let str_alloc = self.str_alloc(id)?;
     let tmp_d = Local(Tmp, -0x08);
     let tmp_s = Local(Tmp, -0x10);
     let tmp_b = Local(Tmp, -0x18);
@@ -21,11 +21,8 @@ impl Jsonpiler {
       Custom(CLD_REPNE_SCASB),
       SubRR(Rdi, R12),
       mov_q(Rbx, Rdi),
-      mov_q(Rcx, heap),
-      mov_d(Rdx, 8),
-      mov_q(R8, Rbx),
-      CallApi(self.api(KERNEL32, "HeapAlloc")),
-      IncMd(Global(self.symbols[LEAK_CNT])),
+      mov_q(Rcx, Rbx),
+      Call(str_alloc),
       mov_q(Rcx, Rbx),
       mov_q(Rdi, Rax),
       mov_q(Rsi, R12),
@@ -55,6 +52,7 @@ impl Jsonpiler {
     let is_neg = Local(Tmp, -0x1D);
     let i64_min_str = Global(self.global_str(i64::MIN.to_string()));
     let copy_str = self.copy_str(id)?;
+    let str_alloc = self.str_alloc(id)?;
     let insts = &[
       mov_q(tmp_s, Rsi),
       mov_q(tmp_b, Rbx),
@@ -88,11 +86,8 @@ impl Jsonpiler {
       LogicRR(Test, Rax, Rax),
       JCc(Ne, count_start),
       Lbl(count_end),
-      mov_q(Rcx, Global(self.symbols[HEAP])),
-      mov_d(Rdx, 8),
-      mov_d(R8, count),
-      CallApi(self.api(KERNEL32, "HeapAlloc")),
-      IncMd(Global(self.symbols[LEAK_CNT])),
+      mov_d(Rcx, count),
+      Call(str_alloc),
       mov_q(Rcx, Rax),
       mov_d(Rax, count),
       AddRR(Rcx, Rax),
@@ -143,6 +138,7 @@ impl Jsonpiler {
     let start_is_posi = self.id();
     let end_is_posi = self.id();
     let search_slice = self.id();
+    let str_alloc = self.str_alloc(id)?;
     let insts = &[
       mov_q(tmp_b, Rbx),
       mov_q(tmp_12, R12),
@@ -175,25 +171,19 @@ impl Jsonpiler {
       mov_q(Rax, slice),
       AddRR(Rax, R9),
       mov_q(slice, Rax),
-      mov_q(Rcx, Global(self.symbols[HEAP])),
-      mov_d(Rdx, 8),
-      mov_q(R8, R10),
-      SubRR(R8, R9),
-      mov_q(slice_len, R8),
-      IncR(R8),
-      CallApi(self.api(KERNEL32, "HeapAlloc")),
-      IncMd(Global(self.symbols[LEAK_CNT])),
+      mov_q(Rcx, R10),
+      SubRR(Rcx, R9),
+      mov_q(slice_len, Rcx),
+      IncR(Rcx),
+      Call(str_alloc),
       mov_q(Rcx, slice_len),
       mov_q(Rdi, Rax),
       mov_q(Rsi, slice),
       Custom(CLD_REP_MOVSB),
       Jmp(epilogue),
       Lbl(abort),
-      mov_q(Rcx, Global(self.symbols[HEAP])),
-      mov_d(Rdx, 8),
-      mov_d(R8, 1),
-      CallApi(self.api(KERNEL32, "HeapAlloc")),
-      IncMd(Global(self.symbols[LEAK_CNT])),
+      mov_d(Rcx, 1),
+      Call(str_alloc),
       Lbl(epilogue),
       mov_q(Rdi, tmp_d),
       mov_q(Rsi, tmp_s),
@@ -236,6 +226,23 @@ impl Jsonpiler {
     );
     Ok(id)
   }
+  pub(crate) fn str_alloc(&mut self, caller: LabelId) -> ErrOR<LabelId> {
+    const SIZE: i32 = 0x20;
+    let id = symbol!(self, caller, STR_ALLOC);
+    let insts = &[
+      mov_q(R8, Rcx),
+      AddRId(R8, STR_HEADER as u32),
+      mov_q(Rcx, Global(self.symbols[HEAP])),
+      mov_d(Rdx, 8),
+      CallApi(self.api(KERNEL32, "HeapAlloc")),
+      IncMd(Global(self.symbols[LEAK_CNT])),
+      mov_d(Rcx, 1),
+      mov_q(Ref(Rax), Rcx),
+      AddRId(Rax, STR_HEADER as u32),
+    ];
+    self.link_function(id, insts, SIZE);
+    Ok(id)
+  }
   pub(crate) fn str_chars_len(&mut self, caller: LabelId) -> ErrOR<LabelId> {
     const SIZE: i32 = 0x30;
     let id = symbol!(self, caller, STR_CHARS_LEN);
@@ -319,4 +326,38 @@ impl Jsonpiler {
     );
     Ok(id)
   }
+  pub(crate) fn str_release(&mut self, caller: LabelId) -> ErrOR<LabelId> {
+    const SIZE: i32 = 0x20;
+    let id = symbol!(self, caller, STR_RELEASE);
+    let epilogue = self.id();
+    let insts = &[
+      mov_q(R8, Rcx),
+      SubRId(R8, STR_HEADER as u32),
+      mov_q(Rax, Ref(R8)),
+      DecR(Rax),
+      mov_q(Ref(R8), Rax),
+      LogicRR(Test, Rax, Rax),
+      JCc(Ne, epilogue),
+      mov_q(Rcx, Global(self.symbols[HEAP])),
+      Clear(Rdx),
+      CallApiCheck(self.api(KERNEL32, "HeapFree")),
+      DecMd(Global(self.symbols[LEAK_CNT])),
+      Lbl(epilogue),
+    ];
+    self.link_function(id, insts, SIZE);
+    Ok(id)
+  }
+  pub(crate) fn str_retain(&mut self, caller: LabelId) -> ErrOR<LabelId> {
+    const SIZE: i32 = 0x20;
+    let id = symbol!(self, caller, STR_RETAIN);
+    let insts = &[
+      mov_q(Rax, Rcx),
+      SubRId(Rcx, STR_HEADER as u32),
+      mov_q(Rdx, Ref(Rcx)),
+      IncR(Rdx),
+      mov_q(Ref(Rcx), Rdx),
+    ];
+    self.link_function(id, insts, SIZE);
+    Ok(id)
+  }
 }
@@ -0,0 +1,204 @@
+mod builtin;
+use crate::prelude::*;
+use io::Write as _;
+use std::{process, rc::Rc};
+enum Flow {
+  Break,
+  Continue,
+  Exit(i32),
+  Return(Json),
+}
+struct Frame {
+  file: usize,
+  locals: Vec<BTreeMap<String, (NameKind, Json)>>,
+  loops: u32,
+  ret_type: Option<JsonType>,
+}
+impl Frame {
+  fn new(file: usize) -> Self {
+    Frame { file, locals: vec![BTreeMap::new()], loops: 0, ret_type: None }
+  }
+}
+struct Function {
+  body: Pos<Json>,
+  file: usize,
+  params: Vec<(String, JsonType)>,
+  ret_type: JsonType,
+}
+#[derive(Default)]
+struct Module {
+  exports: BTreeSet<String>,
+  functions: BTreeMap<String, Rc<Function>>,
+  globals: BTreeMap<String, (NameKind, Json)>,
+}
+struct Interpreter<'jsonpiler> {
+  flow: Option<Flow>,
+  jsonpiler: &'jsonpiler mut Jsonpiler,
+  modules: Vec<Module>,
+  seed: u64,
+}
+impl Jsonpiler {
+  pub(crate) fn interpret(&mut self, parsed: Pos<Json>) -> ErrOR<i32> {
+    let seed = u64::from(process::id()) ^ now().as_nanos() as u64;
+    let mut interpreter =
+      Interpreter { flow: None, jsonpiler: self, modules: vec![Module::default()], seed: seed | 1 };
+    let result = interpreter.eval(parsed, &mut Frame::new(0))?;
+    if let Some(Flow::Exit(code)) = interpreter.flow {
+      return Ok(code);
+    }
+    #[expect(clippy::cast_possible_truncation)]
+    Ok(if let Int(Lit(int)) = result.val { int as i32 } else { 0 })
+  }
+}
+impl Interpreter<'_> {
+  fn call(&mut self, (name, args): KeyVal, frame: &mut Frame) -> ErrOR<Json> {
+    let Some(function) = self.modules[frame.file].functions.get(&name.val).map(Rc::clone) else {
+      if let ArrayT(Some(_)) | MapT(_) = JsonType::from_string(&name.val) {
+        return err!(name.pos, UnsupportedOnTarget(name.val, "the interpreter"));
+      }
+      return err!(name.pos, UndefinedFunc(name.val));
+    };
+    let mut func = self.func_info((name, args), false, frame)?;
+    if self.flow.is_some() {
+      return Ok(Null(Lit(())));
+    }
+    func.validate_args(Exact(len_u32(&function.params)?))?;
+    let mut locals = BTreeMap::new();
+    for (param, param_type) in &function.params {
+      let arg = func.arg()?;
+      if arg.val.as_type() != *param_type {
+        return Err(func.args_err(vec![param_type.clone()], arg.map_ref(Json::as_type)));
+      }
+      locals.insert(param.clone(), (Argument, arg.val));
+    }
+    let mut callee = Frame {
+      file: function.file,
+      locals: vec![locals],
+      loops: 0,
+      ret_type: Some(function.ret_type.clone()),
+    };
+    let ret = self.eval(function.body.clone(), &mut callee)?;
+    match self.flow.take() {
+      Some(Flow::Return(value)) => Ok(value),
+      Some(flow) => {
+        self.flow = Some(flow);
+        Ok(Null(Lit(())))
+      }
+      None if ret.val.as_type() == function.ret_type => Ok(ret.val),
+      None => Err(type_err(
+        format_ret_val(&func.val.name),
+        vec![function.ret_type.clone()],
+        ret.map_ref(Json::as_type),
+      )),
+    }
+  }
+  fn check_defined(&self, name: &Pos<String>, frame: &Frame) -> ErrOR<()> {
+    let module = &self.modules[frame.file];
+    if let Some((kind, _)) =
+      frame.locals.iter().rev().chain([&module.globals]).find_map(|vars| vars.get(&name.val))
+    {
+      return err!(name.pos, DuplicateName(*kind, name.val.clone()));
+    }
+    if self.jsonpiler.builtin.contains_key(name.val.as_str()) {
+      return err!(name.pos, DuplicateName(BuiltInFunc, name.val.clone()));
+    }
+    if module.functions.contains_key(&name.val) {
+      return err!(name.pos, DuplicateName(UserDefinedFunc, name.val.clone()));
+    }
+    Ok(())
+  }
+  fn eval(&mut self, json: Pos<Json>, frame: &mut Frame) -> ErrOR<Pos<Json>> {
+    Ok(if let Array(Lit(array)) = json.val {
+      json.pos.with(Array(Lit(self.eval_args(array, frame)?)))
+    } else if let Object(Lit(object)) = json.val {
+      let mut value = Null(Lit(()));
+      for key_val in object {
+        value = self.eval_func(key_val, frame)?;
+        if self.flow.is_some() {
+          break;
+        }
+      }
+      json.pos.with(value)
+    } else {
+      json
+    })
+  }
+  fn eval_args(&mut self, mut args: Vec<Pos<Json>>, frame: &mut Frame) -> ErrOR<Vec<Pos<Json>>> {
+    for arg in &mut args {
+      *arg = self.eval(take(arg), frame)?;
+      if self.flow.is_some() {
+        break;
+      }
+    }
+    Ok(args)
+  }
+  fn eval_func(&mut self, (name, args): KeyVal, frame: &mut Frame) -> ErrOR<Json> {
+    let Some(&BuiltInInfo { arity, scoped, skip_eval, .. }) =
+      self.jsonpiler.builtin.get(name.val.as_str())
+    else {
+      return self.call((name, args), frame);
+    };
+    if scoped {
+      frame.locals.push(BTreeMap::new());
+    }
+    let mut func = self.func_info((name, args), skip_eval, frame)?;
+    let result = if self.flow.is_some() {
+      Null(Lit(()))
+    } else {
+      func.validate_args(arity)?;
+      self.builtin(&mut func, frame)?
+    };
+    if scoped {
+      frame.locals.pop();
+    }
+    Ok(result)
+  }
+  fn eval_with_scope(&mut self, expr: Pos<Json>, frame: &mut Frame) -> ErrOR<Pos<Json>> {
+    frame.locals.push(BTreeMap::new());
+    let value = self.eval(expr, frame)?;
+    frame.locals.pop();
+    Ok(value)
+  }
+  fn func_info(
+    &mut self,
+    (name, val): KeyVal,
+    skip_eval: bool,
+    frame: &mut Frame,
+  ) -> ErrOR<Pos<BuiltIn>> {
+    let args_vec = if let Array(Lit(args)) = val.val { args } else { vec![val] };
+    let args = if skip_eval { args_vec } else { self.eval_args(args_vec, frame)? };
+    Ok(name.pos.with(BuiltIn {
+      len: len_u32(&args)?,
+      name: name.val,
+      args: args.into_iter(),
+      free_list: BTreeSet::new(),
+      nth: 0,
+    }))
+  }
+  fn get_var<'var>(
+    &'var mut self,
+    name: &Pos<String>,
+    frame: &'var mut Frame,
+  ) -> ErrOR<&'var mut (NameKind, Json)> {
+    let module = &mut self.modules[frame.file];
+    frame
+      .locals
+      .iter_mut()
+      .rev()
+      .chain([&mut module.globals])
+      .find_map(|vars| vars.get_mut(&name.val))
+      .ok_or_else(|| Compilation(UndefinedVar(name.val.clone()), vec![name.pos]))
+  }
+  fn runtime_err(&mut self, err: RuntimeErr, msg: Option<String>, pos: Position) -> ErrOR<Json> {
+    let root = self.jsonpiler.first_parser()?.val.file.clone();
+    let (file, l_c, code, carets) = self.jsonpiler.parsers[pos.file as usize].err_info(pos, &root);
+    let args = msg.unwrap_or_default();
+    io::stdout().flush()?;
+    write!(
+      io::stderr(),
+      "{RUNTIME_ERR}\n| {err}{args}{ERR_SEPARATE}{file}{l_c}{ERR_SEPARATE}{code}| {carets}{ERR_END}"
+    )?;
+    self.flow = Some(Flow::Exit(1));
+    Ok(Null(Lit(())))
+  }
+}
@@ -0,0 +1,519 @@
+use super::{Flow, Frame, Function, Interpreter, Module};
+use crate::prelude::*;
+use io::{BufRead as _, IsTerminal as _, Write as _};
+use std::{cmp::Ordering, rc::Rc};
+impl Interpreter<'_> {
+  #[expect(clippy::float_arithmetic)]
+  fn arithmetic(&mut self, func: &mut Pos<BuiltIn>) -> ErrOR<Json> {
+    let name = func.val.name.clone();
+    match func.arg()? {
+      Pos { val: Int(Lit(int)), .. } if func.val.len == 1 => Ok(Int(Lit(int.wrapping_neg()))),
+      Pos { val: Float(Lit(float)), .. } if func.val.len == 1 => Ok(Float(Lit(-float))),
+      Pos { val: Int(Lit(mut acc)), .. } => {
+        for _ in 1..func.val.len {
+          let rhs = arg!(func, (Int(Lit(x))) => x).val;
+          let result = match name.as_str() {
+            "+" => acc.checked_add(rhs),
+            "-" => acc.checked_sub(rhs),
+            "*" => acc.checked_mul(rhs),
+            _ if rhs == 0 => return self.runtime_err(RuntimeZeroDivision, None, func.pos),
+            _ => acc.checked_div(rhs),
+          };
+          let Some(value) = result else {
+            return self.runtime_err(RuntimeOverflow, None, func.pos);
+          };
+          acc = value;
+        }
+        Ok(Int(Lit(acc)))
+      }
+      Pos { val: Float(Lit(mut acc)), .. } => {
+        for _ in 1..func.val.len {
+          let rhs = arg!(func, (Float(Lit(x))) => x).val;
+          acc = match name.as_str() {
+            "+" => acc + rhs,
+            "-" => acc - rhs,
+            "*" => acc * rhs,
+            _ => acc / rhs,
+          };
+        }
+        Ok(Float(Lit(acc)))
+      }
+      Pos { val: Str(Lit(mut string)), .. } if name == "+" => {
+        for _ in 1..func.val.len {
+          string.push_str(&arg!(func, (Str(Lit(x))) => x).val);
+        }
+        Ok(Str(Lit(string)))
+      }
+      other => Err(func.args_err(
+        if name == "+" { vec![IntT, FloatT, StrT] } else { vec![IntT, FloatT] },
+        other.map_ref(Json::as_type),
+      )),
+    }
+  }
+  fn assign(&mut self, is_global: bool, func: &mut Pos<BuiltIn>, frame: &mut Frame) -> ErrOR<Json> {
+    let mut assign_expr = arg!(func, (Object(Lit(x))) => x);
+    if assign_expr.val.len() == 1
+      && let (name, Pos { val: Array(Lit(mut expr)), .. }) = take(&mut assign_expr.val[0])
+      && name.val == "="
+      && expr.len() == 2
+    {
+      let var = take(&mut expr[0]).into_ident("Variable name")?;
+      let val = self.eval(take(&mut expr[1]), frame)?;
+      if self.flow.is_some() {
+        return Ok(Null(Lit(())));
+      }
+      self.check_defined(&var, frame)?;
+      let vars = if is_global {
+        &mut self.modules[frame.file].globals
+      } else {
+        frame.locals.last_mut().ok_or(Internal(UnknownLabel))?
+      };
+      vars.insert(var.val, (if is_global { GlobalVar } else { LocalVar }, val.val));
+      Ok(Null(Lit(())))
+    } else {
+      Err(type_err(
+        format!("`{}`'s argument", func.val.name),
+        vec![CustomT("Assign expression".into())],
+        assign_expr.pos.with(ObjectT),
+      ))
+    }
+  }
+  #[expect(clippy::float_arithmetic)]
+  fn assign_op(&mut self, func: &mut Pos<BuiltIn>, frame: &mut Frame) -> ErrOR<Json> {
+    let var = func.arg()?.into_ident("Variable name")?;
+    let value = self.eval(func.arg()?, frame)?;
+    if self.flow.is_some() {
+      return Ok(Null(Lit(())));
+    }
+    let name = func.val.name.clone();
+    let (kind, old) = self.get_var(&var, frame)?.clone();
+    let value_type = value.val.as_type();
+    let result = match (old, value.val) {
+      (Int(Lit(lhs)), Int(Lit(rhs))) => {
+        let result = match name.as_str() {
+          "+=" => lhs.checked_add(rhs),
+          "-=" => lhs.checked_sub(rhs),
+          "*=" => lhs.checked_mul(rhs),
+          _ if rhs == 0 => return self.runtime_err(RuntimeZeroDivision, None, var.pos),
+          _ => lhs.checked_div(rhs),
+        };
+        let Some(int) = result else {
+          return self.runtime_err(RuntimeOverflow, None, var.pos);
+        };
+        Int(Lit(int))
+      }
+      (Float(Lit(lhs)), Float(Lit(rhs))) => Float(Lit(match name.as_str() {
+        "+=" => lhs + rhs,
+        "-=" => lhs - rhs,
+        "*=" => lhs * rhs,
+        _ => lhs / rhs,
+      })),
+      (Str(Lit(mut lhs)), Str(Lit(rhs))) if name == "+=" => {
+        lhs.push_str(&rhs);
+        Str(Lit(lhs))
+      }
+      (old_val, Int(_) | Float(_)) => {
+        return Err(type_err(
+          format_variable(&var.val, kind),
+          vec![value_type],
+          var.pos.with(old_val.as_type()),
+        ));
+      }
+      (old_val, Str(_)) if name == "+=" => {
+        return Err(type_err(
+          format_variable(&var.val, kind),
+          vec![StrT],
+          var.pos.with(old_val.as_type()),
+        ));
+      }
+      (_, other) => {
+        return Err(func.args_err(
+          if name == "+=" { vec![IntT, FloatT, StrT] } else { vec![IntT, FloatT] },
+          value.pos.with(other.as_type()),
+        ));
+      }
+    };
+    self.get_var(&var, frame)?.1 = result;
+    Ok(Null(Lit(())))
+  }
+  pub(super) fn builtin(&mut self, func: &mut Pos<BuiltIn>, frame: &mut Frame) -> ErrOR<Json> {
+    match func.val.name.as_str() {
+      "+" | "-" | "*" | "/" => self.arithmetic(func),
+      "%" => self.rem(func),
+      "<<" | ">>" => self.shift(func),
+      "abs" => Ok(match func.arg()? {
+        Pos { val: Int(Lit(int)), .. } => Int(Lit(int.wrapping_abs())),
+        Pos { val: Float(Lit(float)), .. } => Float(Lit(float.abs())),
+        other => return Err(func.args_err(vec![IntT, FloatT], other.map_ref(Json::as_type))),
+      }),
+      "Float" =>
+      {
+        #[expect(clippy::cast_precision_loss)]
+        Ok(Float(Lit(arg!(func, (Int(Lit(x))) => x).val as f64)))
+      }
+      "Int" => Ok(Int(Lit(truncate(arg!(func, (Float(Lit(x))) => x).val)))),
+      "sqrt" => Ok(Float(Lit(arg!(func, (Float(Lit(x))) => x).val.sqrt()))),
+      "random" => {
+        self.seed ^= self.seed << 7u8;
+        self.seed ^= self.seed >> 9u8;
+        self.seed ^= self.seed << 13u8;
+        Ok(Int(Lit(self.seed.cast_signed())))
+      }
+      "==" | "!=" | "<" | "<=" | ">" | ">=" => compare(func),
+      "and" | "or" | "xor" => logic(func),
+      "not" => Ok(match func.arg()? {
+        Pos { val: Bool(Lit(boolean)), .. } => Bool(Lit(!boolean)),
+        Pos { val: Int(Lit(int)), .. } => Int(Lit(!int)),
+        other => return Err(func.args_err(vec![IntT, BoolT], other.map_ref(Json::as_type))),
+      }),
+      "assert" => {
+        let boolean = arg!(func, (Bool(Lit(x))) => x);
+        let string = arg!(func, (Str(Lit(x))) => x).val;
+        if boolean.val {
+          Ok(Null(Lit(())))
+        } else {
+          self.runtime_err(AssertionErr, Some(string), boolean.pos)
+        }
+      }
+      "let" | "global" => self.assign(func.val.name == "global", func, frame),
+      "=" => self.reassign(func, frame),
+      "+=" | "-=" | "*=" | "/=" => self.assign_op(func, frame),
+      "$" => Ok(self.get_var(&arg!(func, (Str(Lit(x))) => x), frame)?.1.clone()),
+      "scope" | "value" => Ok(self.eval(func.arg()?, frame)?.val),
+      "list" => Ok(Array(Lit(take(&mut func.val.args).collect()))),
+      "break" | "continue" => {
+        if frame.loops == 0 {
+          return err!(func.pos, OutSideError { name: func.val.name.clone(), place: "loop" });
+        }
+        self.flow = Some(if func.val.name == "break" { Flow::Break } else { Flow::Continue });
+        Ok(Null(Lit(())))
+      }
+      "if" => self.f_if(func, frame),
+      "while" => self.f_while(func, frame),
+      "define" => self.define(func, frame),
+      "ret" => self.ret(func, frame),
+      "export" => self.export(func, frame),
+      "import" => self.import(func, frame),
+      "main" if func.pos.file == 0 => Ok(self.eval(func.arg()?, frame)?.val),
+      "main" => Ok(Null(Lit(()))),
+      "print" => self.print(func, frame),
+      "input" => {
+        let mut line = String::new();
+        let mut stdin = io::stdin().lock();
+        stdin.read_line(&mut line)?;
+        // Like the compiled runtime, only console input loses its line break.
+        if stdin.is_terminal() {
+          let len = line.trim_end_matches(['\r', '\n']).len();
+          line.truncate(len);
+        }
+        Ok(Str(Lit(line)))
+      }
+      "Str" => Ok(Str(Lit(arg!(func, (Int(Lit(x))) => x).val.to_string()))),
+      "len" => Ok(Int(Lit(i64::try_from(arg!(func, (Str(Lit(x))) => x).val.chars().count())?))),
+      "slice" => slice(func),
+      _ => err!(func.pos, UnsupportedOnTarget(func.val.name.clone(), "the interpreter")),
+    }
+  }
+  fn define(&mut self, func: &mut Pos<BuiltIn>, frame: &mut Frame) -> ErrOR<Json> {
+    let name = func.arg()?.into_ident("Function name")?;
+    self.check_defined(&name, frame)?;
+    let type_annotations = arg_custom!(
+      func, vec![CustomT("TypeAnnotations".into())], (Object(Lit(x))) => x
+    );
+    let mut params = vec![];
+    for (var_name, param) in type_annotations.val {
+      let param_type_str = param.into_ident("Type annotation")?;
+      let json_type = JsonType::from_string(&param_type_str.val);
+      json_type.mem_type(param_type_str.pos)?;
+      params.push((var_name.val, json_type));
+    }
+    let ret_type = JsonType::from_string(&func.arg()?.into_ident("Type annotation")?.val);
+    let body = func.arg()?;
+    let function = Function { body, file: frame.file, params, ret_type };
+    self.modules[frame.file].functions.insert(name.val, Rc::new(function));
+    Ok(Null(Lit(())))
+  }
+  fn export(&mut self, func: &mut Pos<BuiltIn>, frame: &Frame) -> ErrOR<Json> {
+    for _ in 1..=func.val.len {
+      let name = func.arg()?.into_ident("Function name")?;
+      let module = &mut self.modules[frame.file];
+      if !module.functions.contains_key(&name.val) {
+        return err!(name.pos, UndefinedFunc(name.val));
+      }
+      module.exports.insert(name.val);
+    }
+    Ok(Null(Lit(())))
+  }
+  fn f_if(&mut self, func: &mut Pos<BuiltIn>, frame: &mut Frame) -> ErrOR<Json> {
+    let if_expr_t = vec![CustomT("Array[Bool, Any] (Literal)".into())];
+    let mut arg = func.arg()?;
+    let Array(Lit(_)) = arg.val else {
+      let cond = self.eval_with_scope(take(&mut arg), frame)?;
+      if self.flow.is_some() {
+        return Ok(Null(Lit(())));
+      }
+      let Bool(Lit(condition)) = cond.val else {
+        return Err(func.args_err(if_expr_t, cond.map_ref(Json::as_type)));
+      };
+      func.validate_args(Exact(2))?;
+      let then = func.arg()?;
+      if condition {
+        self.eval_with_scope(then, frame)?;
+      }
+      return Ok(Null(Lit(())));
+    };
+    for nth in 1..=func.val.len {
+      let mut if_expr =
+        if nth == 1 { take(&mut arg) } else { func.arg()? }.map(|json| match json {
+          Array(Lit(pair)) => Some(pair),
+          Bool(_) | Float(_) | Func(..) | Int(_) | Null(_) | Object(_) | Str(_) | Struct(..)
+          | Array(Var(_)) => None,
+        });
+      let Some(pair) = if_expr.val.as_mut().filter(|pair| pair.len() == 2) else {
+        return Err(type_err("`if` expression".into(), if_expr_t, if_expr.pos.with(ArrayT(None))));
+      };
+      let then = pair.remove(1);
+      let cond = self.eval_with_scope(pair.remove(0), frame)?;
+      if self.flow.is_some() {
+        break;
+      }
+      let condition = unwrap_arg!(cond, "`if` condition", vec![BoolT], (Bool(Lit(x))) => x);
+      if condition.val {
+        self.eval_with_scope(then, frame)?;
+        break;
+      }
+    }
+    Ok(Null(Lit(())))
+  }
+  fn f_while(&mut self, func: &mut Pos<BuiltIn>, frame: &mut Frame) -> ErrOR<Json> {
+    let cond = func.arg()?;
+    let body = func.arg()?;
+    frame.loops += 1;
+    loop {
+      let value = self.eval(cond.clone(), frame)?;
+      if self.flow.is_some() {
+        break;
+      }
+      let condition = unwrap_arg!(value, "`while` condition", vec![BoolT], (Bool(Lit(x))) => x);
+      if !condition.val {
+        break;
+      }
+      self.eval_with_scope(body.clone(), frame)?;
+      match self.flow {
+        Some(Flow::Break) => {
+          self.flow = None;
+          break;
+        }
+        Some(Flow::Continue) => self.flow = None,
+        Some(Flow::Exit(_) | Flow::Return(_)) => break,
+        None => (),
+      }
+    }
+    frame.loops -= 1;
+    Ok(Null(Lit(())))
+  }
+  fn import(&mut self, func: &mut Pos<BuiltIn>, frame: &mut Frame) -> ErrOR<Json> {
+    let file = self.jsonpiler.resolve_import(&arg!(func, (Str(Lit(x))) => x))?;
+    let mut imports: BTreeMap<String, Vec<Position>> = BTreeMap::new();
+    for _ in 1..func.val.len {
+      let import_func = func.arg()?.into_ident("Function name")?;
+      imports.entry(import_func.val).or_default().push(import_func.pos);
+    }
+    if self.jsonpiler.parsers[file.pos.file as usize].val.file == file.val {
+      return err!(file.pos, RecursiveInclude(file.val));
+    }
+    let parsers = &self.jsonpiler.parsers;
+    let file_idx = if let Some(idx) = parsers.iter().position(|parser| parser.val.file == file.val)
+    {
+      idx
+    } else {
+      let is_jspl = match Path::new(&file.val).extension().map(|ext| ext.to_string_lossy()) {
+        Some(ext) if ext == "jspl" => true,
+        Some(ext) if ext == "json" => false,
+        _ => return err!(file.pos, UnsupportedFile),
+      };
+      let source = self.jsonpiler.read_import(&file)?;
+      let file_idx = self.jsonpiler.parsers.len();
+      let parser = self.jsonpiler.push_parser(source, file.val.clone())?;
+      let map_pos_vec = |mut err| {
+        if let Compilation(_, pos_vec) | Parse(_, pos_vec) = &mut err {
+          pos_vec.push(file.pos);
+        }
+        err
+      };
+      let parsed = if is_jspl { parser.parse_jspl() } else { parser.parse_json() }
+        .map_err(|err| map_pos_vec(err.into()))?;
+      self.modules.resize_with(file_idx + 1, Module::default);
+      let mut module_frame = Frame::new(file_idx);
+      self.eval(parsed, &mut module_frame).map_err(map_pos_vec)?;
+      if self.flow.is_some() {
+        return Ok(Null(Lit(())));
+      }
+      file_idx
+    };
+    for (name, refs) in imports {
+      if !self.modules[file_idx].exports.contains(&name) {
+        return err!(file.pos, IncludeFuncNotFound(BTreeSet::from([name])));
+      }
+      let function = Rc::clone(&self.modules[file_idx].functions[&name]);
+      let current = &self.modules[frame.file].functions;
+      if current.get(&name).is_some_and(|before| Rc::ptr_eq(before, &function)) {
+        continue;
+      }
+      self.check_defined(&refs[0].with(name.clone()), frame)?;
+      self.modules[frame.file].functions.insert(name, function);
+    }
+    Ok(Null(Lit(())))
+  }
+  fn print(&mut self, func: &mut Pos<BuiltIn>, frame: &mut Frame) -> ErrOR<Json> {
+    let mut stdout = io::stdout().lock();
+    for _ in 1..=func.val.len {
+      let printable = self.eval(func.arg()?, frame)?;
+      if self.flow.is_some() {
+        break;
+      }
+      let Str(Lit(string)) = printable.val else {
+        return Err(func.args_err(vec![StrT], printable.map_ref(Json::as_type)));
+      };
+      stdout.write_all(string.as_bytes())?;
+    }
+    stdout.flush()?;
+    Ok(Null(Lit(())))
+  }
+  fn reassign(&mut self, func: &mut Pos<BuiltIn>, frame: &mut Frame) -> ErrOR<Json> {
+    let var = func.arg()?.into_ident("Variable name")?;
+    let val = self.eval(func.arg()?, frame)?;
+    if self.flow.is_some() {
+      return Ok(Null(Lit(())));
+    }
+    let (kind, old) = self.get_var(&var, frame)?;
+    if old.as_type() != val.val.as_type() {
+      return Err(type_err(
+        format_variable(&var.val, *kind),
+        vec![old.as_type()],
+        val.map_ref(Json::as_type),
+      ));
+    }
+    *old = val.val;
+    Ok(Null(Lit(())))
+  }
+  fn rem(&mut self, func: &mut Pos<BuiltIn>) -> ErrOR<Json> {
+    let lhs = arg!(func, (Int(Lit(x))) => x).val;
+    let rhs = arg!(func, (Int(Lit(x))) => x);
+    if rhs.val == 0 {
+      return self.runtime_err(RuntimeZeroDivision, None, rhs.pos);
+    }
+    match lhs.checked_rem(rhs.val) {
+      Some(int) => Ok(Int(Lit(int))),
+      None => self.runtime_err(RuntimeOverflow, None, func.pos),
+    }
+  }
+  fn ret(&mut self, func: &mut Pos<BuiltIn>, frame: &Frame) -> ErrOR<Json> {
+    let ret = func.arg()?;
+    let Some(ret_type) = &frame.ret_type else {
+      return err!(ret.pos, OutSideError { name: func.val.name.clone(), place: "function" });
+    };
+    if *ret_type != ret.val.as_type() {
+      let ret_val = format!("Function `{}`'s return value", func.val.name);
+      return Err(type_err(ret_val, vec![ret_type.clone()], ret.map_ref(Json::as_type)));
+    }
+    self.flow = Some(Flow::Return(ret.val));
+    Ok(Null(Lit(())))
+  }
+  fn shift(&mut self, func: &mut Pos<BuiltIn>) -> ErrOR<Json> {
+    let lhs = arg!(func, (Int(Lit(x))) => x).val;
+    let rhs = arg!(func, (Int(Lit(x))) => x);
+    let Some(amount) = u32::try_from(rhs.val).ok().filter(|amount| *amount < 64) else {
+      return self.runtime_err(RuntimeTooLargeShift, None, rhs.pos);
+    };
+    Ok(Int(Lit(if func.val.name == "<<" {
+      lhs.wrapping_shl(amount)
+    } else {
+      (lhs.cast_unsigned() >> amount).cast_signed()
+    })))
+  }
+}
+fn compare(func: &mut Pos<BuiltIn>) -> ErrOR<Json> {
+  let name = func.val.name.clone();
+  let holds = |ordering: Option<Ordering>| {
+    matches!(
+      (name.as_str(), ordering),
+      ("==", Some(Ordering::Equal) | None)
+        | ("!=" | ">" | ">=", Some(Ordering::Greater))
+        | ("!=" | "<" | "<=", Some(Ordering::Less))
+        | ("<" | "<=", None)
+        | (">=" | "<=", Some(Ordering::Equal))
+    )
+  };
+  let mut result = true;
+  match func.arg()? {
+    Pos { val: Int(Lit(mut prev)), .. } => {
+      for _ in 1..func.val.len {
+        let next = arg!(func, (Int(Lit(x))) => x).val;
+        result &= holds(Some(prev.cmp(&next)));
+        prev = next;
+      }
+    }
+    Pos { val: Float(Lit(mut prev)), .. } => {
+      for _ in 1..func.val.len {
+        let next = arg!(func, (Float(Lit(x))) => x).val;
+        result &= holds(prev.partial_cmp(&next));
+        prev = next;
+      }
+    }
+    Pos { val: Str(Lit(string)), .. } if matches!(name.as_str(), "==" | "!=") => {
+      func.validate_args(Exact(2))?;
+      result = (string == arg!(func, (Str(Lit(x))) => x).val) == (name == "==");
+    }
+    other => return Err(func.args_err(vec![IntT, FloatT, StrT], other.map_ref(Json::as_type))),
+  }
+  Ok(Bool(Lit(result)))
+}
+fn logic(func: &mut Pos<BuiltIn>) -> ErrOR<Json> {
+  let name = func.val.name.clone();
+  match func.arg()? {
+    Pos { val: Bool(Lit(mut acc)), .. } => {
+      for _ in 1..func.val.len {
+        let rhs = arg!(func, (Bool(Lit(x))) => x).val;
+        acc = match name.as_str() {
+          "and" => acc & rhs,
+          "or" => acc | rhs,
+          _ => acc ^ rhs,
+        };
+      }
+      Ok(Bool(Lit(acc)))
+    }
+    Pos { val: Int(Lit(mut acc)), .. } => {
+      for _ in 1..func.val.len {
+        let rhs = arg!(func, (Int(Lit(x))) => x).val;
+        acc = match name.as_str() {
+          "and" => acc & rhs,
+          "or" => acc | rhs,
+          _ => acc ^ rhs,
+        };
+      }
+      Ok(Int(Lit(acc)))
+    }
+    other => Err(func.args_err(vec![IntT, BoolT], other.map_ref(Json::as_type))),
+  }
+}
+fn slice(func: &mut Pos<BuiltIn>) -> ErrOR<Json> {
+  let chars = arg!(func, (Str(Lit(x))) => x).val.chars().collect::<Vec<char>>();
+  let len = i64::try_from(chars.len())?;
+  let start = arg!(func, (Int(Lit(x))) => x).val;
+  let end = if func.val.len == 3 { arg!(func, (Int(Lit(x))) => x).val } else { len };
+  let resolve = |idx: i64| if idx < 0 { idx + len } else { idx };
+  let (start_idx, end_idx) = (resolve(start), resolve(end));
+  if !(0 <= start_idx && start_idx < end_idx && end_idx <= len) {
+    return Ok(Str(Lit(String::new())));
+  }
+  Ok(Str(Lit(chars[usize::try_from(start_idx)?..usize::try_from(end_idx)?].iter().collect())))
+}
+#[expect(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+fn truncate(float: f64) -> i64 {
+  if float.is_nan() || float >= i64::MAX as f64 || float < i64::MIN as f64 {
+    i64::MIN
+  } else {
+    float as i64
+  }
+}
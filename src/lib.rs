@@ -1,26 +1,38 @@
+pub mod api;
 mod assembler;
 mod command_line;
 mod compiler;
 mod dependency;
+pub mod extension;
 mod internal;
+mod interpreter;
 mod parser;
 mod prelude;
 mod server;
+pub mod syntax;
 mod utility;
+pub mod vfs;
 use prelude::*;
 pub struct Jsonpiler {
   analysis: Option<Analysis>,
-  builtin: HashMap<&'static str, BuiltInInfo>,
+  builtin: BTreeMap<&'static str, BuiltInInfo>,
+  closures: BTreeMap<LabelId, LabelId>,
   data: Vec<DataLbl>,
   dlls: Vec<Dll>,
+  emit: Emit,
+  extensions: Vec<Extension>,
   functions: BTreeMap<LabelId, CompiledFunc>,
   globals: BTreeMap<String, Pos<Variable>>,
   handlers: Handlers,
   id_seed: LabelId,
+  locations: BTreeMap<LabelId, Position>,
   parsers: Vec<Pos<Parser>>,
+  provider: Box<dyn SourceProvider>,
   release: bool,
   startup: Vec<Inst>,
-  str_cache: HashMap<String, LabelId>,
-  symbols: HashMap<&'static str, LabelId>,
+  str_cache: BTreeMap<String, LabelId>,
+  structs: Vec<Pos<StructInfo>>,
+  symbols: BTreeMap<&'static str, LabelId>,
+  target: Target,
   user_defined: BTreeMap<String, Pos<UserDefinedInfo>>,
 }
@@ -1,3 +1,4 @@
+use crate::api::DiagnosticCode;
 use crate::prelude::*;
 use std::{num::TryFromIntError, path};
 pub(crate) type ParseErrOR<T> = Result<T, Pos<ParseErr>>;
@@ -10,17 +11,12 @@ pub(crate) enum JsonpilerErr {
   Platform(String),
 }
 #[derive(Debug, Clone, Copy)]
-pub(crate) enum Arity {
-  AtLeast(u32),
-  #[expect(dead_code)]
-  AtMost(u32),
-  Exact(u32),
-  Range(u32, u32),
-}
-#[derive(Debug, Clone, Copy)]
 pub(crate) enum RuntimeErr {
   AssertionErr,
   // Debug,
+  RuntimeEmptyArray,
+  RuntimeIndexOutOfBounds,
+  RuntimeMissingKey,
   RuntimeOverflow,
   RuntimeTooLargeShift,
   RuntimeZeroDivision,
@@ -30,6 +26,7 @@ pub(crate) enum RuntimeErr {
 pub(crate) enum CompilationErr {
   ArityError { name: String, expected: Arity, actual: u32 },
   DuplicateName(NameKind, String),
+  ExtensionError(String),
   IOError(String),
   IncludeFuncNotFound(BTreeSet<String>),
   OutSideError { name: String, place: &'static str },
@@ -38,10 +35,12 @@ pub(crate) enum CompilationErr {
   TooLargeFile,
   TooLargeShift,
   TypeError { name: String, expected: Vec<JsonType>, actual: JsonType },
+  UndefinedField(String, String),
   UndefinedFunc(String),
   UndefinedVar(String),
   UnknownType(String),
   UnsupportedFile,
+  UnsupportedOnTarget(String, &'static str),
   UnsupportedType(String),
   ZeroDivision,
 }
@@ -91,6 +90,8 @@ pub(crate) enum NameKind {
   GlobalVar,
   #[default]
   LocalVar,
+  StructField,
+  StructType,
   UserDefinedFunc,
 }
 impl From<Pos<ParseErr>> for JsonpilerErr {
@@ -124,6 +125,14 @@ impl fmt::Display for JsonpilerErr {
   }
 }
 impl JsonpilerErr {
+  pub(crate) fn err_code(&self) -> DiagnosticCode {
+    match self {
+      Compilation(kind, _) => kind.err_code(),
+      Internal(_) => DiagnosticCode::Internal,
+      Parse(kind, _) => kind.err_code(),
+      Platform(_) => DiagnosticCode::Platform,
+    }
+  }
   pub(crate) fn issue_msg(&self) -> Option<String> {
     match self {
       Compilation(..) | Parse(..) | Platform(_) => None,
@@ -211,9 +220,12 @@ impl fmt::Display for CompilationErr {
       UnknownType(typ) => write!(f, "Unknown type:\n  {typ}"),
       UndefinedVar(var) => write!(f, "Undefined variable:\n  {var}"),
       UndefinedFunc(func) => write!(f, "Undefined function:\n  {func}"),
+      UndefinedField(name, field) => write!(f, "Undefined field:\n  `{name}` has no `{field}`"),
       UnsupportedFile => write!(f, "Unsupported file:\n  .json or .jspl expected"),
+      UnsupportedOnTarget(name, target) => write!(f, "`{name}` is not supported on {target}"),
       RecursiveInclude(file) => write!(f, "Recursive include:\n  {file}"),
       DuplicateName(kind, name) => write!(f, "Duplicate {kind}:\n  `{name}`"),
+      ExtensionError(message) => write!(f, "{message}"),
       OutSideError { name, place } => write!(f, "`{name}` outside of {place}"),
       TypeError { name, expected, actual: typ } => {
         write!(
@@ -259,6 +271,9 @@ impl fmt::Display for InternalErr {
 impl fmt::Display for RuntimeErr {
   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
     match self {
+      RuntimeEmptyArray => write!(f, "{EMPTY_ARRAY}"),
+      RuntimeIndexOutOfBounds => write!(f, "{INDEX_OUT_OF_BOUNDS}"),
+      RuntimeMissingKey => write!(f, "{MISSING_KEY}:\n|   "),
       RuntimeOverflow => write!(f, "Overflow"),
       RuntimeZeroDivision => write!(f, "{ZERO_DIVISION}"),
       RuntimeTooLargeShift => write!(f, "{TOO_LARGE_SHIFT}"),
@@ -287,10 +302,13 @@ impl fmt::Display for NameKind {
       UserDefinedFunc => "user-defined function",
       GlobalVar => "global variable",
       LocalVar => "local variable",
+      StructField => "field",
+      StructType => "struct",
     })
   }
 }
 impl fmt::Display for Arity {
+  #[inline]
   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
     write!(f, "{} argument{}", self.range(), self.plural())
   }
@@ -338,6 +356,57 @@ impl Arity {
     }
   }
 }
+impl CompilationErr {
+  pub(crate) fn err_code(&self) -> DiagnosticCode {
+    match self {
+      ArityError { .. } => DiagnosticCode::ArityError,
+      DuplicateName(..) => DiagnosticCode::DuplicateName,
+      ExtensionError(_) => DiagnosticCode::ExtensionError,
+      IOError(_) => DiagnosticCode::IoError,
+      IncludeFuncNotFound(_) => DiagnosticCode::IncludeFuncNotFound,
+      OutSideError { .. } => DiagnosticCode::OutsideError,
+      Overflow => DiagnosticCode::Overflow,
+      RecursiveInclude(_) => DiagnosticCode::RecursiveInclude,
+      TooLargeFile => DiagnosticCode::TooLargeFile,
+      TooLargeShift => DiagnosticCode::TooLargeShift,
+      TypeError { .. } => DiagnosticCode::TypeError,
+      UndefinedField(..) => DiagnosticCode::UndefinedField,
+      UndefinedFunc(_) => DiagnosticCode::UndefinedFunc,
+      UndefinedVar(_) => DiagnosticCode::UndefinedVar,
+      UnknownType(_) => DiagnosticCode::UnknownType,
+      UnsupportedFile => DiagnosticCode::UnsupportedFile,
+      UnsupportedOnTarget(..) => DiagnosticCode::UnsupportedOnTarget,
+      UnsupportedType(_) => DiagnosticCode::UnsupportedType,
+      ZeroDivision => DiagnosticCode::ZeroDivision,
+    }
+  }
+}
+impl ParseErr {
+  pub(crate) fn err_code(&self) -> DiagnosticCode {
+    match self {
+      ExpectedIdent => DiagnosticCode::ExpectedIdent,
+      ExpectedToken(_) => DiagnosticCode::ExpectedToken,
+      IntOutOfRange => DiagnosticCode::IntOutOfRange,
+      InvalidChar => DiagnosticCode::InvalidChar,
+      InvalidFloat => DiagnosticCode::InvalidFloat,
+      InvalidKeyword => DiagnosticCode::InvalidKeyword,
+      UnexpectedToken(_) => DiagnosticCode::UnexpectedToken,
+      UnterminatedLiteral => DiagnosticCode::UnterminatedLiteral,
+    }
+  }
+}
+impl Warning {
+  pub(crate) fn err_code(&self) -> DiagnosticCode {
+    match self {
+      EarlyElse => DiagnosticCode::EarlyElse,
+      UnreachableIf => DiagnosticCode::UnreachableIf,
+      UnreachableWhile => DiagnosticCode::UnreachableWhile,
+      UnusedName(..) => DiagnosticCode::UnusedName,
+      UselessIfTrue => DiagnosticCode::UselessIfTrue,
+      UselessLiteral => DiagnosticCode::UselessLiteral,
+    }
+  }
+}
 impl InternalErr {
   pub(crate) fn err_code(&self) -> &'static str {
     match self {
@@ -390,7 +459,7 @@ impl Pos<BuiltIn> {
     }
   }
 }
-fn format_nth_args(nth: u32, name: &str) -> String {
+pub(crate) fn format_nth_args(nth: u32, name: &str) -> String {
   let suffix = match nth % 10 {
     _ if (11..=13).contains(&(nth % 100)) => "th",
     1 => "st",
@@ -89,7 +89,7 @@ impl Pos<Parser> {
       };
     let is_single_if = if let Array(Lit(array)) = &val.val
       && array.len() == 2
-      && array[0].val.as_type() != ArrayT
+      && !matches!(array[0].val, Array(_))
     {
       matches!(key.val.as_ref(), "if")
     } else {
@@ -91,7 +91,7 @@ impl Pos<Parser> {
         self.format_object(out, size, json.pos, object, indentation)?;
       }
       Null(Var(_)) | Int(Var(_)) | Str(Var(_)) | Object(Var(_)) | Array(Var(_)) | Bool(Var(_))
-      | Float(Var(_)) => return None,
+      | Float(Var(_)) | Func(..) | Struct(..) => return None,
     }
     Some(())
   }
@@ -135,7 +135,8 @@ impl Pos<Parser> {
           Some(acc)
         }
       }
-      Null(_) | Array(_) | Bool(_) | Float(_) | Int(_) | Object(_) | Str(_) => None,
+      Null(_) | Array(_) | Bool(_) | Float(_) | Func(..) | Int(_) | Object(_) | Str(_)
+      | Struct(..) => None,
     }
   }
 }
@@ -72,7 +72,15 @@ impl Pos<Parser> {
       return Err(pos.with(ExpectedIdent));
     }
     self.set_size(&mut pos);
-    Ok(pos.with(self.get_slice(pos)?.into()))
+    let mut ident: String = self.get_slice(pos)?.into();
+    if ident.starts_with(|char: char| char.is_ascii_uppercase())
+      && self.check_eof().is_ok()
+      && self.peek() == b'['
+    {
+      self.parse_type_args(&mut ident)?;
+      self.set_size(&mut pos);
+    }
+    Ok(pos.with(ident))
   }
   pub(crate) fn parse_jspl(&mut self) -> ParseErrOR<Pos<Json>> {
     if self.skip_ws_comment(true).is_err() {
@@ -87,6 +95,27 @@ impl Pos<Parser> {
       Err(self.pos.with(ExpectedToken(TokenKind::Eof)))
     }
   }
+  fn parse_type_args(&mut self, ident: &mut String) -> ParseErrOR<()> {
+    let mut depth = 0u32;
+    loop {
+      let byte = self.consume()?;
+      match byte {
+        b'[' => depth += 1,
+        b']' => depth -= 1,
+        b' ' | b'\t' => continue,
+        b',' | b'_' => (),
+        _ if byte.is_ascii_alphanumeric() => (),
+        _ => {
+          self.pos.offset -= 1;
+          return Err(self.pos.with(InvalidChar));
+        }
+      }
+      ident.push(byte as char);
+      if depth == 0 {
+        return Ok(());
+      }
+    }
+  }
   fn skip_space_check_sep(&mut self) -> bool {
     while (self.pos.offset as usize) < self.val.text.len() {
       match self.peek() {
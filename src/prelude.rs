@@ -9,7 +9,7 @@ pub(crate) use crate::assembler::ops::{
   UnaryKind::{self, *},
 };
 pub(crate) use crate::assembler::{
-  Assembler,
+  Assembler, ImageInfo, PdbFunc, Resources, allocate_registers, disassemble,
   disp::Disp,
   inst::Inst::{self, *},
   register::Register::{self, *},
@@ -18,9 +18,16 @@ pub(crate) use crate::assembler::{
     Section::{self, *},
     SectionHeader,
   },
+  write_pdb,
 };
 pub(crate) use crate::dependency::{Analysis, CompiledFunc, Dependency, SymbolInfo};
-pub(crate) use crate::internal::handler::Handlers;
+pub(crate) use crate::extension::{
+  Arity::{self, *},
+  Extension,
+};
+pub(crate) use crate::internal::{
+  array::array_elem, handler::Handlers, map::map_value, record::record_field,
+};
 pub(crate) use crate::json::{
   Bind::{self, *},
   Json::{self, *},
@@ -29,7 +36,6 @@ pub(crate) use crate::json::{
   KeyVal,
 };
 pub(crate) use crate::parser::error::{
-  Arity::{self, *},
   CompilationErr::*,
   ErrOR,
   InternalErr::*,
@@ -40,7 +46,7 @@ pub(crate) use crate::parser::error::{
   RuntimeErr::{self, *},
   TokenKind,
   Warning::{self, *},
-  format_ret_val, format_variable, make_header, type_err,
+  format_nth_args, format_ret_val, format_variable, make_header, type_err,
 };
 pub(crate) use crate::parser::{
   Comment, Parser,
@@ -52,8 +58,8 @@ pub(crate) use crate::server::{
   sync::{Channel, Scheduler},
 };
 pub(crate) use crate::utility::consts::{
-  assembly_consts::*, builtin_flags::*, custom_insts::*, dll::*, format_config::*, gui_config::*,
-  runtime_err::*, symbols::*, version::*,
+  array_shape::*, assembly_consts::*, builtin_flags::*, custom_insts::*, dll::*, format_config::*,
+  gui_config::*, runtime_err::*, symbols::*, version::*,
 };
 pub(crate) use crate::utility::memory::{
   Address::{self, *},
@@ -65,7 +71,8 @@ pub(crate) use crate::utility::memory::{
   Storage::{self, *},
 };
 pub(crate) use crate::utility::other::{
-  BuiltIn, BuiltInInfo, BuiltInPtr, Dll, FileIdx, LabelId, Seh, Signature, UserDefinedInfo,
+  BuiltIn, BuiltInInfo, BuiltInPtr, Dll, Emit, FileIdx, LabelId, Params, Seh, Signature, SourceMap,
+  StructInfo, Subsystem, Target, UnwindFrame, UserDefinedInfo,
 };
 pub(crate) use crate::utility::{
   data_lbl::{
@@ -77,7 +84,8 @@ pub(crate) use crate::utility::{
   var_table::{VarTable, Variable},
   *,
 };
-pub(crate) use crate::{arg, arg_custom, built_in, err, extend, symbol, unwrap_arg, write_all};
+pub(crate) use crate::vfs::{FileSystem, SourceProvider};
+pub(crate) use crate::{arg, arg_custom, built_in, err, extend, symbol, unwrap_arg};
 pub(crate) use std::{
   collections::{BTreeMap, BTreeSet, HashMap},
   env, fmt, fs, io, iter,
@@ -1,7 +1,7 @@
 mod method;
 pub(crate) mod sync;
 mod time_stamp;
-mod utility;
+pub(crate) mod utility;
 use self::time_stamp::{format_micros, time_stamp};
 pub(crate) use self::utility::*;
 use crate::prelude::*;
@@ -12,9 +12,19 @@ use std::{
   time::Instant,
 };
 const MB: u64 = 1 << 20u8;
+struct OpenBuffers(HashMap<String, String>);
+impl SourceProvider for OpenBuffers {
+  fn read(&self, file: &str, limit: u64) -> io::Result<String> {
+    if self.0.contains_key(file) { self.0.read(file, limit) } else { FileSystem.read(file, limit) }
+  }
+  fn resolve(&self, from: &str, path: &str) -> io::Result<String> {
+    FileSystem.resolve(from, path)
+  }
+}
 pub(crate) struct Server {
   channel: Channel,
   pub docs: Option<HashMap<String, String>>,
+  extensions: Vec<Extension>,
   pub requests: BTreeMap<IdKind, (String, Instant)>,
   scheduler: Scheduler,
   shutdown: bool,
@@ -75,26 +85,26 @@ impl Server {
       ObjectN(vec![("uri".into(), StrN(uri)), ("diagnostics".into(), ArrayN(vec![]))]),
     )
   }
+  pub(crate) fn compiler(&self) -> Jsonpiler {
+    let mut jsonpiler = Jsonpiler::new(true);
+    for extension in &self.extensions {
+      jsonpiler.register_extension(extension.clone());
+    }
+    jsonpiler
+  }
   pub(crate) fn get_source(&mut self, uri: &str) -> Option<Source> {
     let file = uri2path(uri);
     self.sources.get(uri).cloned().or_else(|| {
-      if fs::metadata(&file).ok()?.len() <= MB {
-        let source = Source::new(fs::read_to_string(&file).ok()?);
-        self.sources.insert(uri.to_owned(), source);
-        self.update_source(uri);
-        self.sources.get(uri).cloned()
-      } else {
-        None
-      }
+      let source = Source::new(FileSystem.read(&file, MB).ok()?);
+      self.sources.insert(uri.to_owned(), source);
+      self.update_source(uri);
+      self.sources.get(uri).cloned()
     })
   }
   pub(crate) fn get_source_mut(&mut self, uri: &str) -> Option<&mut Source> {
     let file = uri2path(uri);
     if let Entry::Vacant(entry) = self.sources.entry(uri.to_owned()) {
-      if fs::metadata(&file).ok()?.len() > MB {
-        return None;
-      }
-      entry.insert(Source::new(fs::read_to_string(&file).ok()?));
+      entry.insert(Source::new(FileSystem.read(&file, MB).ok()?));
       self.update_source(uri);
     }
     self.sources.get_mut(uri)
@@ -168,7 +178,7 @@ impl Server {
       }
     }
   }
-  pub(crate) fn new() -> Self {
+  pub(crate) fn new(extensions: Vec<Extension>) -> Self {
     let channel = Channel::new();
     Server {
       shutdown: false,
@@ -178,9 +188,23 @@ impl Server {
       stdin: BufReader::new(io::stdin()),
       stdout: io::stdout(),
       docs: None,
+      extensions,
       requests: BTreeMap::new(),
     }
   }
+  fn open_buffers(&self) -> OpenBuffers {
+    OpenBuffers(
+      self
+        .sources
+        .iter()
+        .map(|(uri, source)| {
+          let file = uri2path(uri);
+          let canonical = Path::new(&file).canonicalize().map(|path| path.to_string_lossy().into());
+          (canonical.unwrap_or(file), source.text.clone())
+        })
+        .collect(),
+    )
+  }
   fn read(&mut self) -> Option<String> {
     let mut content_length = 0;
     loop {
@@ -75,7 +75,9 @@ impl Server {
   }
   pub(crate) fn m_initialize(&mut self, id: IdKind) {
     use super::build_doc_cache;
-    self.docs = Some(build_doc_cache());
+    let mut docs = build_doc_cache();
+    docs.extend(self.extensions.iter().map(|ext| (ext.name.to_owned(), ext.doc.clone())));
+    self.docs = Some(docs);
     let mut capabilities = vec![
       ("textDocumentSync".into(), IntN(2)),
       (
@@ -75,7 +75,8 @@ impl Server {
       self.sources.remove(uri);
       return;
     };
-    let mut jsonpiler = Jsonpiler::new(true);
+    let mut jsonpiler = self.compiler();
+    jsonpiler.set_source_provider(self.open_buffers());
     for reload_uri in take(&mut source.reload) {
       if Path::new(&uri2path(&reload_uri)).exists() {
         self.update_source(&reload_uri)
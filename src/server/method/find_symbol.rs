@@ -29,7 +29,7 @@ impl Server {
         format!(
           "```jspl\n{}\n```\n{}\n",
           match info.kind {
-            Argument => format!("{{ {}: {} }}", info.name, info.json_type),
+            Argument | StructField => format!("{{ {}: {} }}", info.name, info.json_type),
             GlobalVar => format!("global({}: {} = _)", info.name, info.json_type),
             LocalVar => format!("let({}: {} = _)", info.name, info.json_type),
             BuiltInFunc | UserDefinedFunc =>
@@ -48,6 +48,24 @@ impl Server {
               } else {
                 format!("{}(?) -> ?", info.name)
               },
+            StructType =>
+              if let StructT(_, struct_id) = &info.json_type
+                && let Some(record) = jsonpiler.structs.get(*struct_id as usize)
+              {
+                format!(
+                  "struct({}, {{ {} }})",
+                  info.name,
+                  record
+                    .val
+                    .fields
+                    .iter()
+                    .map(|(field, json_type, _)| format!("{}: {json_type}", field.val))
+                    .collect::<Vec<_>>()
+                    .join("; ")
+                )
+              } else {
+                format!("struct({}, {{ ? }})", info.name)
+              },
           },
           info.kind
         )
@@ -90,7 +108,7 @@ impl Server {
     self.flush(uri.clone());
     let source = self.get_source(&uri)?;
     let offset = range2offset(&source.text, &position)?;
-    let mut jsonpiler = Jsonpiler::new(true);
+    let mut jsonpiler = self.compiler();
     let parsed = jsonpiler.push_parser(source.text, uri2path(&uri)).ok()?.parse_jspl().ok()?;
     jsonpiler.compile(parsed).ok()?;
     Some((jsonpiler, offset))
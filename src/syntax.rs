@@ -0,0 +1,92 @@
+use crate::api::Diagnostic;
+use crate::prelude::*;
+/// A parsed value and the source range it came from.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct Node<T> {
+  pub span: Span,
+  pub val: T,
+}
+/// A source range in bytes; `line` is the 0-based line on which it starts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct Span {
+  pub line: u32,
+  pub offset: u32,
+  pub size: u32,
+}
+/// The syntax tree shared by JSON and JSPL sources.
+///
+/// JSPL is lowered to the JSON form the compiler reads: a call `f(x, y)` is the object
+/// `{"f": [x, y]}`, an operator `a + b` is `{"+": [a, b]}` and a variable `a` is `{"$": "a"}`.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum Value {
+  Array(Vec<Node<Value>>),
+  Bool(bool),
+  Float(f64),
+  Int(i64),
+  Null,
+  Object(Vec<(Node<String>, Node<Value>)>),
+  Str(String),
+}
+/// Converts a parsed tree; the parsers only produce literals, so any runtime value is a bug.
+fn node(json: Pos<Json>) -> ErrOR<Node<Value>> {
+  let val = match json.val {
+    Array(Lit(array)) => Value::Array(array.into_iter().map(node).collect::<ErrOR<_>>()?),
+    Bool(Lit(boolean)) => Value::Bool(boolean),
+    Float(Lit(float)) => Value::Float(float),
+    Int(Lit(int)) => Value::Int(int),
+    Null(Lit(())) => Value::Null,
+    Object(Lit(object)) => Value::Object(
+      object
+        .into_iter()
+        .map(|(key, value)| Ok((Node { span: span(key.pos), val: key.val }, node(value)?)))
+        .collect::<ErrOR<_>>()?,
+    ),
+    Str(Lit(string)) => Value::Str(string),
+    Array(Var(_)) | Bool(Var(_)) | Float(Var(_)) | Int(Var(_)) | Null(Var(_)) | Object(Var(_))
+    | Str(Var(_)) | Func(..) | Struct(..) => return Err(Internal(CastError)),
+  };
+  Ok(Node { span: span(json.pos), val })
+}
+fn parse(file: &str, text: String, is_jspl: bool) -> Result<Node<Value>, Diagnostic> {
+  let mut jsonpiler = Jsonpiler::new(false);
+  let parsed = (|| -> ErrOR<Pos<Json>> {
+    let parser = jsonpiler.push_parser(text, file.to_owned())?;
+    Ok(if is_jspl { parser.parse_jspl() } else { parser.parse_json() }?)
+  })();
+  parsed.and_then(node).map_err(|err| jsonpiler.diagnostic(&err))
+}
+/// Formats JSPL `text` the way `jsonpiler format` does; sources the formatter cannot lay out are
+/// returned unchanged.
+///
+/// # Errors
+/// When `text` does not parse.
+#[inline]
+pub fn format_jspl(file: &str, text: String) -> Result<String, Diagnostic> {
+  if let Some(out) = <Pos<Parser>>::new(text.clone(), 0, file.to_owned(), 0).format() {
+    return Ok(out);
+  }
+  parse_jspl(file, text.clone())?;
+  Ok(text)
+}
+/// Parses a JSON source named `file`.
+///
+/// # Errors
+/// The first syntax error, with its stable `code`.
+#[inline]
+pub fn parse_json(file: &str, text: String) -> Result<Node<Value>, Diagnostic> {
+  parse(file, text, false)
+}
+/// Parses a JSPL source named `file`.
+///
+/// # Errors
+/// The first syntax error, with its stable `code`.
+#[inline]
+pub fn parse_jspl(file: &str, text: String) -> Result<Node<Value>, Diagnostic> {
+  parse(file, text, true)
+}
+fn span(pos: Position) -> Span {
+  Span { line: pos.line, offset: pos.offset, size: pos.size }
+}
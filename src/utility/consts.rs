@@ -50,10 +50,13 @@ pub mod builtin_flags {
 pub mod custom_insts {
   pub const CQO: &[u8] = &[0x48, 0x99];
   pub const RET: &[u8] = &[0xC3];
+  pub const CALL_RAX: &[u8] = &[0xFF, 0xD0];
   pub const CLD_REPNE_SCASB: &[u8] = &[0xFC, 0xF2, 0xAE];
   pub const CLD_REP_MOVSB: &[u8] = &[0xFC, 0xF3, 0xA4];
   pub const BTR_RAX_63: &[u8] = &[0x48, 0x0F, 0xBA, 0xF0, 0x3F];
   pub const BTC_RAX_63: &[u8] = &[0x48, 0x0F, 0xBA, 0xF8, 0x3F];
+  pub const SYSCALL: &[u8] = &[0x0F, 0x05];
+  pub const RDTSC: &[u8] = &[0x0F, 0x31];
 }
 pub mod gui_config {
   pub const GUI_H: u32 = 0x200;
@@ -78,17 +81,40 @@ pub mod format_config {
   ];
 }
 pub mod assembly_consts {
-  use crate::Register::{self, R8, R9, Rcx, Rdx};
+  use crate::Register::{self, R8, R9, R12, Rbx, Rcx, Rdi, Rdx, Rsi};
   pub const ARG_REGS: [Register; 4] = [Rcx, Rdx, R8, R9];
+  pub const NON_VOLATILE_REGS: [Register; 4] = [Rbx, Rsi, Rdi, R12];
   pub const IMAGE_BASE: u64 = 0x1_4000_0000;
+  pub const DLL_IMAGE_BASE: u64 = 0x1_8000_0000;
+  pub const ELF_IMAGE_BASE: u64 = 0x40_0000;
   pub const FILE_ALIGNMENT: u32 = 0x200;
   pub const SECTION_ALIGNMENT: u32 = 0x1000;
   pub const PE_HEADER_OFFSET: u32 = 0x40;
-  pub const NUMBER_OF_SECTIONS: u16 = 7;
+  pub const NUMBER_OF_SECTIONS: u16 = 10;
   pub const OPTIONAL_HEADER_SIZE: u16 = 0xF0;
   pub const HEADERS_SIZE: u32 =
     PE_HEADER_OFFSET + 0x18 + OPTIONAL_HEADER_SIZE as u32 + 0x28 * NUMBER_OF_SECTIONS as u32;
 }
+pub mod array_shape {
+  pub const SHAPE_BITS: u32 = 4;
+  pub const SHAPE_MASK: u64 = 0xF;
+  pub const SHAPE_ARRAY: u64 = 1;
+  pub const SHAPE_STR: u64 = 2;
+  pub const SHAPE_INT: u64 = 3;
+  pub const SHAPE_FLOAT: u64 = 4;
+  pub const SHAPE_BOOL: u64 = 5;
+  pub const SHAPE_NULL: u64 = 6;
+  pub const SHAPE_MAP: u64 = 7;
+  pub const ARRAY_HEADER: i8 = 16;
+  pub const STR_HEADER: i8 = 8;
+  pub const CLOSURE_HEADER: u32 = 3;
+  pub const CLOSURE_COUNT: u32 = 1;
+  pub const CLOSURE_DROP: u32 = 2;
+  pub const MAP_HEADER: i8 = 16;
+  pub const MAP_INIT_CAP: u32 = 8;
+  pub const FNV_OFFSET: u64 = 0xCBF2_9CE4_8422_2325;
+  pub const FNV_PRIME: u64 = 0x0100_0000_01B3;
+}
 pub mod symbols {
   macro_rules! def_sym {
     ($($name:ident,)+) => {
@@ -117,11 +143,33 @@ pub mod symbols {
     STR_EQ,
     INT2STR,
     UTF8_SLICE,
+    LAST_ERROR,
+    ARRAY_COPY,
+    ARRAY_DROP,
+    ARRAY_PUSH,
+    ELEM_COPY,
+    ELEM_DROP,
+    MAP_COPY,
+    MAP_DROP,
+    MAP_FIND,
+    MAP_GROW,
+    MAP_INSERT,
+    MAP_KEYS,
+    MAP_REMOVE,
+    STR_HASH,
+    STR_ALLOC,
+    STR_RELEASE,
+    STR_RETAIN,
+    CLOSURE_RELEASE,
+    CLOSURE_RETAIN,
   );
 }
 pub mod runtime_err {
   pub const ZERO_DIVISION: &str = "Division by zero";
   pub const TOO_LARGE_SHIFT: &str = "Shift amount exceeds 63 bits";
+  pub const INDEX_OUT_OF_BOUNDS: &str = "Index out of bounds";
+  pub const EMPTY_ARRAY: &str = "Pop from an empty array";
+  pub const MISSING_KEY: &str = "Key not found";
   pub const ACCESS_VIOLATION: &str = "AccessViolation";
   pub const STACK_OVERFLOW: &str = "StackOverflow";
   pub const EXCEPTION_OCCURRED: &str = "ExceptionOccurred";
@@ -158,29 +206,83 @@ Include:
   pub const COMMAND: &str = "
 Commands:
 
-version
-    Print program version
-
-help
-    Print this help message
-
-<input.jspl | input.json> [args for .exe]
-    Build an executable and run
-
-release <input.jspl | input.json> [args for .exe]
-    Build a release version and run
+<input.jspl | input.json> [-- args for .exe]
+    Build an executable and run it with the arguments after `--`
 
 build <input.jspl | input.json>
-    Build an executable
+    Build without running
 
-build release <input.jspl | input.json>
-release build <input.jspl | input.json>
-    Build a release version
+interpret <input.jspl | input.json>
+    Run the program with the reference interpreter and exit with its final Int
 
 format input.jspl
     Format the source code
 
+disasm input.exe
+    Disassemble the code sections of a PE executable
+
 server
     Start a LSP server for the VS Code extension
+
+help, -h, --help
+    Print this help message
+
+version, -V, --version
+    Print program version
+
+Options:
+
+-o, --output <path>
+    Write the output to <path> (default: next to the input, named after it); the source map
+    and PDB, when requested, are written next to it
+
+--out-dir <dir>
+    Write the output to <dir>, named after the input (cannot be combined with -o)
+
+--emit <exe | asm | obj | map | dll>
+    Select the output: an executable (default), an Intel-syntax listing (.asm), a COFF object
+    (.obj) whose exported functions can be linked from C or Rust after calling
+    `jsonpiler_init`, only the source map (.map.json), or a DLL (.dll) that exports the
+    exported functions
+
+--release
+    Build a release version with register allocation and peephole optimization
+
+-W, --deny-warnings
+    Fail when any warning is reported
+
+-v, --verbose
+    Report how many bytes of .text the peephole optimizer saved in release builds
+
+--target <x86_64-windows | x86_64-linux>
+    Select the target platform (default: x86_64-windows)
+
+--icon <icon.ico>
+    Embed the icon in the executable or DLL
+
+--version-info <key>=<value>
+    Add a version string such as FileVersion=1.2.3.4, ProductName or CompanyName (repeatable)
+
+--manifest
+    Embed a manifest that enables per-monitor DPI awareness and the UTF-8 code page
+
+--deterministic
+    Write 0 as the build timestamp so that identical sources produce identical files
+    (SOURCE_DATE_EPOCH, when set, is used instead)
+
+--source-map
+    Also write a .map.json that maps .text ranges to the JSPL source
+
+--pdb
+    Write a .pdb with function names and .jspl line numbers for debuggers
+
+--subsystem <console | windows>
+    Select the PE subsystem (default: windows when the only I/O is GUI, message or confirm,
+    console otherwise)
+
+--
+    Pass the remaining arguments to the executable
+
+The words `release` and `build dll` are still accepted for `--release` and `--emit dll`.
 ";
 }
@@ -5,6 +5,7 @@ pub(crate) enum DataLbl {
   BssLbl(LabelId, u32, u32),
   Byte(LabelId, u8),
   Quad(LabelId, u64),
+  SrcMapLbl(LabelId),
   StrLbl(LabelId, String),
   WStrLbl(LabelId, String),
 }
@@ -1,7 +1,7 @@
 use crate::prelude::*;
 impl Jsonpiler {
   pub(crate) fn drop_all(&mut self, result: Json, scope: &mut Scope) -> ErrOR<()> {
-    self.drop_json(result, false, scope);
+    self.drop_json(result, false, scope)?;
     self.drop_all_local(scope)?;
     self.drop_global(scope)
   }
@@ -21,20 +21,22 @@ impl Jsonpiler {
       globals,
       |jsonpiler, json, scope_2| {
         if let Some(memory) = json.memory() {
-          jsonpiler.heap_free(memory, scope_2);
+          jsonpiler.heap_free(memory, scope_2)?;
         }
+        Ok(())
       },
       scope,
     )
   }
   #[expect(clippy::needless_pass_by_value)]
-  pub(crate) fn drop_json(&mut self, json: Json, force: bool, scope: &mut Scope) {
+  pub(crate) fn drop_json(&mut self, json: Json, force: bool, scope: &mut Scope) -> ErrOR<()> {
     if let Some(memory @ Memory(Local(lifetime, offset), mem_type)) = json.memory()
       && (force || lifetime == Tmp)
     {
       scope.free(offset, mem_type);
-      self.heap_free(memory, scope);
+      self.heap_free(memory, scope)?;
     }
+    Ok(())
   }
   pub(crate) fn drop_scope(&mut self, scope: &mut Scope) -> ErrOR<()> {
     self.drop_var_table(
@@ -50,7 +52,7 @@ impl Jsonpiler {
     scope: &mut Scope,
   ) -> ErrOR<()>
   where
-    F: FnMut(&mut Jsonpiler, Json, &mut Scope),
+    F: FnMut(&mut Jsonpiler, Json, &mut Scope) -> ErrOR<()>,
   {
     for (name, variable) in var_table {
       if variable.val.refs.is_empty() && !name.starts_with('_') {
@@ -63,11 +65,40 @@ impl Jsonpiler {
         json_type: variable.val.val.as_type(),
         refs: variable.val.refs,
       });
-      free(self, variable.val.val, scope);
+      free(self, variable.val.val, scope)?;
     }
     Ok(())
   }
-  pub(crate) fn heap_free(&mut self, Memory(addr, mem_type): Memory, scope: &mut Scope) {
+  pub(crate) fn heap_free(
+    &mut self,
+    Memory(addr, mem_type): Memory,
+    scope: &mut Scope,
+  ) -> ErrOR<()> {
+    if let ArrayOf(shape) = mem_type.size {
+      let array_drop = self.array_drop(scope.id)?;
+      scope.extend(&[mov_q(Rcx, addr), mov_q(Rdx, shape), Call(array_drop)]);
+      return Ok(());
+    }
+    if let MapOf(shape) = mem_type.size {
+      let map_drop = self.map_drop(scope.id)?;
+      scope.extend(&[mov_q(Rcx, addr), mov_q(Rdx, shape), Call(map_drop)]);
+      return Ok(());
+    }
+    if let RecordOf(id) = mem_type.size {
+      let record_drop = self.record_drop(id, scope.id)?;
+      scope.extend(&[mov_q(Rcx, addr), Call(record_drop)]);
+      return Ok(());
+    }
+    if mem_type.size == Closure {
+      let closure_release = self.closure_release(scope.id)?;
+      scope.extend(&[mov_q(Rcx, addr), Call(closure_release)]);
+      return Ok(());
+    }
+    if mem_type.size == Dynamic {
+      let str_release = self.str_release(scope.id)?;
+      scope.extend(&[mov_q(Rcx, addr), Call(str_release)]);
+      return Ok(());
+    }
     if mem_type.heap == HeapPtr {
       scope.extend(&[
         mov_q(Rcx, Global(self.symbols[HEAP])),
@@ -77,5 +108,6 @@ impl Jsonpiler {
         DecMd(Global(self.symbols[LEAK_CNT])),
       ]);
     }
+    Ok(())
   }
 }
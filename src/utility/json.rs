@@ -5,10 +5,12 @@ pub(crate) enum Json {
   Array(Bind<Vec<Pos<Json>>>),
   Bool(Bind<bool>),
   Float(Bind<f64>),
+  Func(Box<Signature>, Memory),
   Int(Bind<i64>),
   Null(Bind<()>),
   Object(Bind<Vec<KeyVal>>),
   Str(Bind<String>),
+  Struct(String, Memory),
 }
 #[derive(Debug, Clone)]
 pub(crate) enum Bind<T> {
@@ -37,15 +39,17 @@ pub(crate) enum JsonNoPos {
 }
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub(crate) enum JsonType {
-  ArrayT,
+  ArrayT(Option<Box<JsonType>>),
   BoolT,
   CustomT(String),
   FloatT,
   FuncT(Box<Signature>),
   IntT,
+  MapT(Box<JsonType>),
   NullT,
   ObjectT,
   StrT,
+  StructT(String, u32),
 }
 impl Json {
   pub(crate) fn as_str(&self) -> Option<&str> {
@@ -53,13 +57,29 @@ impl Json {
   }
   pub(crate) fn as_type(&self) -> JsonType {
     match self {
-      Array(_) => ArrayT,
+      Array(Lit(array)) => {
+        let mut types = array.iter().map(|elem| elem.val.as_type());
+        match types.next() {
+          Some(first) if types.all(|elem_type| elem_type == first) => ArrayT(Some(Box::new(first))),
+          _ => ArrayT(None),
+        }
+      }
+      Array(Var(Memory(_, MemoryType { size: ArrayOf(shape), .. }))) => {
+        ArrayT(Some(Box::new(JsonType::from_shape(*shape))))
+      }
+      Array(Var(_)) => ArrayT(None),
       Bool(_) => BoolT,
       Float(_) => FloatT,
+      Func(sig, _) => FuncT(sig.clone()),
       Int(_) => IntT,
       Null(_) => NullT,
+      Object(Var(Memory(_, MemoryType { size: MapOf(shape), .. }))) => {
+        MapT(Box::new(JsonType::from_shape(*shape)))
+      }
       Object(_) => ObjectT,
       Str(_) => StrT,
+      Struct(name, Memory(_, MemoryType { size: RecordOf(id), .. })) => StructT(name.clone(), *id),
+      Struct(name, _) => CustomT(name.clone()),
     }
   }
   pub(crate) fn delete_pos(self) -> JsonNoPos {
@@ -76,7 +96,7 @@ impl Json {
       ),
       Str(Lit(bind)) => StrN(bind),
       Array(Var(_)) | Bool(Var(_)) | Float(Var(_)) | Int(Var(_)) | Null(Var(_))
-      | Object(Var(_)) | Str(Var(_)) => StrN(self.describe()),
+      | Object(Var(_)) | Str(Var(_)) | Struct(..) | Func(..) => StrN(self.describe()),
     }
   }
   pub(crate) fn describe(&self) -> String {
@@ -91,13 +111,21 @@ impl Json {
         Int(bind) => format!("{bind}"),
         Str(bind) => format!("{bind}"),
         Array(bind) => format!("{bind}"),
+        Func(_, memory) | Struct(_, memory) => format!("{memory}"),
       }
     )
   }
   pub(crate) fn memory(&self) -> Option<Memory> {
     match self {
-      Int(Var(memory)) | Float(Var(memory)) | Str(Var(memory)) | Bool(Var(memory))
-      | Array(Var(memory)) | Null(Var(memory)) | Object(Var(memory)) => Some(*memory),
+      Int(Var(memory))
+      | Float(Var(memory))
+      | Str(Var(memory))
+      | Bool(Var(memory))
+      | Array(Var(memory))
+      | Null(Var(memory))
+      | Object(Var(memory))
+      | Func(_, memory)
+      | Struct(_, memory) => Some(*memory),
       Array(_) | Bool(_) | Float(_) | Int(_) | Null(_) | Object(_) | Str(_) => None,
     }
   }
@@ -135,7 +163,36 @@ impl JsonNoPos {
   }
 }
 impl JsonType {
+  pub(crate) fn any_map() -> Self {
+    MapT(Box::new(CustomT("T".into())))
+  }
+  pub(crate) fn from_shape(shape: u64) -> Self {
+    match shape & SHAPE_MASK {
+      SHAPE_ARRAY => ArrayT(Some(Box::new(Self::from_shape(shape >> SHAPE_BITS)))),
+      SHAPE_STR => StrT,
+      SHAPE_INT => IntT,
+      SHAPE_FLOAT => FloatT,
+      SHAPE_BOOL => BoolT,
+      SHAPE_MAP => MapT(Box::new(Self::from_shape(shape >> SHAPE_BITS))),
+      _ => NullT,
+    }
+  }
   pub(crate) fn from_string(name: &str) -> Self {
+    if let Some(elem) = name.strip_prefix("Array[").and_then(|rest| rest.strip_suffix(']')) {
+      return ArrayT(Some(Box::new(Self::from_string(elem))));
+    }
+    if let Some(value) = name.strip_prefix("Map[Str,").and_then(|rest| rest.strip_suffix(']')) {
+      return MapT(Box::new(Self::from_string(value.trim_start())));
+    }
+    if let Some(types) = name.strip_prefix("Func[").and_then(|rest| rest.strip_suffix(']'))
+      && !types.is_empty()
+    {
+      let mut params = split_type_args(types)
+        .map(|param| (String::new(), Self::from_string(param)))
+        .collect::<Vec<_>>();
+      let ret_type = params.pop().map_or(NullT, |(_, ret)| ret);
+      return FuncT(Box::new(Signature { params, ret_type }));
+    }
     match name {
       "Str" => StrT,
       "Int" => IntT,
@@ -143,7 +200,7 @@ impl JsonType {
       "Null" => NullT,
       "Bool" => BoolT,
       "Object" => ObjectT,
-      "Array" => ArrayT,
+      "Array" => ArrayT(None),
       unknown => CustomT(unknown.to_owned()),
     }
   }
@@ -152,11 +209,31 @@ impl JsonType {
       BoolT => Ok(MemoryType { heap: Value, size: Small(RB) }),
       FloatT | IntT | NullT => Ok(MemoryType { heap: Value, size: Small(RQ) }),
       StrT => Ok(MemoryType { heap: HeapPtr, size: Dynamic }),
-      FuncT(_) | ArrayT | ObjectT => err!(pos, UnsupportedType(self.name())),
+      ArrayT(Some(elem)) => Ok(MemoryType { heap: HeapPtr, size: ArrayOf(elem.shape(pos)?) }),
+      MapT(value) => Ok(MemoryType { heap: HeapPtr, size: MapOf(value.shape(pos)?) }),
+      StructT(_, id) => Ok(MemoryType { heap: HeapPtr, size: RecordOf(*id) }),
+      FuncT(sig) => {
+        for (_, param) in &sig.params {
+          param.mem_type(pos)?;
+        }
+        sig.ret_type.mem_type(pos)?;
+        Ok(MemoryType { heap: HeapPtr, size: Closure })
+      }
+      ArrayT(None) | ObjectT => err!(pos, UnsupportedType(self.name())),
       CustomT(_) => err!(pos, UnknownType(self.name())),
     }
   }
   pub(crate) fn name(&self) -> String {
+    if let ArrayT(Some(elem)) = self {
+      return format!("Array[{elem}]");
+    }
+    if let MapT(value) = self {
+      return format!("Map[Str, {value}]");
+    }
+    if let FuncT(sig) = self {
+      let types = sig.params.iter().map(|(_, param)| param).chain(iter::once(&sig.ret_type));
+      return format!("Func[{}]", types.map(JsonType::name).collect::<Vec<_>>().join(", "));
+    }
     match self {
       BoolT => "Bool",
       NullT => "Null",
@@ -165,11 +242,33 @@ impl JsonType {
       FuncT(_) => "Func",
       IntT => "Int",
       StrT => "Str",
-      ArrayT => "Array",
-      CustomT(name) => name,
+      ArrayT(_) => "Array",
+      MapT(_) => "Map",
+      CustomT(name) | StructT(name, _) => name,
     }
     .into()
   }
+  pub(crate) fn shape(&self, pos: Position) -> ErrOR<u64> {
+    Ok(match self {
+      ArrayT(Some(inner)) | MapT(inner) => {
+        let inner_shape = inner.shape(pos)?;
+        if inner_shape.leading_zeros() < SHAPE_BITS {
+          return err!(pos, UnsupportedType(self.name()));
+        }
+        let tag = if let MapT(_) = self { SHAPE_MAP } else { SHAPE_ARRAY };
+        (inner_shape << SHAPE_BITS) | tag
+      }
+      StrT => SHAPE_STR,
+      IntT => SHAPE_INT,
+      FloatT => SHAPE_FLOAT,
+      BoolT => SHAPE_BOOL,
+      NullT => SHAPE_NULL,
+      FuncT(_) | ArrayT(None) | ObjectT | StructT(..) => {
+        return err!(pos, UnsupportedType(self.name()));
+      }
+      CustomT(name) => return err!(pos, UnknownType(name.clone())),
+    })
+  }
   pub(crate) fn to_json(&self, pos: Position, addr: Address) -> ErrOR<Json> {
     let memory = Memory(addr, self.mem_type(pos)?);
     match self {
@@ -178,7 +277,11 @@ impl JsonType {
       FloatT => Ok(Float(Var(memory))),
       NullT => Ok(Null(Var(memory))),
       BoolT => Ok(Bool(Var(memory))),
-      FuncT(_) | ArrayT | ObjectT => err!(pos, UnsupportedType(self.name())),
+      ArrayT(_) => Ok(Array(Var(memory))),
+      MapT(_) => Ok(Object(Var(memory))),
+      StructT(name, _) => Ok(Struct(name.clone(), memory)),
+      FuncT(sig) => Ok(Func(sig.clone(), memory)),
+      ObjectT => err!(pos, UnsupportedType(self.name())),
       CustomT(name) => err!(pos, UnknownType(name.clone())),
     }
   }
@@ -262,6 +365,19 @@ fn format_items<'a, T, F: Fn((&mut fmt::Formatter, &'a T)) -> Result<&'a JsonNoP
     Ok(())
   }
 }
+fn split_type_args(types: &str) -> impl Iterator<Item = &str> {
+  let mut depth = 0u32;
+  types
+    .split(move |char: char| {
+      match char {
+        '[' => depth += 1,
+        ']' => depth = depth.saturating_sub(1),
+        _ => (),
+      }
+      char == ',' && depth == 0
+    })
+    .map(str::trim)
+}
 fn indent(fmter: &mut fmt::Formatter, n: usize) -> fmt::Result {
   for _ in 0..n {
     write!(fmter, "  ")?;
@@ -12,10 +12,6 @@ macro_rules! symbol {
   }};
 }
 #[macro_export]
-macro_rules! write_all {
-  ($writer:expr, $($data:expr),+ $(,)?) => { $($writer.write_all(&$data)?;)+ };
-}
-#[macro_export]
 macro_rules! extend {
   ($vec:expr, $($data:expr),+ $(,)?) => { $($vec.extend_from_slice(&$data);)+ };
 }
@@ -23,8 +23,12 @@ pub(crate) enum Storage {
 }
 #[derive(Debug, Clone, Copy, Ord, PartialOrd, Eq, PartialEq)]
 pub(crate) enum MemorySize {
+  ArrayOf(u64),
+  Closure,
   Dynamic,
   Known(i32),
+  MapOf(u64),
+  RecordOf(u32),
   Small(RegSize),
 }
 #[derive(Debug, Clone, Copy, Ord, PartialOrd, Eq, PartialEq)]
@@ -66,7 +70,7 @@ impl MemoryType {
       match self.size {
         Known(size) => size,
         Small(size) => size as i32,
-        Dynamic => 8,
+        ArrayOf(_) | Closure | Dynamic | MapOf(_) | RecordOf(_) => 8,
       }
     }
   }
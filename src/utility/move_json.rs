@@ -10,7 +10,7 @@ impl Jsonpiler {
     if !copy {
       return self.mov_args_json_mini(idx, arg, None, scope);
     }
-    if arg.val.as_type() != StrT {
+    if !matches!(arg.val, Str(_) | Array(_) | Object(_) | Struct(..) | Func(..)) {
       return self.mov_args_json_mini(idx, arg, Some(scope.id), scope);
     }
     let reg = *ARG_REGS.get(idx as usize).unwrap_or(&Rax);
@@ -54,6 +54,7 @@ impl Jsonpiler {
       Var(memory) => mov_memory_xmm(xmm, tmp, memory),
     }
   }
+  // With `copy`, strings and closures are shared; arrays, maps and structs are deep-copied.
   pub(crate) fn mov_json(
     &mut self,
     dst: Register,
@@ -65,12 +66,42 @@ impl Jsonpiler {
       Bool(boolean) => Ok(mov_bool(dst, boolean)),
       Int(int) => Ok(mov_int(dst, int)),
       Float(float) => Ok(mov_float_reg(dst, float)),
-      Str(string) => Ok(if let Some(caller) = copy {
-        vec![self.mov_str(Rcx, string), Call(self.copy_str(caller)?), mov_q(dst, Rax)]
+      Str(string) => Ok(match (copy, string) {
+        (Some(caller), Lit(lit)) => {
+          vec![self.mov_str(Rcx, Lit(lit)), Call(self.copy_str(caller)?), mov_q(dst, Rax)]
+        }
+        (Some(caller), Var(Memory(addr, _))) => {
+          vec![mov_q(Rcx, addr), Call(self.str_retain(caller)?), mov_q(dst, Rax)]
+        }
+        (None, string_2) => vec![self.mov_str(dst, string_2)],
+      }),
+      Array(Var(Memory(addr, MemoryType { size: ArrayOf(shape), .. }))) => {
+        Ok(if let Some(caller) = copy {
+          vec![mov_q(Rcx, addr), mov_q(Rdx, shape), Call(self.array_copy(caller)?), mov_q(dst, Rax)]
+        } else {
+          vec![mov_q(dst, addr)]
+        })
+      }
+      Object(Var(Memory(addr, MemoryType { size: MapOf(shape), .. }))) => {
+        Ok(if let Some(caller) = copy {
+          vec![mov_q(Rcx, addr), mov_q(Rdx, shape), Call(self.map_copy(caller)?), mov_q(dst, Rax)]
+        } else {
+          vec![mov_q(dst, addr)]
+        })
+      }
+      Struct(_, Memory(addr, MemoryType { size: RecordOf(id), .. })) => {
+        Ok(if let Some(caller) = copy {
+          vec![mov_q(Rcx, addr), Call(self.record_copy(id, caller)?), mov_q(dst, Rax)]
+        } else {
+          vec![mov_q(dst, addr)]
+        })
+      }
+      Func(_, Memory(addr, _)) => Ok(if let Some(caller) = copy {
+        vec![mov_q(Rcx, addr), Call(self.closure_retain(caller)?), mov_q(dst, Rax)]
       } else {
-        vec![self.mov_str(dst, string)]
+        vec![mov_q(dst, addr)]
       }),
-      Array(_) | Object(_) => err!(src.pos, UnsupportedType(src.val.describe())),
+      Array(_) | Object(_) | Struct(..) => err!(src.pos, UnsupportedType(src.val.describe())),
     }
   }
   pub(crate) fn mov_str(&mut self, dst: Register, string: Bind<String>) -> Inst {
@@ -146,7 +177,9 @@ pub(crate) fn ret_memory(
     Known(_) if mem_type.heap == Value => {
       Err(Internal(InvalidInst("ret_memory non-heap Known(_)".into())))
     }
-    Known(_) | Dynamic => Ok(vec![mov_q(addr, src)]),
+    ArrayOf(_) | Closure | Known(_) | Dynamic | MapOf(_) | RecordOf(_) => {
+      Ok(vec![mov_q(addr, src)])
+    }
   }
 }
 pub(crate) fn mov_memory(dst: Register, Memory(addr, mem_type): Memory) -> Vec<Inst> {
@@ -167,7 +200,7 @@ pub(crate) fn mov_memory(dst: Register, Memory(addr, mem_type): Memory) -> Vec<I
       insts
     }
     Known(_) if mem_type.heap == Value => vec![LeaRM(dst, addr)],
-    Known(_) | Dynamic => vec![mov_q(dst, addr)],
+    ArrayOf(_) | Closure | Known(_) | Dynamic | MapOf(_) | RecordOf(_) => vec![mov_q(dst, addr)],
   }
 }
 pub(crate) fn mov_memory_xmm(
@@ -184,7 +217,9 @@ pub(crate) fn mov_memory_xmm(
       }),
       RB | RD => Err(Internal(InvalidInst("illegal float".into()))),
     },
-    Known(_) | Dynamic => Err(Internal(InvalidInst("illegal float".into()))),
+    ArrayOf(_) | Closure | Known(_) | Dynamic | MapOf(_) | RecordOf(_) => {
+      Err(Internal(InvalidInst("illegal float".into())))
+    }
   }
 }
 pub(crate) fn ret_memory_xmm(
@@ -201,6 +236,8 @@ pub(crate) fn ret_memory_xmm(
       }),
       RB | RD => Err(Internal(InvalidInst("illegal float".into()))),
     },
-    Known(_) | Dynamic => Err(Internal(InvalidInst("illegal float".into()))),
+    ArrayOf(_) | Closure | Known(_) | Dynamic | MapOf(_) | RecordOf(_) => {
+      Err(Internal(InvalidInst("illegal float".into())))
+    }
   }
 }
@@ -4,7 +4,10 @@ pub(crate) type BuiltInPtr = fn(&mut Jsonpiler, &mut Pos<BuiltIn>, &mut Scope) -
 pub(crate) type Dll = (String, Vec<String>);
 pub(crate) type FileIdx = u32;
 pub(crate) type LabelId = u32;
-pub(crate) type Seh = Vec<(LabelId, LabelId, i32)>;
+pub(crate) type Params = Vec<(String, JsonType)>;
+pub(crate) type Seh = Vec<(LabelId, LabelId, i32, Vec<Register>)>;
+pub(crate) type SourceMap = Vec<(u32, u32, LabelId)>;
+pub(crate) type UnwindFrame = (i32, Vec<Register>);
 #[derive(Debug, Clone)]
 pub(crate) struct BuiltIn {
   pub args: IntoIter<Pos<Json>>,
@@ -21,9 +24,38 @@ pub(crate) struct UserDefinedInfo {
 }
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub(crate) struct Signature {
-  pub params: Vec<(String, JsonType)>,
+  pub params: Params,
   pub ret_type: JsonType,
 }
+#[derive(Debug, Clone)]
+pub(crate) struct StructInfo {
+  pub copy: Option<LabelId>,
+  pub drop: Option<LabelId>,
+  pub fields: Vec<(Pos<String>, JsonType, Vec<Position>)>,
+  pub name: String,
+  pub refs: Vec<Position>,
+}
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum Emit {
+  Asm,
+  Dll,
+  #[default]
+  Exe,
+  Map,
+  Obj,
+}
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum Target {
+  Linux,
+  #[default]
+  Windows,
+}
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum Subsystem {
+  #[default]
+  Console,
+  Windows,
+}
 #[derive(Debug, Clone, Copy)]
 pub(crate) struct BuiltInInfo {
   pub arity: Arity,
@@ -42,6 +74,64 @@ impl Pos<BuiltIn> {
     }
   }
 }
+impl Signature {
+  pub(crate) fn unnamed(self) -> Self {
+    let params = self.params.into_iter().map(|(_, param)| (String::new(), param)).collect();
+    Signature { params, ret_type: self.ret_type }
+  }
+}
+impl Emit {
+  pub(crate) fn extension(self, target: Target) -> &'static str {
+    match self {
+      Emit::Asm => "asm",
+      Emit::Dll => "dll",
+      Emit::Exe if target == Target::Linux => "",
+      Emit::Exe => "exe",
+      Emit::Map => "map.json",
+      Emit::Obj => "obj",
+    }
+  }
+  pub(crate) fn from_name(name: &str) -> Option<Self> {
+    match name {
+      "asm" => Some(Emit::Asm),
+      "dll" => Some(Emit::Dll),
+      "exe" => Some(Emit::Exe),
+      "map" => Some(Emit::Map),
+      "obj" => Some(Emit::Obj),
+      _ => None,
+    }
+  }
+}
+impl Target {
+  pub(crate) fn from_name(name: &str) -> Option<Self> {
+    match name {
+      "x86_64-linux" => Some(Target::Linux),
+      "x86_64-windows" => Some(Target::Windows),
+      _ => None,
+    }
+  }
+  pub(crate) fn name(self) -> &'static str {
+    match self {
+      Target::Linux => "x86_64-linux",
+      Target::Windows => "x86_64-windows",
+    }
+  }
+}
+impl Subsystem {
+  pub(crate) fn from_name(name: &str) -> Option<Self> {
+    match name {
+      "console" => Some(Subsystem::Console),
+      "windows" => Some(Subsystem::Windows),
+      _ => None,
+    }
+  }
+  pub(crate) fn value(self) -> u16 {
+    match self {
+      Subsystem::Console => 3,
+      Subsystem::Windows => 2,
+    }
+  }
+}
 impl<T> From<T> for Operand<T>
 where
   T: Copy + Add<Output = T>,
@@ -5,6 +5,7 @@ pub(crate) struct Scope {
   body: Vec<Inst>,
   pub epilogue: Option<(LabelId, JsonType)>,
   pub id: LabelId,
+  loc: Option<LabelId>,
   pub local_top: BTreeMap<String, Pos<Variable>>,
   pub locals: Vec<BTreeMap<String, Pos<Variable>>>,
   pub loop_labels: Vec<(LabelId, LabelId, usize)>,
@@ -92,6 +93,10 @@ impl Scope {
   ) -> impl Iterator<Item = &mut BTreeMap<String, Pos<Variable>>> {
     self.locals.iter_mut().rev().chain(iter::once(&mut self.local_top))
   }
+  pub(crate) fn mark(&mut self, loc: LabelId) -> Option<LabelId> {
+    self.body.push(Loc(loc));
+    self.loc.replace(loc)
+  }
   pub(crate) fn new(id: LabelId) -> Self {
     Scope { id, ..Scope::default() }
   }
@@ -119,13 +124,29 @@ impl Scope {
     Ok(Bool(Var(Memory(addr, MemoryType { heap: Value, size: Small(RB) }))))
   }
   pub(crate) fn ret_json_take(&mut self, dst: &Pos<JsonType>, src: Register) -> ErrOR<Json> {
-    Ok(match dst.val {
+    Ok(match &dst.val {
       NullT => Null(Var(self.ret(src)?)),
       IntT => Int(Var(self.ret(src)?)),
       BoolT => self.ret_bool(src)?,
       FloatT => Float(Var(self.ret(src)?)),
       StrT => self.ret_str(src, HeapPtr)?,
-      CustomT(_) | FuncT(_) | ArrayT | ObjectT => {
+      ArrayT(Some(_)) => {
+        let mem_type = dst.val.mem_type(dst.pos)?;
+        Array(Var(Memory(self.ret(src)?.0, mem_type)))
+      }
+      MapT(_) => {
+        let mem_type = dst.val.mem_type(dst.pos)?;
+        Object(Var(Memory(self.ret(src)?.0, mem_type)))
+      }
+      StructT(name, _) => {
+        let mem_type = dst.val.mem_type(dst.pos)?;
+        Struct(name.clone(), Memory(self.ret(src)?.0, mem_type))
+      }
+      FuncT(sig) => {
+        let mem_type = dst.val.mem_type(dst.pos)?;
+        Func(sig.clone(), Memory(self.ret(src)?.0, mem_type))
+      }
+      CustomT(_) | ArrayT(None) | ObjectT => {
         return err!(dst.pos, UnsupportedType(dst.val.to_string()));
       }
     })
@@ -39,6 +39,9 @@ impl Jsonpiler {
     if self.user_defined.contains_key(&name.val) {
       return err!(pos, DuplicateName(UserDefinedFunc, name.val.clone()));
     }
+    if self.structs.iter().any(|record| record.val.name == name.val) {
+      return err!(pos, DuplicateName(StructType, name.val.clone()));
+    }
     Ok(())
   }
   pub(crate) fn get_var(&mut self, var: &Pos<String>, scope: &mut Scope) -> ErrOR<Pos<Variable>> {
@@ -0,0 +1,60 @@
+use crate::prelude::*;
+use std::path::{Component, PathBuf};
+/// Supplies source text to the CLI, `import` and the language server.
+pub trait SourceProvider {
+  /// Returns the text of `file`, failing with `FileTooLarge` when it exceeds `limit` bytes.
+  ///
+  /// # Errors
+  /// When `file` cannot be read or is too large.
+  fn read(&self, file: &str, limit: u64) -> io::Result<String>;
+  /// Returns the name of the file that `from` refers to as `path`.
+  ///
+  /// # Errors
+  /// When `path` does not name a file.
+  fn resolve(&self, from: &str, path: &str) -> io::Result<String>;
+}
+/// Reads sources from disk and names them by canonical path.
+#[derive(Debug, Clone, Copy, Default)]
+#[non_exhaustive]
+pub struct FileSystem;
+impl SourceProvider for FileSystem {
+  #[inline]
+  fn read(&self, file: &str, limit: u64) -> io::Result<String> {
+    if fs::metadata(file)?.len() > limit {
+      return Err(io::ErrorKind::FileTooLarge.into());
+    }
+    fs::read_to_string(file)
+  }
+  #[inline]
+  fn resolve(&self, from: &str, path: &str) -> io::Result<String> {
+    let folder = Path::new(from).parent().unwrap_or(Path::new("."));
+    Ok(folder.join(path).canonicalize()?.to_string_lossy().to_string())
+  }
+}
+/// An in-memory module tree keyed by path; imports are joined lexically.
+impl SourceProvider for HashMap<String, String> {
+  #[inline]
+  fn read(&self, file: &str, limit: u64) -> io::Result<String> {
+    let text = self.get(file).ok_or(io::ErrorKind::NotFound)?;
+    if text.len() as u64 > limit {
+      return Err(io::ErrorKind::FileTooLarge.into());
+    }
+    Ok(text.clone())
+  }
+  #[inline]
+  fn resolve(&self, from: &str, path: &str) -> io::Result<String> {
+    let mut joined = PathBuf::new();
+    for component in Path::new(from).parent().unwrap_or(Path::new("")).join(path).components() {
+      match component {
+        Component::CurDir => (),
+        Component::ParentDir if joined.file_name().is_some() => {
+          joined.pop();
+        }
+        Component::ParentDir | Component::Normal(_) | Component::Prefix(_) | Component::RootDir => {
+          joined.push(component);
+        }
+      }
+    }
+    Ok(joined.to_string_lossy().to_string())
+  }
+}
@@ -0,0 +1,503 @@
+section .text
+
+; ctrl_c_handler
+ctrl_c_handler:
+  push rbp
+  mov rbp, rsp
+  sub rsp, 0x30
+  mov qword ptr [rbp - 0x8], rcx
+  lea rcx, [.L49]
+  call PRINT_E
+  lea rcx, [.L22]
+  call PRINT_E
+  mov rax, qword ptr [rbp - 0x8]
+  lea rcx, [.L50]
+  lea rdx, [.L51]
+  mov r8d, 0x1
+  cmp al, r8b
+  cmove rcx, rdx
+  lea rdx, [.L52]
+  mov r8d, 0x2
+  cmp al, r8b
+  cmove rcx, rdx
+  lea rdx, [.L53]
+  mov r8d, 0x5
+  cmp al, r8b
+  cmove rcx, rdx
+  lea rdx, [.L54]
+  mov r8d, 0x6
+  cmp al, r8b
+  cmove rcx, rdx
+  call PRINT_E
+  lea rcx, [.L31]
+  call PRINT_E
+  xor eax, eax
+  add rsp, 0x30
+  pop rbp
+  ret
+.L55:
+
+; seh_handler
+seh_handler:
+  push rbp
+  mov rbp, rsp
+  sub rsp, 0x30
+  xor esi, esi
+  mov rax, rcx
+  add rax, 0x10
+  mov rax, qword ptr [rax]
+  lea r8, [.L14]
+  mov r9d, dword ptr [r8]
+  mov r10, r8
+  sub r10, r9
+  sub rax, r10
+  add r8, 0x4
+  mov r9d, dword ptr [r8]
+.L18:
+  test r9, r9
+  je .L19
+  dec r9
+  add r8, 0x4
+  mov r11d, dword ptr [r8]
+  add r8, 0x4
+  mov edx, dword ptr [r8]
+  add r8, 0x4
+  cmp rax, r11
+  jb .L18
+  cmp rax, rdx
+  jae .L18
+  mov esi, dword ptr [r8]
+  add rsi, r10
+.L19:
+  mov rbx, qword ptr [rcx]
+  mov rcx, qword ptr [STD_E]
+  lea rdx, [.L21]
+  mov r8d, 0x20
+  lea r9, [rbp - 0x8]
+  xor eax, eax
+  mov qword ptr [rsp + 0x20], rax
+  call qword ptr [kernel32.dll!WriteFile]
+  mov rcx, qword ptr [STD_E]
+  lea rdx, [.L22]
+  mov r8d, 0x3
+  lea r9, [rbp - 0x8]
+  xor eax, eax
+  mov qword ptr [rsp + 0x20], rax
+  call qword ptr [kernel32.dll!WriteFile]
+  mov eax, 0xc00000fd
+  lea rdx, [.L23]
+  mov r8d, 0xd
+  lea rdi, [.L24]
+  cmp rbx, rax
+  je .L12
+  mov eax, 0xc0000005
+  lea rdx, [.L25]
+  mov r8d, 0xf
+  lea rdi, [.L26]
+  cmp rbx, rax
+  je .L12
+  mov eax, 0xc0000094
+  lea rdx, [.L27]
+  mov r8d, 0x10
+  lea rdi, [.L28]
+  cmp rbx, rax
+  je .L12
+  lea rdx, [.L29]
+  mov r8d, 0x11
+  lea rdi, [.L30]
+.L12:
+  mov rcx, qword ptr [STD_E]
+  lea r9, [rbp - 0x8]
+  xor eax, eax
+  mov qword ptr [rsp + 0x20], rax
+  call qword ptr [kernel32.dll!WriteFile]
+  test rsi, rsi
+  je .L15
+  mov rcx, qword ptr [STD_E]
+  lea rdx, [.L20]
+  mov r8d, 0x22
+  lea r9, [rbp - 0x8]
+  xor eax, eax
+  mov qword ptr [rsp + 0x20], rax
+  call qword ptr [kernel32.dll!WriteFile]
+  mov rcx, rsi
+  call STR_LEN
+  mov r8, rax
+  mov rcx, qword ptr [STD_E]
+  mov rdx, rsi
+  lea r9, [rbp - 0x8]
+  xor eax, eax
+  mov qword ptr [rsp + 0x20], rax
+  call qword ptr [kernel32.dll!WriteFile]
+.L15:
+  mov rcx, qword ptr [STD_E]
+  lea rdx, [.L31]
+  mov r8d, 0x22
+  lea r9, [rbp - 0x8]
+  xor eax, eax
+  mov qword ptr [rsp + 0x20], rax
+  call qword ptr [kernel32.dll!WriteFile]
+  mov rcx, qword ptr [STD_E]
+  lea rdx, [.L32]
+  mov r8d, 0xa3
+  lea r9, [rbp - 0x8]
+  xor eax, eax
+  mov qword ptr [rsp + 0x20], rax
+  call qword ptr [kernel32.dll!WriteFile]
+  mov rcx, qword ptr [STD_E]
+  mov rdx, rdi
+  mov r8d, 0x5
+  lea r9, [rbp - 0x8]
+  xor eax, eax
+  mov qword ptr [rsp + 0x20], rax
+  call qword ptr [kernel32.dll!WriteFile]
+  mov rcx, qword ptr [STD_E]
+  lea rdx, [.L33]
+  mov r8d, 0x2
+  lea r9, [rbp - 0x8]
+  xor eax, eax
+  mov qword ptr [rsp + 0x20], rax
+  call qword ptr [kernel32.dll!WriteFile]
+.L13:
+  mov rcx, rbx
+  call qword ptr [kernel32.dll!ExitProcess]
+
+; win_handler
+win_handler:
+  mov rbp, rsp
+  sub rsp, 0x70
+  call qword ptr [kernel32.dll!GetLastError]
+  mov rdi, rax
+  mov ecx, 0x1300
+  xor edx, edx
+  mov r8, rdi
+  xor r9d, r9d
+  lea rax, [rbp - 0x10]
+  mov qword ptr [rsp + 0x20], rax
+  mov qword ptr [rsp + 0x28], rdx
+  mov qword ptr [rsp + 0x30], rdx
+  call qword ptr [kernel32.dll!FormatMessageW]
+  test rax, rax
+  je .L35
+  mov rcx, qword ptr [rbp - 0x10]
+  mov edx, 0xfde9
+  call U16TO8
+  mov qword ptr [rbp - 0x18], rax
+  mov rcx, qword ptr [STD_E]
+  lea rdx, [.L21]
+  mov r8d, 0x20
+  lea r9, [rbp - 0x8]
+  xor eax, eax
+  mov qword ptr [rsp + 0x20], rax
+  call qword ptr [kernel32.dll!WriteFile]
+  mov rcx, qword ptr [STD_E]
+  lea rdx, [.L46]
+  mov r8d, 0x14
+  lea r9, [rbp - 0x8]
+  xor eax, eax
+  mov qword ptr [rsp + 0x20], rax
+  call qword ptr [kernel32.dll!WriteFile]
+  mov rcx, qword ptr [rbp - 0x18]
+  call PRINT_E
+  mov rcx, qword ptr [STD_E]
+  lea rdx, [.L31]
+  mov r8d, 0x22
+  lea r9, [rbp - 0x8]
+  xor eax, eax
+  mov qword ptr [rsp + 0x20], rax
+  call qword ptr [kernel32.dll!WriteFile]
+  mov rcx, qword ptr [STD_E]
+  lea rdx, [.L32]
+  mov r8d, 0xa3
+  lea r9, [rbp - 0x8]
+  xor eax, eax
+  mov qword ptr [rsp + 0x20], rax
+  call qword ptr [kernel32.dll!WriteFile]
+  mov rcx, qword ptr [STD_E]
+  lea rdx, [.L47]
+  mov r8d, 0x1
+  lea r9, [rbp - 0x8]
+  xor eax, eax
+  mov qword ptr [rsp + 0x20], rax
+  call qword ptr [kernel32.dll!WriteFile]
+  mov r9, rdi
+  lea rax, [rbp - 0x20]
+  add rax, 0x4
+  xor ecx, ecx
+  mov byte ptr [rax], cl
+  mov r8d, 0x4
+.L44:
+  dec rax
+  mov rdx, r9
+  mov ecx, 0xf
+  and rdx, rcx
+  mov ecx, 0xa
+  cmp rdx, rcx
+  jb .L43
+  add rdx, 0x37
+  jmp .L45
+.L43:
+  add rdx, 0x30
+.L45:
+  mov byte ptr [rax], dl
+  shr r9, 0x4
+  dec r8
+  jne .L44
+  mov rcx, qword ptr [STD_E]
+  lea rdx, [rbp - 0x20]
+  mov r8d, 0x4
+  lea r9, [rbp - 0x8]
+  xor eax, eax
+  mov qword ptr [rsp + 0x20], rax
+  call qword ptr [kernel32.dll!WriteFile]
+  mov rcx, qword ptr [STD_E]
+  lea rdx, [.L33]
+  mov r8d, 0x2
+  lea r9, [rbp - 0x8]
+  xor eax, eax
+  mov qword ptr [rsp + 0x20], rax
+  call qword ptr [kernel32.dll!WriteFile]
+.L35:
+  mov rcx, qword ptr [rbp - 0x10]
+  call qword ptr [kernel32.dll!LocalFree]
+  mov rcx, rdi
+  call qword ptr [kernel32.dll!ExitProcess]
+.L48:
+
+; main (hello.jspl:2:1)
+main:
+  push rbp
+  mov rbp, rsp
+  sub rsp, 0x30
+  mov ecx, 0xfde9
+  call qword ptr [kernel32.dll!SetConsoleCP]
+  mov ecx, 0xfde9
+  call qword ptr [kernel32.dll!SetConsoleOutputCP]
+  call qword ptr [kernel32.dll!GetProcessHeap]
+  test rax, rax
+  je win_handler
+  mov qword ptr [HEAP], rax
+  lea rcx, [ctrl_c_handler]
+  xor edx, edx
+  inc rdx
+  call qword ptr [kernel32.dll!SetConsoleCtrlHandler]
+  test rax, rax
+  je win_handler
+  mov ecx, 0xfffffff6
+  call qword ptr [kernel32.dll!GetStdHandle]
+  xor ecx, ecx
+  dec rcx
+  xor edx, edx
+  cmp rax, rcx
+  cmove rax, rdx
+  mov qword ptr [STD_I], rax
+  mov ecx, 0xfffffff5
+  call qword ptr [kernel32.dll!GetStdHandle]
+  xor ecx, ecx
+  dec rcx
+  xor edx, edx
+  cmp rax, rcx
+  cmove rax, rdx
+  mov qword ptr [STD_O], rax
+  mov ecx, 0xfffffff4
+  call qword ptr [kernel32.dll!GetStdHandle]
+  xor ecx, ecx
+  dec rcx
+  xor edx, edx
+  cmp rax, rcx
+  cmove rax, rdx
+  mov qword ptr [STD_E], rax
+  ; hello.jspl:1:1
+  lea rcx, [.L57]
+  call PRINT
+  xor ecx, ecx
+  mov qword ptr [rbp - 0x8], rcx
+  mov rax, qword ptr [rbp - 0x8]
+  mov ecx, dword ptr [LEAK_CNT]
+  test rcx, rcx
+  cmove rcx, rax
+  je .L60
+  lea rcx, [.L21]
+  call PRINT_E
+  lea rcx, [.L61]
+  call PRINT_E
+  lea rcx, [.L31]
+  call PRINT_E
+  lea rcx, [.L32]
+  call PRINT_E
+  lea rcx, [.L62]
+  call PRINT_E
+  mov ecx, dword ptr [LEAK_CNT]
+.L60:
+  call qword ptr [kernel32.dll!ExitProcess]
+.L63:
+
+; STR_LEN
+STR_LEN:
+  push rbp
+  mov rbp, rsp
+  sub rsp, 0x30
+  mov qword ptr [rbp - 0x8], rdi
+  mov rdx, rcx
+  mov rdi, rdx
+  xor ecx, ecx
+  dec rcx
+  xor eax, eax
+  cld
+  repne scasb
+  sub rdi, rdx
+  dec rdi
+  mov rax, rdi
+  mov rdi, qword ptr [rbp - 0x8]
+  add rsp, 0x30
+  pop rbp
+  ret
+.L17:
+
+; PRINT_E
+PRINT_E:
+  push rbp
+  mov rbp, rsp
+  sub rsp, 0x20
+  mov rdx, qword ptr [STD_E]
+  call PRINT_N
+  add rsp, 0x20
+  pop rbp
+  ret
+.L40:
+
+; PRINT_N
+PRINT_N:
+  push rbp
+  mov rbp, rsp
+  sub rsp, 0x40
+  test rdx, rdx
+  je .L38
+  mov qword ptr [rbp - 0x10], rcx
+  mov qword ptr [rbp - 0x8], rdx
+  call STR_LEN
+  mov r8, rax
+  mov rcx, qword ptr [rbp - 0x8]
+  mov rdx, qword ptr [rbp - 0x10]
+  lea r9, [rbp - 0x8]
+  xor eax, eax
+  mov qword ptr [rsp + 0x20], rax
+  call qword ptr [kernel32.dll!WriteFile]
+  test rax, rax
+  je win_handler
+.L38:
+  add rsp, 0x40
+  pop rbp
+  ret
+.L39:
+
+; U16TO8
+U16TO8:
+  push rbp
+  mov rbp, rsp
+  sub rsp, 0x60
+  mov qword ptr [rbp - 0x10], rdi
+  mov qword ptr [rbp - 0x18], rsi
+  mov qword ptr [rbp - 0x20], rbx
+  mov rdi, rcx
+  mov dword ptr [rbp - 0x4], edx
+  mov ecx, edx
+  xor edx, edx
+  mov r8, rdi
+  mov r9d, 0xffffffff
+  mov qword ptr [rsp + 0x20], rdx
+  mov qword ptr [rsp + 0x28], rdx
+  mov qword ptr [rsp + 0x30], rdx
+  mov qword ptr [rsp + 0x38], rdx
+  call qword ptr [kernel32.dll!WideCharToMultiByte]
+  test rax, rax
+  je win_handler
+  mov rsi, rax
+  mov rcx, qword ptr [HEAP]
+  mov edx, 0x8
+  mov r8, rsi
+  call qword ptr [kernel32.dll!HeapAlloc]
+  inc dword ptr [LEAK_CNT]
+  mov rbx, rax
+  mov ecx, dword ptr [rbp - 0x4]
+  xor edx, edx
+  mov r8, rdi
+  mov r9d, 0xffffffff
+  mov rax, rbx
+  mov qword ptr [rsp + 0x20], rax
+  mov rax, rsi
+  mov qword ptr [rsp + 0x28], rax
+  mov qword ptr [rsp + 0x30], rdx
+  mov qword ptr [rsp + 0x38], rdx
+  call qword ptr [kernel32.dll!WideCharToMultiByte]
+  test rax, rax
+  je win_handler
+  add rax, rbx
+  dec rax
+  xor ecx, ecx
+  mov byte ptr [rax], cl
+  dec rax
+  mov byte ptr [rax], cl
+  mov rax, rbx
+  mov rdi, qword ptr [rbp - 0x10]
+  mov rsi, qword ptr [rbp - 0x18]
+  mov rbx, qword ptr [rbp - 0x20]
+  add rsp, 0x60
+  pop rbp
+  ret
+.L42:
+
+; PRINT
+PRINT:
+  push rbp
+  mov rbp, rsp
+  sub rsp, 0x20
+  mov rdx, qword ptr [STD_O]
+  call PRINT_N
+  add rsp, 0x20
+  pop rbp
+  ret
+.L59:
+
+section .data
+.L5: db 0x0
+
+section .rdata
+.L14: ; source map
+.L20: db "\n|-----------------------------\n| ", 0
+.L21: db "\n╭- InternalError ------------", 0
+.L22: db "\n| ", 0
+.L23: db "StackOverflow", 0
+.L24: db "R00FD", 0
+.L25: db "AccessViolation", 0
+.L26: db "R0005", 0
+.L27: db "Division by zero", 0
+.L28: db "R0094", 0
+.L29: db "ExceptionOccurred", 0
+.L30: db "R0000", 0
+.L31: db "\n╰-----------------------------\n", 0
+.L32: db "\nInternal Jsonpiler error.\nThis is a compiler bug.\n\nReport:\nhttps://github.com/HAL-G1THuB/jsonpiler/issues/new\n\nInclude:\n- source\n- version: 0.10.4\n- error code: `", 0
+.L33: db "`\n", 0
+.L46: db "\n| WinApiError:\n|   ", 0
+.L47: db "W", 0
+.L49: db "\n╭- Exit ----------------------", 0
+.L50: db "Ctrl+C", 0
+.L51: db "Ctrl+Break", 0
+.L52: db "Console closed", 0
+.L53: db "User logged off", 0
+.L54: db "System shutdown", 0
+.L57: db "Hello😃 Jsonp¡ler❕\n", 0
+.L61: db "\n| Memory leak detected", 0
+.L62: db "LEAK`\n", 0
+.L56: db "hello.jspl:1:1", 0
+
+section .bss
+FLAG_GUI: resb 0x1 ; align 1
+HEAP: resb 0x8 ; align 8
+STD_O: resb 0x8 ; align 8
+STD_E: resb 0x8 ; align 8
+STD_I: resb 0x8 ; align 8
+LEAK_CNT: resb 0x4 ; align 4
+
+; imports
+; kernel32.dll: WriteFile, ExitProcess, WideCharToMultiByte, HeapAlloc, GetLastError, FormatMessageW, LocalFree, SetConsoleCP, SetConsoleOutputCP, GetProcessHeap, SetConsoleCtrlHandler, GetStdHandle
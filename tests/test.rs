@@ -13,7 +13,6 @@ mod tests {
       let _: io::Result<()> = fs::remove_dir_all(&self.0);
     }
   }
-  #[cfg(all(target_os = "windows", target_arch = "x86_64"))]
   fn copied_examples_dir() -> (PathBuf, TempDirGuard) {
     let src = Path::new(env!("CARGO_MANIFEST_DIR")).join("examples/jspl");
     let stamp = SystemTime::now()
@@ -34,11 +33,13 @@ mod tests {
     }
     (dst, dir)
   }
-  #[cfg(all(target_os = "windows", target_arch = "x86_64"))]
-  fn run_example(examples_dir: &Path, file: &str) -> i32 {
+  #[cfg(all(any(target_os = "windows", target_os = "linux"), target_arch = "x86_64"))]
+  fn run_example(examples_dir: &Path, file: &str, options: &[&str]) -> i32 {
     use jsonpiler::Jsonpiler;
     let example_path = examples_dir.join(file);
-    let args = vec!["jsonpiler.exe".into(), example_path.to_string_lossy().to_string()];
+    let mut args = vec!["jsonpiler.exe".to_owned()];
+    args.extend(options.iter().map(|option| (*option).to_owned()));
+    args.push(example_path.to_string_lossy().to_string());
     let mut jsonpiler = Jsonpiler::new(false);
     match jsonpiler.main(args) {
       Ok(code) => code,
@@ -51,16 +52,21 @@ mod tests {
     let (examples_dir, _dir) = copied_examples_dir();
     let cases = [
       ("arithmetic.jspl", 9),
+      ("array.jspl", 40),
+      ("closure.jspl", 32),
       ("counter.jspl", 0),
       ("global_and_local.jspl", 1000),
       ("hello.jspl", 0),
       ("import_and_assert.jspl", 11),
       ("is_prime.jspl", 0),
       ("lcm.jspl", 36),
+      ("map.jspl", 22),
       ("or_nand_xor.jspl", 0),
+      ("shared_str.jspl", 22),
+      ("struct.jspl", 49),
     ];
     for (file, expected) in cases {
-      let code = run_example(&examples_dir, file);
+      let code = run_example(&examples_dir, file, &[]);
       assert_eq!(code, expected, "unexpected exit code for {file}");
     }
   }
@@ -68,15 +74,1056 @@ mod tests {
   #[test]
   fn run_random_example_and_check_exit_code_range() {
     let (examples_dir, _dir) = copied_examples_dir();
-    let code = run_example(&examples_dir, "random.jspl");
+    let code = run_example(&examples_dir, "random.jspl", &[]);
     assert!(
       (0..100).contains(&code),
       "random.jspl exit code is out of expected range [0, 99]: {code}"
     );
   }
-  #[cfg(not(all(target_os = "windows", target_arch = "x86_64")))]
+  #[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+  #[test]
+  fn run_jspl_examples_on_linux_and_check_exit_codes() {
+    let (examples_dir, _dir) = copied_examples_dir();
+    let cases = [
+      ("arithmetic.jspl", 9),
+      ("array.jspl", 40),
+      ("closure.jspl", 32),
+      ("counter.jspl", 0),
+      ("global_and_local.jspl", 1000 & 0xFF),
+      ("hello.jspl", 0),
+      ("import_and_assert.jspl", 11),
+      ("is_prime.jspl", 0),
+      ("lcm.jspl", 36),
+      ("map.jspl", 22),
+      ("or_nand_xor.jspl", 0),
+      ("shared_str.jspl", 22),
+      ("struct.jspl", 49),
+    ];
+    for (file, expected) in cases {
+      let code = run_example(&examples_dir, file, &["--target", "x86_64-linux"]);
+      assert_eq!(code, expected, "unexpected exit code for {file}");
+      let release_code = run_example(&examples_dir, file, &["release", "--target", "x86_64-linux"]);
+      assert_eq!(release_code, expected, "unexpected exit code for {file} (release)");
+    }
+    let random = run_example(&examples_dir, "random.jspl", &["--target", "x86_64-linux"]);
+    assert!((0..100).contains(&random), "random.jspl exit code is out of range [0, 99]: {random}");
+  }
+  #[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+  #[test]
+  fn array_and_map_errors_are_reported_at_runtime_and_compile_time() {
+    use std::{collections::HashMap, process::Command};
+    let (examples_dir, _dir) = copied_examples_dir();
+    for (name, source, message) in [
+      ("bounds", "let(xs = [1, 2])\nget(xs, 2)", "Index out of bounds"),
+      ("negative", "let(xs = [1, 2])\nset(xs, -1, 3)", "Index out of bounds"),
+      ("empty", "let(xs = Array[Str]())\npop(xs)", "Pop from an empty array"),
+      ("missing", "let(m = Map[Str, Int]())\nget(m, \"a\")", "Key not found"),
+    ] {
+      let file = examples_dir.join(format!("{name}.jspl"));
+      fs::write(&file, source).expect("failed to write source");
+      let output = Command::new(env!("CARGO_BIN_EXE_jsonpiler"))
+        .args(["--target", "x86_64-linux"])
+        .arg(&file)
+        .output()
+        .expect("failed to run jsonpiler");
+      assert_eq!(output.status.code(), Some(1), "unexpected exit code for {name}");
+      let stderr = String::from_utf8_lossy(&output.stderr);
+      assert!(stderr.contains(message), "unexpected error for {name}: {stderr}");
+    }
+    let compile = |source: &str| {
+      jsonpiler::Jsonpiler::new(false).compile_source(
+        "main.jspl",
+        source.to_owned(),
+        false,
+        HashMap::new(),
+      )
+    };
+    for source in [
+      "let(xs = [1])\npush(xs, \"a\")",
+      "let(xs = [1, \"a\"])",
+      "let(m = Map[Str, Int]())\ninsert(m, \"a\", \"b\")",
+      "let(m = Map[Str, Int](1))",
+    ] {
+      assert!(compile(source).is_err(), "{source} must be rejected");
+    }
+  }
+  #[test]
+  fn struct_misuse_is_rejected_with_diagnostic_codes() {
+    use jsonpiler::api::DiagnosticCode;
+    use std::collections::HashMap;
+    let header = "struct(Point, { x: Int; y: Int })\nlet(p = Point(1, 2))\n";
+    for (source, code) in [
+      ("p.z", DiagnosticCode::UndefinedField),
+      ("p.x = \"a\"", DiagnosticCode::TypeError),
+      ("Point(1, \"a\")", DiagnosticCode::TypeError),
+      ("struct(Point, { z: Int })", DiagnosticCode::DuplicateName),
+    ] {
+      let errors = jsonpiler::Jsonpiler::new(false)
+        .compile_source("main.jspl", format!("{header}{source}"), false, HashMap::new())
+        .expect_err(source);
+      assert!(errors.iter().any(|err| err.code == code), "{source} must report {code}");
+    }
+  }
+  #[test]
+  fn function_value_misuse_is_rejected_with_diagnostic_codes() {
+    use jsonpiler::api::DiagnosticCode;
+    use std::collections::HashMap;
+    let header = "let(f = fn({ n: Int }, Int, n + 1))\nlet(x = 1)\n";
+    for (source, code) in [
+      ("f(\"a\")", DiagnosticCode::TypeError),
+      ("x(1)", DiagnosticCode::TypeError),
+      ("f = fn({ s: Str }, Int, len(s))", DiagnosticCode::TypeError),
+      ("fn({ n: Int }, Str, n)", DiagnosticCode::TypeError),
+      ("let(fs = [f])", DiagnosticCode::UnsupportedType),
+    ] {
+      let errors = jsonpiler::Jsonpiler::new(false)
+        .compile_source("main.jspl", format!("{header}{source}"), false, HashMap::new())
+        .expect_err(source);
+      assert!(errors.iter().any(|err| err.code == code), "{source} must report {code}");
+    }
+    let shadowing = "let(p = \"q\")\nlet(g = fn({ n: Int }, Int, { let(p = 3); p + n }))\ng(1)";
+    jsonpiler::Jsonpiler::new(false)
+      .compile_source("main.jspl", shadowing.to_owned(), false, HashMap::new())
+      .expect("a local of a function value may shadow an enclosing variable");
+  }
+  #[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+  #[test]
+  fn build_linux_executable_has_elf_header() {
+    use jsonpiler::Jsonpiler;
+    let (examples_dir, _dir) = copied_examples_dir();
+    let source = examples_dir.join("hello.jspl").to_string_lossy().to_string();
+    let args = ["jsonpiler", "build", "--target", "x86_64-linux", &source].map(String::from);
+    let code = Jsonpiler::new(false).main(args).expect("failed to build hello.jspl");
+    assert_eq!(code, 0);
+    let elf = fs::read(examples_dir.join("hello")).expect("missing ELF output");
+    assert_eq!(&elf[..4], b"\x7FELF");
+    assert_eq!(elf[4], 2, "ELF64 expected");
+    assert_eq!(u16::from_le_bytes([elf[0x12], elf[0x13]]), 0x3E, "x86-64 machine expected");
+    let entry = u64::from_le_bytes(elf[0x18..0x20].try_into().expect("entry point"));
+    assert!(entry > 0x40_1000, "entry point must be inside .text: {entry:#x}");
+  }
+  #[test]
+  fn emit_asm_matches_golden_listing() {
+    use jsonpiler::Jsonpiler;
+    let (examples_dir, _dir) = copied_examples_dir();
+    let source = examples_dir.join("hello.jspl").to_string_lossy().to_string();
+    let args = ["jsonpiler", "--emit", "asm", &source].map(String::from);
+    let code = Jsonpiler::new(false).main(args).expect("failed to emit hello.asm");
+    assert_eq!(code, 0);
+    let listing = fs::read_to_string(examples_dir.join("hello.asm")).expect("missing listing");
+    let golden = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden/hello.asm");
+    if env::var_os("UPDATE_GOLDEN").is_some() {
+      fs::write(&golden, &listing).expect("failed to update golden listing");
+    }
+    let expected = fs::read_to_string(&golden).expect("missing tests/golden/hello.asm");
+    assert!(
+      listing == expected.replace("\r\n", "\n"),
+      "hello.asm differs from tests/golden/hello.asm; rerun with UPDATE_GOLDEN=1 to accept"
+    );
+  }
+  #[test]
+  fn cli_options_choose_output_paths_and_deny_warnings() {
+    use jsonpiler::Jsonpiler;
+    use std::process::Command;
+    let (examples_dir, _dir) = copied_examples_dir();
+    let run = |args: &[&str]| {
+      let mut argv = vec!["jsonpiler".to_owned()];
+      argv.extend(args.iter().map(|arg| (*arg).to_owned()));
+      Jsonpiler::new(false).main(argv)
+    };
+    let source = examples_dir.join("hello.jspl").to_string_lossy().to_string();
+    let out_dir = examples_dir.join("out");
+    fs::create_dir_all(&out_dir).expect("failed to create output dir");
+    let out = out_dir.to_string_lossy().to_string();
+    let listing = out_dir.join("listing.s").to_string_lossy().to_string();
+    run(&["--emit", "asm", "-o", &listing, &source]).expect("failed to emit to -o");
+    assert!(Path::new(&listing).exists());
+    assert!(!examples_dir.join("hello.asm").exists(), "-o must replace the default path");
+    run(&[&source, "--out-dir", &out, "--emit", "map"]).expect("failed to emit map");
+    let map = fs::read_to_string(out_dir.join("hello.map.json")).expect("missing source map");
+    assert!(map.contains("\"ranges\""), "{map}");
+    assert!(!out_dir.join("hello.exe").exists(), "--emit map must not write the executable");
+    let exe = out_dir.join("app.exe").to_string_lossy().to_string();
+    run(&["build", "--pdb", "-o", &exe, &source]).expect("failed to build to -o");
+    assert!(!out_dir.join("app.map.json").exists(), "the source map must be opt-in");
+    run(&["build", "--release", "--pdb", "--source-map", "-o", &exe, &source])
+      .expect("failed to build to -o");
+    for side in ["app.exe", "app.map.json", "app.pdb"] {
+      assert!(out_dir.join(side).exists(), "missing {side}");
+    }
+    for (verbose, expected) in [(None, ""), (Some("-v"), "Peephole optimizer saved")] {
+      let output = Command::new(env!("CARGO_BIN_EXE_jsonpiler"))
+        .args(["build", "--release", "-o", &exe, &source].into_iter().chain(verbose))
+        .output()
+        .expect("failed to run jsonpiler");
+      let stderr = String::from_utf8_lossy(&output.stderr);
+      assert!(stderr.starts_with(expected) && (verbose.is_some() || stderr.is_empty()), "{stderr}");
+    }
+    let both = run(&["build", "-o", &exe, "--out-dir", &out, &source]);
+    assert!(both.expect_err("-o with --out-dir must fail").contains("`--out-dir`"));
+    let stray = run(&["build", &source, "extra"]);
+    assert!(stray.expect_err("a stray argument must fail").contains("`extra`"));
+    let unknown = run(&["build", "--bogus", &source]);
+    assert!(unknown.expect_err("an unknown option must fail").contains("`--bogus`"));
+    let warn_path = examples_dir.join("warn.jspl");
+    fs::write(&warn_path, "let(x = 1)\n0\n").expect("failed to write warn.jspl");
+    let warn = warn_path.to_string_lossy().to_string();
+    run(&["build", &warn]).expect("warnings alone must not fail the build");
+    let denied = run(&["build", "-W", &warn]);
+    assert!(denied.expect_err("-W must fail on warnings").contains("`--deny-warnings`"));
+  }
+  #[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+  #[test]
+  fn exe_arguments_follow_double_dash() {
+    use jsonpiler::Jsonpiler;
+    let (examples_dir, _dir) = copied_examples_dir();
+    let source = examples_dir.join("hello.jspl").to_string_lossy().to_string();
+    let args = ["jsonpiler", "--target", "x86_64-linux", &source, "--release", "--", "-o", "x"];
+    let code = Jsonpiler::new(false).main(args.map(String::from)).expect("failed to run hello");
+    assert_eq!(code, 0);
+    assert!(!examples_dir.join("x").exists(), "arguments after `--` are not options");
+  }
+  #[test]
+  fn release_listing_has_no_redundant_sequences() {
+    use jsonpiler::Jsonpiler;
+    let (examples_dir, _dir) = copied_examples_dir();
+    for file in ["fib.jspl", "is_prime.jspl", "mandelbrot.jspl"] {
+      let source = examples_dir.join(file).to_string_lossy().to_string();
+      let args = ["jsonpiler", "release", "--emit", "asm", &source].map(String::from);
+      Jsonpiler::new(false).main(args).expect("failed to emit listing");
+      let listing =
+        fs::read_to_string(examples_dir.join(file).with_extension("asm")).expect("missing listing");
+      let lines = listing.lines().map(str::trim).collect::<Vec<&str>>();
+      for (&prev, &next) in lines.iter().zip(lines.iter().skip(1)) {
+        if let Some(target) = prev.strip_prefix("jmp ") {
+          assert_ne!(format!("{target}:"), next, "jump to the next label in {file}");
+        }
+        if prev.starts_with("xor ") {
+          assert_ne!(prev, next, "repeated clear in {file}");
+        }
+        if let Some((addr, reg)) =
+          prev.strip_prefix("mov qword ptr ").and_then(|rest| rest.split_once(", "))
+        {
+          assert_ne!(format!("mov {reg}, qword ptr {addr}"), next, "reload after store in {file}");
+        }
+      }
+    }
+  }
+  #[test]
+  fn release_listing_saves_allocated_registers() {
+    use jsonpiler::Jsonpiler;
+    let (examples_dir, _dir) = copied_examples_dir();
+    let source = examples_dir.join("mandelbrot.jspl").to_string_lossy().to_string();
+    let args = ["jsonpiler", "release", "--emit", "asm", &source].map(String::from);
+    Jsonpiler::new(false).main(args).expect("failed to emit listing");
+    let listing = fs::read_to_string(examples_dir.join("mandelbrot.asm")).expect("missing listing");
+    assert!(listing.contains("push r13"), "no register was allocated");
+    for function in listing.split("\n\n").filter(|function| function.contains("  ret")) {
+      let lines = function.lines().map(str::trim).collect::<Vec<&str>>();
+      for reg in ["r13", "r14", "r15"] {
+        let pushed = lines.contains(&format!("push {reg}").as_str());
+        let popped = lines.contains(&format!("pop {reg}").as_str());
+        assert_eq!(pushed, popped, "{reg} is not restored in:\n{function}");
+      }
+    }
+  }
+  #[test]
+  fn branches_in_rel8_range_use_short_forms() {
+    use jsonpiler::Jsonpiler;
+    use std::process::Command;
+    let (examples_dir, _dir) = copied_examples_dir();
+    for file in ["fib.jspl", "is_prime.jspl", "mandelbrot.jspl"] {
+      let source = examples_dir.join(file).to_string_lossy().to_string();
+      let args = ["jsonpiler", "build", &source].map(String::from);
+      Jsonpiler::new(false).main(args).expect("failed to build example");
+      let output = Command::new(env!("CARGO_BIN_EXE_jsonpiler"))
+        .arg("disasm")
+        .arg(examples_dir.join(file).with_extension("exe"))
+        .output()
+        .expect("failed to run disasm");
+      let listing = String::from_utf8_lossy(&output.stdout);
+      let mut forward_short = 0;
+      for line in listing.lines() {
+        let fields = line.split_whitespace().collect::<Vec<&str>>();
+        let Some((&mnemonic, &target)) = fields.iter().rev().nth(1).zip(fields.last()) else {
+          continue;
+        };
+        let Some(label) = target.strip_prefix("loc_").or_else(|| target.strip_prefix("sub_"))
+        else {
+          continue;
+        };
+        if !mnemonic.starts_with('j') {
+          continue;
+        }
+        let parse = |hex: &str| i64::from_str_radix(hex, 16).expect("hex address");
+        let len = i64::try_from(fields.len() - 3).expect("instruction length");
+        let disp = parse(label) - parse(fields[0]) - len;
+        if len == 2 {
+          forward_short += usize::from(disp > 0);
+        } else {
+          let short_disp = if disp > 0 { disp } else { disp + len - 2 };
+          assert!(
+            i8::try_from(short_disp).is_err(),
+            "rel32 branch in rel8 range in {file}: {line}"
+          );
+        }
+      }
+      assert!(forward_short > 0, "no forward short branch in {file}");
+    }
+  }
+  #[test]
+  fn disasm_decodes_every_instruction_of_built_executable() {
+    use jsonpiler::Jsonpiler;
+    use std::process::Command;
+    let (examples_dir, _dir) = copied_examples_dir();
+    for file in ["fib.jspl", "hello.jspl", "mandelbrot.jspl"] {
+      let source = examples_dir.join(file).to_string_lossy().to_string();
+      let args = ["jsonpiler", "build", &source].map(String::from);
+      Jsonpiler::new(false).main(args).expect("failed to build example");
+      let exe = examples_dir.join(file).with_extension("exe");
+      let output = Command::new(env!("CARGO_BIN_EXE_jsonpiler"))
+        .arg("disasm")
+        .arg(&exe)
+        .output()
+        .expect("failed to run disasm");
+      assert!(output.status.success(), "disasm failed for {file}");
+      let listing = String::from_utf8_lossy(&output.stdout);
+      assert!(listing.contains("\nentry:\n"), "missing entry label for {file}");
+      assert!(listing.contains("call qword ptr [kernel32.dll!ExitProcess]"), "missing import");
+      assert!(!listing.contains(" db 0x"), "undecoded bytes in {file}:\n{listing}");
+    }
+  }
+  #[test]
+  fn disasm_matches_reference_decoding_of_built_executables() {
+    use jsonpiler::Jsonpiler;
+    use std::{collections::HashSet, process::Command};
+    let (examples_dir, _dir) = copied_examples_dir();
+    let mut decoded = HashSet::new();
+    for file in ["is_prime.jspl", "lcm.jspl", "mandelbrot.jspl", "random.jspl"] {
+      let source = examples_dir.join(file).to_string_lossy().to_string();
+      let args = ["jsonpiler", "build", &source].map(String::from);
+      Jsonpiler::new(false).main(args).expect("failed to build example");
+      let output = Command::new(env!("CARGO_BIN_EXE_jsonpiler"))
+        .arg("disasm")
+        .arg(examples_dir.join(file).with_extension("exe"))
+        .output()
+        .expect("failed to run disasm");
+      for line in String::from_utf8_lossy(&output.stdout).lines() {
+        let fields = line.split_whitespace().skip(1).collect::<Vec<&str>>();
+        let len = fields.iter().take_while(|field| field.len() == 2).count();
+        decoded.insert((fields[..len].join(" "), fields[len..].join(" ")));
+      }
+    }
+    for (bytes, text) in [
+      ("55", "push rbp"),
+      ("48 89 e5", "mov rbp, rsp"),
+      ("48 81 ec 30 00 00 00", "sub rsp, 0x30"),
+      ("48 89 4d f8", "mov qword ptr [rbp - 0x8], rcx"),
+      ("48 89 44 24 20", "mov qword ptr [rsp + 0x20], rax"),
+      ("45 8b 08", "mov r9d, dword ptr [r8]"),
+      ("4d 29 ca", "sub r10, r9"),
+      ("41 3a c0", "cmp al, r8b"),
+      ("48 0f 44 ca", "cmove rcx, rdx"),
+      ("0f 9c c0", "setl al"),
+      ("f6 da", "neg dl"),
+      ("48 99", "cqo"),
+      ("48 f7 f9", "idiv rcx"),
+      ("48 0f af c1", "imul rax, rcx"),
+      ("48 c1 e9 09", "shr rcx, 0x9"),
+      ("48 b9 00 00 00 00 00 00 00 80", "mov rcx, 0x8000000000000000"),
+      ("f2 ae", "repne scasb"),
+      ("f3 a4", "rep movsb"),
+      ("f2 48 0f 2a c0", "cvtsi2sd xmm0, rax"),
+      ("f2 0f 11 45 f0", "movsd qword ptr [rbp - 0x10], xmm0"),
+      ("f2 0f 59 c1", "mulsd xmm0, xmm1"),
+      ("f2 0f 5c c1", "subsd xmm0, xmm1"),
+      ("66 0f 2e c1", "ucomisd xmm0, xmm1"),
+    ] {
+      assert!(decoded.contains(&(bytes.to_owned(), text.to_owned())), "missing `{bytes}  {text}`");
+    }
+  }
+  #[test]
+  fn build_dll_writes_export_directory() {
+    use jsonpiler::Jsonpiler;
+    let (examples_dir, _dir) = copied_examples_dir();
+    let source = examples_dir.join("lcm.jspl").to_string_lossy().to_string();
+    let args = ["jsonpiler", "build", "dll", &source].map(String::from);
+    let code = Jsonpiler::new(false).main(args).expect("failed to build lcm.dll");
+    assert_eq!(code, 0);
+    let dll = fs::read(examples_dir.join("lcm.dll")).expect("missing DLL output");
+    let u16_at = |offset: usize| u16::from_le_bytes([dll[offset], dll[offset + 1]]);
+    let u32_at =
+      |offset: usize| u32::from_le_bytes(dll[offset..offset + 4].try_into().expect("u32 field"));
+    let pe = u32_at(0x3C) as usize;
+    assert_eq!(&dll[pe..pe + 4], b"PE\0\0");
+    assert_eq!(u16_at(pe + 22) & 0x2000, 0x2000, "IMAGE_FILE_DLL expected");
+    let optional = pe + 24;
+    let sections = (0..usize::from(u16_at(pe + 6)))
+      .map(|idx| optional + usize::from(u16_at(pe + 20)) + idx * 40)
+      .map(|header| {
+        (&dll[header..header + 8], u32_at(header + 12), u32_at(header + 8), u32_at(header + 20))
+      })
+      .collect::<Vec<_>>();
+    let offset = |rva: u32| {
+      let (_, v_addr, _, r_ptr) = sections
+        .iter()
+        .find(|(_, v_addr, v_size, _)| (*v_addr..v_addr + v_size).contains(&rva))
+        .expect("RVA outside of every section");
+      (rva - v_addr + r_ptr) as usize
+    };
+    let (_, text_addr, text_size, _) = sections[0];
+    let text = text_addr..text_addr + text_size;
+    assert!(text.contains(&u32_at(optional + 16)), "DllMain must be in .text");
+    let export_dir = offset(u32_at(optional + 112));
+    assert!(sections.iter().any(|section| section.0 == b".edata\0\0"));
+    let c_str = |rva: u32| {
+      let start = offset(rva);
+      let len = dll[start..].iter().position(|&byte| byte == 0).expect("terminated name");
+      String::from_utf8_lossy(&dll[start..start + len]).to_string()
+    };
+    assert_eq!(c_str(u32_at(export_dir + 12)), "lcm.dll");
+    assert_eq!(u32_at(export_dir + 24), 2, "unexpected number of names");
+    let (eat, names, ordinals) = (
+      offset(u32_at(export_dir + 28)),
+      offset(u32_at(export_dir + 32)),
+      offset(u32_at(export_dir + 36)),
+    );
+    for (idx, export) in ["gcd", "lcm"].into_iter().enumerate() {
+      assert_eq!(c_str(u32_at(names + idx * 4)), export);
+      let ordinal = usize::from(u16_at(ordinals + idx * 2));
+      assert!(text.contains(&u32_at(eat + ordinal * 4)), "export {export} must point into .text");
+    }
+  }
+  #[test]
+  fn build_dll_passes_floats_in_xmm_and_rejects_heap_types() {
+    use jsonpiler::Jsonpiler;
+    use std::process::Command;
+    let (examples_dir, _dir) = copied_examples_dir();
+    let build = |name: &str, source: &str| {
+      let path = examples_dir.join(name);
+      fs::write(&path, source).expect("failed to write source");
+      let args = ["jsonpiler", "build", "dll", &path.to_string_lossy()].map(String::from);
+      Jsonpiler::new(false).main(args)
+    };
+    build("scale.jspl", "define(scale, { n: Int; x: Float }, Float, x * Float(n))\nexport(scale)")
+      .expect("failed to build scale.dll");
+    let output = Command::new(env!("CARGO_BIN_EXE_jsonpiler"))
+      .arg("disasm")
+      .arg(examples_dir.join("scale.dll"))
+      .output()
+      .expect("failed to run disasm");
+    let listing = String::from_utf8_lossy(&output.stdout);
+    assert!(listing.contains("movq rax, xmm1"), "`x` must be read from xmm1:\n{listing}");
+    assert!(listing.contains("movq xmm0, rax"), "the result must be returned in xmm0");
+    for (param, ret, body) in
+      [("s: Str", "Int", "len(s)"), ("a: Array[Int]", "Int", "len(a)"), ("n: Int", "Str", "\"n\"")]
+    {
+      let source = format!("define(f, {{ {param} }}, {ret}, {body})\nexport(f)");
+      let err = build("reject.jspl", &source).expect_err(&source);
+      assert!(err.contains("Unsupported type"), "{source}: {err}");
+    }
+  }
+  #[test]
+  fn base_relocations_are_empty_because_code_is_rip_relative() {
+    use jsonpiler::Jsonpiler;
+    let (examples_dir, _dir) = copied_examples_dir();
+    for (command, output) in [(&["build"][..], "fib.exe"), (&["build", "dll"][..], "lcm.dll")] {
+      let source = examples_dir.join(output).with_extension("jspl").to_string_lossy().to_string();
+      let args = ["jsonpiler"].iter().chain(command).map(|arg| (*arg).to_owned());
+      Jsonpiler::new(false).main(args.chain([source])).expect("failed to build example");
+      let image = fs::read(examples_dir.join(output)).expect("missing PE output");
+      let u16_at = |offset: usize| u16::from_le_bytes([image[offset], image[offset + 1]]);
+      let u32_at = |offset: usize| {
+        u32::from_le_bytes(image[offset..offset + 4].try_into().expect("u32 field"))
+      };
+      let u64_at = |offset: usize| {
+        u64::from_le_bytes(image[offset..offset + 8].try_into().expect("u64 field"))
+      };
+      let pe = u32_at(0x3C) as usize;
+      assert_eq!(u16_at(pe + 22) & 1, 0, "relocations must not be stripped in {output}");
+      let optional = pe + 24;
+      assert_eq!(u16_at(optional + 70) & 0x60, 0x60, "DYNAMICBASE and HIGH_ENTROPY_VA expected");
+      let (image_base, image_size) = (u64_at(optional + 24), u64::from(u32_at(optional + 56)));
+      let sections = (0..usize::from(u16_at(pe + 6)))
+        .map(|idx| optional + usize::from(u16_at(pe + 20)) + idx * 40)
+        .map(|header| {
+          (&image[header..header + 8], u32_at(header + 12), u32_at(header + 8), u32_at(header + 20))
+        })
+        .collect::<Vec<_>>();
+      let reloc = sections.iter().find(|section| section.0 == b".reloc\0\0").expect("no .reloc");
+      let (dir_rva, dir_size) = (u32_at(optional + 152), u32_at(optional + 156));
+      assert_eq!((dir_rva, dir_size), (reloc.1, reloc.2), "directory must cover .reloc");
+      let mut fixups = vec![];
+      let mut block = reloc.3 as usize;
+      let end = block + dir_size as usize;
+      while block < end {
+        let (page, size) = (u32_at(block), u32_at(block + 4) as usize);
+        assert!(page % 0x1000 == 0 && size >= 8 && size % 4 == 0, "malformed block in {output}");
+        for entry in (block + 8..block + size).step_by(2).map(u16_at) {
+          match entry >> 12 {
+            0 => (),
+            0xA => fixups.push(page + u32::from(entry & 0xFFF)),
+            kind => panic!("unexpected relocation type {kind} in {output}"),
+          }
+        }
+        block += size;
+      }
+      assert_eq!(block, end, "relocation blocks overrun the directory in {output}");
+      assert!(fixups.is_empty(), "unexpected base relocations in {output}: {fixups:x?}");
+      let absolute = image_base..image_base + image_size;
+      for (name, v_addr, v_size, r_ptr) in sections.iter().filter(|section| section.3 != 0) {
+        let step = if *name == b".text\0\0\0" { 1 } else { 8 };
+        for rva in (*v_addr..v_addr + v_size.saturating_sub(7)).step_by(step) {
+          let value = u64_at((rva - v_addr + r_ptr) as usize);
+          assert!(!absolute.contains(&value), "absolute address at {rva:#x} in {output}");
+        }
+      }
+    }
+  }
+  #[test]
+  fn resource_options_embed_icon_version_and_manifest() {
+    use jsonpiler::Jsonpiler;
+    let (examples_dir, _dir) = copied_examples_dir();
+    let image = b"\x89PNG\r\n\x1a\n icon bytes".to_vec();
+    let mut ico = vec![0, 0, 1, 0, 1, 0, 32, 32, 0, 0, 1, 0, 32, 0];
+    ico.extend_from_slice(&u32::try_from(image.len()).expect("icon size").to_le_bytes());
+    ico.extend_from_slice(&22u32.to_le_bytes());
+    ico.extend_from_slice(&image);
+    let icon_path = examples_dir.join("app.ico");
+    fs::write(&icon_path, &ico).expect("failed to write icon");
+    let source = examples_dir.join("hello.jspl").to_string_lossy().to_string();
+    let icon = icon_path.to_string_lossy().to_string();
+    let args = [
+      "jsonpiler",
+      "build",
+      "--icon",
+      &icon,
+      "--manifest",
+      "--version-info",
+      "FileVersion=1.2.3.4",
+      "--version-info",
+      "ProductName=Demo",
+      &source,
+    ];
+    Jsonpiler::new(false).main(args.map(String::from)).expect("failed to build hello.jspl");
+    let exe = fs::read(examples_dir.join("hello.exe")).expect("missing PE output");
+    let u16_at = |offset: usize| u16::from_le_bytes([exe[offset], exe[offset + 1]]);
+    let u32_at =
+      |offset: usize| u32::from_le_bytes(exe[offset..offset + 4].try_into().expect("u32 field"));
+    let optional = u32_at(0x3C) as usize + 24;
+    let section = (0..usize::from(u16_at(optional - 18)))
+      .map(|idx| optional + usize::from(u16_at(optional - 4)) + idx * 40)
+      .find(|header| u32_at(header + 12) == u32_at(optional + 128))
+      .expect("resource directory must start a section");
+    assert_eq!(&exe[section..section + 8], b".rsrc\0\0\0");
+    let (v_addr, r_ptr) = (u32_at(section + 12), u32_at(section + 20) as usize);
+    let entries = |dir: usize| {
+      let count = usize::from(u16_at(r_ptr + dir + 12) + u16_at(r_ptr + dir + 14));
+      (0..count)
+        .map(|idx| r_ptr + dir + 16 + idx * 8)
+        .map(|entry| (u32_at(entry), (u32_at(entry + 4) & 0x7FFF_FFFF) as usize))
+        .collect::<Vec<_>>()
+    };
+    let mut resources = vec![];
+    for (kind, ids) in entries(0) {
+      for (id, langs) in entries(ids) {
+        let (_, data_entry) = entries(langs)[0];
+        let offset = (u32_at(r_ptr + data_entry) - v_addr) as usize + r_ptr;
+        let data = exe[offset..offset + u32_at(r_ptr + data_entry + 4) as usize].to_vec();
+        resources.push((kind, id, data));
+      }
+    }
+    let kinds = resources.iter().map(|(kind, id, _)| (*kind, *id)).collect::<Vec<_>>();
+    assert_eq!(kinds, [(3, 1), (14, 1), (16, 1), (24, 1)], "unexpected resource tree");
+    assert_eq!(resources[0].2, image, "icon image must be copied verbatim");
+    assert_eq!(resources[1].2[..18], ico[..18], "group icon must mirror the .ico directory");
+    let version = &resources[2].2;
+    let fixed = version
+      .windows(4)
+      .position(|word| word == 0xFEEF_04BDu32.to_le_bytes())
+      .expect("missing VS_FIXEDFILEINFO");
+    assert_eq!(version[fixed + 8..fixed + 16], [2, 0, 1, 0, 4, 0, 3, 0], "file version 1.2.3.4");
+    for string in ["ProductName\0", "Demo\0"] {
+      let utf16 = string.encode_utf16().flat_map(u16::to_le_bytes).collect::<Vec<u8>>();
+      assert!(version.windows(utf16.len()).any(|window| *window == *utf16), "missing {string}");
+    }
+    let manifest = String::from_utf8_lossy(&resources[3].2);
+    assert!(manifest.contains("PerMonitorV2") && manifest.contains("UTF-8"), "{manifest}");
+  }
+  #[test]
+  fn gui_only_programs_use_windows_subsystem() {
+    use jsonpiler::Jsonpiler;
+    let (examples_dir, _dir) = copied_examples_dir();
+    let cases = [
+      ("gui_julia_mouse.jspl", None, 2),
+      ("square.jspl", None, 2),
+      ("hello.jspl", None, 3),
+      ("ping_pong.jspl", None, 3),
+      ("hello.jspl", Some("windows"), 2),
+      ("square.jspl", Some("console"), 3),
+    ];
+    for (file, forced, expected) in cases {
+      let source = examples_dir.join(file).to_string_lossy().to_string();
+      let mut args = vec!["jsonpiler", "build"];
+      if let Some(subsystem) = forced {
+        args.extend(["--subsystem", subsystem]);
+      }
+      args.push(&source);
+      Jsonpiler::new(false).main(args.into_iter().map(String::from)).expect("failed to build");
+      let exe = fs::read(examples_dir.join(file).with_extension("exe")).expect("missing PE output");
+      let optional =
+        u32::from_le_bytes(exe[0x3C..0x40].try_into().expect("e_lfanew")) as usize + 24;
+      let subsystem = u16::from_le_bytes([exe[optional + 68], exe[optional + 69]]);
+      assert_eq!(subsystem, expected, "{file} with {forced:?}");
+    }
+  }
+  #[test]
+  fn deterministic_builds_are_byte_identical() {
+    use jsonpiler::Jsonpiler;
+    use std::hash::{DefaultHasher, Hash as _, Hasher as _};
+    let (examples_dir, _dir) = copied_examples_dir();
+    for file in ["fib.jspl", "gui_julia_mouse.jspl", "bulls_and_cows.jspl"] {
+      let source = examples_dir.join(file).to_string_lossy().to_string();
+      let mut hashes = vec![];
+      for _ in 0..2 {
+        let args = ["jsonpiler", "build", "--deterministic", "--pdb", "--source-map", &source]
+          .map(String::from);
+        Jsonpiler::new(false).main(args).expect("failed to build");
+        let mut hasher = DefaultHasher::new();
+        for ext in ["exe", "pdb", "map.json"] {
+          fs::read(examples_dir.join(file).with_extension(ext))
+            .expect("missing output")
+            .hash(&mut hasher);
+        }
+        hashes.push(hasher.finish());
+      }
+      assert_eq!(hashes[0], hashes[1], "{file} differs between builds");
+      let exe = fs::read(examples_dir.join(file).with_extension("exe")).expect("missing PE output");
+      let coff = u32::from_le_bytes(exe[0x3C..0x40].try_into().expect("e_lfanew")) as usize + 4;
+      assert_eq!(exe[coff + 4..coff + 8], [0; 4], "{file} has a build-time timestamp");
+    }
+  }
+  #[test]
+  fn compile_source_resolves_imports_in_memory_and_reports_diagnostics() {
+    use jsonpiler::{
+      Jsonpiler,
+      api::{DiagnosticCode, Severity},
+    };
+    use std::collections::HashMap;
+    let src = Path::new(env!("CARGO_MANIFEST_DIR")).join("examples/jspl");
+    let read = |file: &str| fs::read_to_string(src.join(file)).expect("failed to read example");
+    let files: HashMap<String, String> =
+      ["fib.jspl", "lcm.jspl", "is_prime.jspl"].map(|file| (file.to_owned(), read(file))).into();
+    let main = read("import_and_assert.jspl");
+    let exe = Jsonpiler::new(false)
+      .compile_source("import_and_assert.jspl", main, false, files)
+      .expect("failed to compile in memory");
+    assert_eq!(exe.get(..2), Some(&b"MZ"[..]));
+    let missing = Jsonpiler::new(false)
+      .compile_source("main.jspl", "import(\"fib.jspl\", fib)".to_owned(), false, HashMap::new())
+      .expect_err("an unresolved import must fail");
+    assert_eq!(missing[0].severity, Severity::Error);
+    let errors = Jsonpiler::new(false)
+      .compile_source("main.jspl", "let(x = 1)\n  no_such_fn(x)".to_owned(), false, HashMap::new())
+      .expect_err("an undefined function must fail");
+    let error = &errors[0];
+    assert_eq!((error.file.as_str(), error.line, error.column), ("main.jspl", 2, 3), "{error:?}");
+    assert_eq!(error.code, DiagnosticCode::UndefinedFunc);
+    assert_eq!(error.code.as_str(), "UNDEFINED_FUNC");
+  }
+  #[test]
+  fn source_provider_supplies_in_memory_module_trees() {
+    use jsonpiler::Jsonpiler;
+    use std::collections::HashMap;
+    let files = [
+      ("app/main.jspl", "import(\"../lib/math.jspl\", quad)\nquad(3)"),
+      ("lib/math.jspl", "import(\"util.jspl\", twice)\ndefine(quad, { a: Int }, Int, twice(twice(a)))\nexport(quad)"),
+      ("lib/util.jspl", "define(twice, { a: Int }, Int, a * 2)\nexport(twice)"),
+    ]
+    .map(|(file, text)| (file.to_owned(), text.to_owned()));
+    let mut jsonpiler = Jsonpiler::new(false);
+    jsonpiler.set_source_provider(HashMap::from(files.clone()));
+    let args = ["jsonpiler", "interpret", "app/main.jspl"].map(String::from);
+    assert_eq!(jsonpiler.main(args).expect("failed to interpret in memory"), 12);
+    let main = files[0].1.clone();
+    let exe = Jsonpiler::new(false)
+      .compile_source("app/main.jspl", main, false, HashMap::from(files))
+      .expect("failed to compile in memory");
+    assert_eq!(exe.get(..2), Some(&b"MZ"[..]));
+  }
+  #[test]
+  fn syntax_module_parses_formats_and_reports_codes() {
+    use jsonpiler::{
+      api::DiagnosticCode,
+      syntax::{Value, format_jspl, parse_json, parse_jspl},
+    };
+    let tree = parse_jspl("main.jspl", "print(\"hi\", x)".to_owned()).expect("failed to parse");
+    let Value::Object(entries) = tree.val else { panic!("expected a block, got {tree:?}") };
+    let Value::Array(args) = &entries[0].1.val else { panic!("expected arguments") };
+    assert_eq!(entries[0].0.val, "print");
+    assert_eq!(args[0].val, Value::Str("hi".to_owned()));
+    assert_eq!((args[0].span.offset, args[0].span.size), (6, 4));
+    let Value::Object(var) = &args[1].val else { panic!("expected a variable") };
+    assert_eq!((var[0].0.val.as_str(), &var[0].1.val), ("$", &Value::Str("x".to_owned())));
+    let json = parse_json("data.json", "[1, 2.5, null]".to_owned()).expect("failed to parse");
+    let Value::Array(items) = json.val else { panic!("expected an array") };
+    assert_eq!(
+      items.into_iter().map(|item| item.val).collect::<Vec<_>>(),
+      [Value::Int(1), Value::Float(2.5), Value::Null]
+    );
+    let error = parse_jspl("main.jspl", "print(\"hi)".to_owned()).expect_err("must not parse");
+    let location = (error.code, error.line, error.column);
+    assert_eq!(location, (DiagnosticCode::UnexpectedToken, 1, 11), "{error:?}");
+    let formatted = format_jspl("main.jspl", "let(x=1)\nprint( Str(x) )".to_owned());
+    assert_eq!(formatted.expect("failed to format"), "let(x=1); print(Str(x))\n");
+  }
+  #[cfg(all(any(target_os = "windows", target_os = "linux"), target_arch = "x86_64"))]
+  #[test]
+  fn embedder_builtins_generate_code_and_report_errors() {
+    use jsonpiler::{
+      Jsonpiler,
+      api::DiagnosticCode,
+      extension::{ArgType, Arity, Cond, Flags, Op, Reg},
+    };
+    use std::collections::HashMap;
+    let register = |jsonpiler: &mut Jsonpiler| {
+      jsonpiler
+        .add_builtin("mul_add", Arity::Exact(3), Flags::COMMON, "`a * b + c`", |call| {
+          call.arg_of(Reg::Rax, ArgType::Int)?;
+          call.arg_of(Reg::Rcx, ArgType::Int)?;
+          call.arg_of(Reg::Rdx, ArgType::Int)?;
+          call.emit(Op::Mul(Reg::Rax, Reg::Rcx))?;
+          call.emit(Op::Add(Reg::Rax, Reg::Rdx))?;
+          call.int(Reg::Rax)
+        })
+        .expect("failed to register mul_add");
+      jsonpiler
+        .add_builtin("is_even", Arity::Exact(1), Flags::new(false, true), "", |call| {
+          if call.arg(Reg::Rax)? != ArgType::Int {
+            return Err(call.error("`is_even` takes an Int"));
+          }
+          call.emit(Op::MovImm(Reg::Rcx, 1))?;
+          call.emit(Op::And(Reg::Rax, Reg::Rcx))?;
+          call.emit(Op::Xor(Reg::Rcx, Reg::Rcx))?;
+          call.emit(Op::Cmp(Reg::Rax, Reg::Rcx))?;
+          call.emit(Op::Set(Cond::Eq, Reg::Rax))?;
+          call.bool(Reg::Rax)
+        })
+        .expect("failed to register is_even");
+      jsonpiler
+        .add_builtin("add2", Arity::Exact(2), Flags::new(false, true), "", |call| {
+          call.arg_of(Reg::Rax, ArgType::Int)?;
+          call.arg_of(Reg::Rcx, ArgType::Int)?;
+          call.emit(Op::Add(Reg::Rax, Reg::Rcx))?;
+          call.int(Reg::Rax)
+        })
+        .expect("failed to register add2");
+    };
+    let (examples_dir, _dir) = copied_examples_dir();
+    let file = examples_dir.join("builtins.jspl");
+    let source = "define(id, { n: Int }, Int, n * 1)\nlet(x = mul_add(6, 7, 0))\nlet(y = 1)\n\
+      if(is_even(x), { y = x })\nadd2(y - 8, id(8))";
+    fs::write(&file, source).expect("failed to write builtins.jspl");
+    for release in [false, true] {
+      let mut args = vec!["jsonpiler".to_owned()];
+      if release {
+        args.push("release".to_owned());
+      }
+      if cfg!(target_os = "linux") {
+        args.extend(["--target", "x86_64-linux"].map(String::from));
+      }
+      args.push(file.to_string_lossy().to_string());
+      let mut jsonpiler = Jsonpiler::new(false);
+      register(&mut jsonpiler);
+      assert_eq!(jsonpiler.main(args).expect("failed to run"), 42, "release: {release}");
+    }
+    let mut jsonpiler = Jsonpiler::new(false);
+    assert!(
+      jsonpiler
+        .add_builtin("print", Arity::Exact(1), Flags::COMMON, "", |call| Ok(call.null()))
+        .is_err()
+    );
+    register(&mut jsonpiler);
+    let errors = jsonpiler
+      .compile_source("main.jspl", "is_even(\"two\")".to_owned(), false, HashMap::new())
+      .expect_err("a Str argument must be rejected");
+    assert_eq!(
+      (errors[0].code, errors[0].message.as_str()),
+      (DiagnosticCode::ExtensionError, "`is_even` takes an Int")
+    );
+  }
+  #[test]
+  fn source_map_covers_calls_and_is_embedded_in_debug_builds() {
+    use jsonpiler::Jsonpiler;
+    fn field<'a>(line: &'a str, key: &str) -> Option<&'a str> {
+      line.trim().strip_prefix(&format!("\"{key}\": ")).map(|val| val.trim_end_matches(','))
+    }
+    let (examples_dir, _dir) = copied_examples_dir();
+    let source = examples_dir.join("fib.jspl").to_string_lossy().to_string();
+    for release in [false, true] {
+      let mut args = vec!["jsonpiler", "build", "--source-map"];
+      if release {
+        args.push("release");
+      }
+      args.push(&source);
+      Jsonpiler::new(false).main(args.into_iter().map(String::from)).expect("failed to build");
+      let map = fs::read_to_string(examples_dir.join("fib.map.json")).expect("missing source map");
+      let lines = map.lines().collect::<Vec<_>>();
+      let mut prev_end = 0;
+      let mut locations = vec![];
+      for (idx, line) in lines.iter().enumerate() {
+        let Some(start_field) = field(line, "start") else { continue };
+        let start = start_field.parse::<u32>().expect("start");
+        let end = field(lines[idx + 1], "end").expect("end").parse::<u32>().expect("end");
+        assert!(prev_end <= start && start < end, "overlapping range {start:#x}..{end:#x}");
+        prev_end = end;
+        locations.push(field(lines[idx + 2], "location").expect("location").trim_matches('"'));
+      }
+      for expected in ["fib.jspl:15:1", "fib.jspl:3:25", "fib.jspl:11:3"] {
+        assert!(locations.contains(&expected), "{expected} missing from {locations:?}");
+      }
+      let exe = fs::read(examples_dir.join("fib.exe")).expect("missing PE output");
+      let embedded = exe.windows(14).any(|window| window == b"fib.jspl:15:1\0");
+      assert_eq!(embedded, !release, "locations are embedded only in debug builds");
+    }
+  }
+  #[test]
+  fn pdb_names_functions_and_maps_lines() {
+    use jsonpiler::Jsonpiler;
+    fn u32_at(bytes: &[u8], offset: usize) -> u32 {
+      u32::from_le_bytes(bytes[offset..offset + 4].try_into().expect("u32 field"))
+    }
+    fn u16_at(bytes: &[u8], offset: usize) -> usize {
+      usize::from(u16::from_le_bytes([bytes[offset], bytes[offset + 1]]))
+    }
+    let (examples_dir, _dir) = copied_examples_dir();
+    let source = examples_dir.join("fib.jspl").to_string_lossy().to_string();
+    let args = ["jsonpiler", "build", "--pdb", &source].map(String::from);
+    Jsonpiler::new(false).main(args).expect("failed to build with --pdb");
+    let pdb = fs::read(examples_dir.join("fib.pdb")).expect("missing PDB");
+    assert!(pdb.starts_with(b"Microsoft C/C++ MSF 7.00\r\n\x1aDS\0\0\0"), "bad MSF magic");
+    let block_size = u32_at(&pdb, 32) as usize;
+    let block = |idx: u32| &pdb[idx as usize * block_size..(idx as usize + 1) * block_size];
+    let dir_bytes = u32_at(&pdb, 44) as usize;
+    let dir_map = block(u32_at(&pdb, 52));
+    let mut directory = vec![];
+    for idx in 0..dir_bytes.div_ceil(block_size) {
+      directory.extend_from_slice(block(u32_at(dir_map, idx * 4)));
+    }
+    let stream_count = u32_at(&directory, 0) as usize;
+    let mut cursor = 4 + stream_count * 4;
+    let mut streams = vec![];
+    for idx in 0..stream_count {
+      let size = u32_at(&directory, 4 + idx * 4) as usize;
+      let mut stream = vec![];
+      for _ in 0..size.div_ceil(block_size) {
+        stream.extend_from_slice(block(u32_at(&directory, cursor)));
+        cursor += 4;
+      }
+      stream.truncate(size);
+      streams.push(stream);
+    }
+    let exe = fs::read(examples_dir.join("fib.exe")).expect("missing PE output");
+    let rsds = exe.windows(4).position(|window| window == b"RSDS").expect("missing RSDS record");
+    assert_eq!(streams[1][12..28], exe[rsds + 4..rsds + 20], "PDB GUID must match the image");
+    let dbi = &streams[3];
+    assert_eq!(u32_at(dbi, 4), 19_990_903, "unexpected DBI version");
+    let module = &streams[u16_at(dbi, 98)];
+    let (sym_size, c13_size) = (u32_at(dbi, 100) as usize, u32_at(dbi, 108) as usize);
+    let mut procs = vec![];
+    let mut offset = 4;
+    while offset < sym_size {
+      if u16_at(module, offset + 2) == 0x1110 {
+        let name_start = offset + 39;
+        let name_len = module[name_start..].iter().position(|byte| *byte == 0).expect("name");
+        let name = String::from_utf8_lossy(&module[name_start..name_start + name_len]).to_string();
+        procs.push((name, u32_at(module, offset + 32)));
+      }
+      offset += u16_at(module, offset) + 2;
+    }
+    let mut lines = vec![];
+    while offset < sym_size + c13_size {
+      let len = u32_at(module, offset + 4) as usize;
+      if u32_at(module, offset) == 0xF2 {
+        let func_offset = u32_at(module, offset + 8);
+        let count = u32_at(module, offset + 24) as usize;
+        for idx in 0..count {
+          lines.push((func_offset, u32_at(module, offset + 32 + idx * 8 + 4) & 0xFF_FFFF));
+        }
+      }
+      offset += 8 + len.next_multiple_of(4);
+    }
+    for name in ["main", "fib_recursive", "fib_loop"] {
+      assert!(procs.iter().any(|(proc, _)| proc == name), "{name} missing from {procs:?}");
+    }
+    for (name, line) in [("main", 15), ("fib_recursive", 3), ("fib_loop", 11)] {
+      let (_, func_offset) = procs.iter().find(|(proc, _)| proc == name).expect("proc");
+      assert!(lines.contains(&(*func_offset, line)), "{name} does not map to line {line}");
+    }
+  }
+  #[test]
+  fn emit_obj_accepts_immediate_stores_to_section_start() {
+    use jsonpiler::Jsonpiler;
+    let (examples_dir, _dir) = copied_examples_dir();
+    for file in ["ping_pong.jspl", "gui_julia_mouse.jspl"] {
+      let source = examples_dir.join(file).to_string_lossy().to_string();
+      let args = ["jsonpiler", "--emit", "obj", &source].map(String::from);
+      Jsonpiler::new(false).main(args).expect("failed to emit object");
+      assert!(examples_dir.join(file).with_extension("obj").exists(), "missing object for {file}");
+    }
+  }
+  #[test]
+  fn emit_obj_writes_coff_with_exports_and_import_relocations() {
+    use jsonpiler::Jsonpiler;
+    let (examples_dir, _dir) = copied_examples_dir();
+    let source = examples_dir.join("fib.jspl").to_string_lossy().to_string();
+    let args = ["jsonpiler", "--emit", "obj", &source].map(String::from);
+    let code = Jsonpiler::new(false).main(args).expect("failed to emit fib.obj");
+    assert_eq!(code, 0);
+    let obj = fs::read(examples_dir.join("fib.obj")).expect("missing COFF output");
+    let u16_at = |offset: usize| u16::from_le_bytes([obj[offset], obj[offset + 1]]);
+    let u32_at =
+      |offset: usize| u32::from_le_bytes(obj[offset..offset + 4].try_into().expect("u32 field"));
+    assert_eq!(u16_at(0), 0x8664, "x64 machine expected");
+    assert_eq!(u16_at(2), 6, "unexpected number of sections");
+    let symbol_table = u32_at(8) as usize;
+    let symbol_count = u32_at(12) as usize;
+    let strings = symbol_table + symbol_count * 18;
+    let name = |symbol: usize| {
+      let field = &obj[symbol..symbol + 8];
+      let bytes = if field[..4] == [0; 4] {
+        let start = strings + u32_at(symbol + 4) as usize;
+        let len = obj[start..].iter().position(|&byte| byte == 0).expect("terminated name");
+        &obj[start..start + len]
+      } else {
+        let len = field.iter().position(|&byte| byte == 0).unwrap_or(8);
+        &field[..len]
+      };
+      String::from_utf8_lossy(bytes).to_string()
+    };
+    let mut externals = vec![];
+    let mut idx = 0;
+    while idx < symbol_count {
+      let symbol = symbol_table + idx * 18;
+      if obj[symbol + 16] == 2 {
+        externals.push((name(symbol), u16_at(symbol + 12)));
+      }
+      idx += 1 + usize::from(obj[symbol + 17]);
+    }
+    for export in ["fib_loop", "fib_recursive"] {
+      assert!(externals.contains(&(export.to_owned(), 1)), "missing export {export}");
+    }
+    assert!(externals.contains(&("__imp_ExitProcess".to_owned(), 0)), "missing import symbol");
+    let text_relocs = u32_at(20 + 24) as usize;
+    for reloc in 0..usize::from(u16_at(20 + 32)) {
+      let target = u32_at(text_relocs + reloc * 10 + 4) as usize;
+      assert!(target < symbol_count, "relocation points outside of the symbol table");
+    }
+  }
+  #[test]
+  fn emit_obj_exports_resolve_to_thunks() {
+    use jsonpiler::Jsonpiler;
+    let (examples_dir, _dir) = copied_examples_dir();
+    let emit = |source: &str| {
+      let path = examples_dir.join("scale.jspl");
+      fs::write(&path, source).expect("failed to write source");
+      let args = ["jsonpiler", "--emit", "obj", &path.to_string_lossy()].map(String::from);
+      Jsonpiler::new(false).main(args).map(|_| fs::read(path.with_extension("obj")))
+    };
+    let obj = emit("define(scale, { n: Int; x: Float }, Float, x * Float(n))\nexport(scale)")
+      .expect("failed to emit scale.obj")
+      .expect("missing COFF output");
+    let u16_at = |offset: usize| u16::from_le_bytes([obj[offset], obj[offset + 1]]);
+    let u32_at =
+      |offset: usize| u32::from_le_bytes(obj[offset..offset + 4].try_into().expect("u32 field"));
+    let text = u32_at(20 + 20) as usize;
+    let symbol_table = u32_at(8) as usize;
+    let strings = symbol_table + u32_at(12) as usize * 18;
+    let mut symbols = vec![];
+    let mut idx = 0;
+    while idx < u32_at(12) as usize {
+      let symbol = symbol_table + idx * 18;
+      let name = if obj[symbol..symbol + 4] == [0; 4] {
+        &obj[strings + u32_at(symbol + 4) as usize..]
+      } else {
+        &obj[symbol..symbol + 8]
+      };
+      let len = name.iter().position(|&byte| byte == 0).unwrap_or(name.len());
+      symbols.push((&name[..len], u32_at(symbol + 8) as usize, u16_at(symbol + 12)));
+      idx += 1 + usize::from(obj[symbol + 17]);
+    }
+    let code = |name: &[u8]| {
+      let (_, value, section) =
+        symbols.iter().find(|(symbol, ..)| *symbol == name).expect("missing export");
+      assert_eq!(*section, 1, "exports must be defined in .text");
+      let start = text + value;
+      let call = obj[start..].iter().position(|&byte| byte == 0xE8).expect("thunk calls");
+      &obj[start..start + call]
+    };
+    let scale = code(b"scale");
+    assert!(scale.windows(4).any(|bytes| bytes == [0x48, 0x89, 0x4D, 0x10]), "rcx must be homed");
+    assert!(
+      scale.windows(5).any(|bytes| bytes == [0x66, 0x48, 0x0F, 0x7E, 0xC8]),
+      "`x` must be read from xmm1"
+    );
+    assert!(scale.windows(4).any(|bytes| bytes == [0x48, 0x89, 0x5D, 0xF8]), "rbx must be saved");
+    let init = code(b"jsonpiler_init");
+    assert!(init.windows(4).any(|bytes| bytes == [0x48, 0x89, 0x5D, 0xF8]), "rbx must be saved");
+    let err = emit("define(f, { s: Str }, Int, len(s))\nexport(f)").expect_err("heap parameter");
+    assert!(err.contains("Unsupported type"), "{err}");
+  }
+  #[test]
+  fn interpret_examples_and_check_exit_codes() {
+    let (examples_dir, _dir) = copied_examples_dir();
+    let cases = [
+      ("arithmetic.jspl", 9),
+      ("counter.jspl", 0),
+      ("global_and_local.jspl", 1000),
+      ("hello.jspl", 0),
+      ("import_and_assert.jspl", 11),
+      ("is_prime.jspl", 0),
+      ("lcm.jspl", 36),
+      ("or_nand_xor.jspl", 0),
+    ];
+    for (file, expected) in cases {
+      let source = examples_dir.join(file).to_string_lossy().to_string();
+      let args = ["jsonpiler", "interpret", &source].map(String::from);
+      let code = jsonpiler::Jsonpiler::new(false).main(args).expect("failed to interpret");
+      assert_eq!(code, expected, "unexpected interpreted exit code for {file}");
+    }
+  }
+  #[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+  #[test]
+  fn interpreted_and_compiled_results_agree() {
+    use std::process::{Command, Output};
+    let (examples_dir, _dir) = copied_examples_dir();
+    let run = |options: &[&str], file: &str| -> Output {
+      Command::new(env!("CARGO_BIN_EXE_jsonpiler"))
+        .args(options)
+        .arg(examples_dir.join(file))
+        .output()
+        .expect("failed to run jsonpiler")
+    };
+    for file in [
+      "arithmetic.jspl",
+      "counter.jspl",
+      "fib.jspl",
+      "global_and_local.jspl",
+      "import_and_assert.jspl",
+      "is_prime.jspl",
+      "lcm.jspl",
+      "or_nand_xor.jspl",
+    ] {
+      let interpreted = run(&["interpret"], file);
+      let compiled = run(&["--target", "x86_64-linux"], file);
+      assert_eq!(interpreted.stdout, compiled.stdout, "stdout differs for {file}");
+      let code = interpreted.status.code().expect("interpreter exit code");
+      assert_eq!(Some(code & 0xFF), compiled.status.code(), "exit code differs for {file}");
+    }
+  }
+  #[cfg(not(all(any(target_os = "windows", target_os = "linux"), target_arch = "x86_64")))]
   #[test]
   fn skip_on_unsupported_platform() {
-    eprintln!("These tests require Windows x64 and are skipped on this target.");
+    eprintln!("These tests require Windows x64 or Linux x64 and are skipped on this target.");
   }
 }